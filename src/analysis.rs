@@ -0,0 +1,289 @@
+use std::fmt;
+
+use crate::board::Board;
+use crate::engine::{self, EngineConfig, MATE_SCORE};
+use crate::field::{ChessError, Color, Move};
+use crate::pgn::{to_san, PgnGame};
+use crate::transposition::TranspositionTable;
+
+//centipawns lost by a move from which it is judged
+const INACCURACY: i32 = 50;
+const MISTAKE: i32 = 100;
+const BLUNDER: i32 = 300;
+//won or lost positions aren't told apart beyond this
+const SCORE_CAP: i32 = 1500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Judgment {
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Judgment {
+    fn from_loss(loss: i32) -> Option<Judgment> {
+        if loss >= BLUNDER {
+            Some(Judgment::Blunder)
+        } else if loss >= MISTAKE {
+            Some(Judgment::Mistake)
+        } else if loss >= INACCURACY {
+            Some(Judgment::Inaccuracy)
+        } else {
+            None
+        }
+    }
+
+    //numeric annotation glyph: $6 dubious, $2 poor and $4 very poor move
+    pub fn nag(self) -> u8 {
+        match self {
+            Judgment::Inaccuracy => 6,
+            Judgment::Mistake => 2,
+            Judgment::Blunder => 4,
+        }
+    }
+}
+
+impl fmt::Display for Judgment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Judgment::Inaccuracy => write!(f, "Inaccuracy"),
+            Judgment::Mistake => write!(f, "Mistake"),
+            Judgment::Blunder => write!(f, "Blunder"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveAnalysis {
+    pub color: Color,
+    pub played: Move,
+    pub best_move: Option<Move>,
+    //centipawns from the view of the moving player
+    pub best_score: i32,
+    pub played_score: i32,
+    pub judgment: Option<Judgment>,
+}
+
+impl MoveAnalysis {
+    pub fn loss(&self) -> i32 {
+        (self.best_score.clamp(-SCORE_CAP, SCORE_CAP)
+            - self.played_score.clamp(-SCORE_CAP, SCORE_CAP))
+        .max(0)
+    }
+
+    //0 to 100 from the drop of the winning chances, like the common online accuracy
+    pub fn accuracy(&self) -> f64 {
+        let drop = win_chance(self.best_score) - win_chance(self.played_score);
+        (103.1668 * (-0.04354 * drop.max(0.0)).exp() - 3.1669).clamp(0.0, 100.0)
+    }
+}
+
+//winning chances in percent for a score in centipawns
+fn win_chance(score: i32) -> f64 {
+    let score = score.clamp(-SCORE_CAP, SCORE_CAP) as f64;
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * score).exp()) - 1.0)
+}
+
+//scores from the view of white, mates as "#moves"
+fn format_score(score: i32) -> String {
+    if score.abs() > MATE_SCORE - 1000 {
+        let moves = (MATE_SCORE - score.abs() + 1) / 2;
+        format!("#{}{}", if score < 0 { "-" } else { "" }, moves)
+    } else {
+        format!("{:+.2}", score as f64 / 100.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlayerSummary {
+    pub color: Color,
+    pub moves: usize,
+    pub average_loss: f64,
+    pub accuracy: f64,
+    pub inaccuracies: usize,
+    pub mistakes: usize,
+    pub blunders: usize,
+}
+
+impl fmt::Display for PlayerSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: accuracy {:.1}%, average loss {:.0} cp, {} inaccuracies, {} mistakes, {} blunders",
+            self.color,
+            self.accuracy,
+            self.average_loss,
+            self.inaccuracies,
+            self.mistakes,
+            self.blunders
+        )
+    }
+}
+
+pub struct GameAnalysis {
+    start: Board,
+    pub moves: Vec<MoveAnalysis>,
+    depth: u32,
+}
+
+impl GameAnalysis {
+    //every position of the game is searched, the played move is judged by the search of the next one
+    pub fn new(game: &PgnGame, config: &EngineConfig) -> Result<GameAnalysis, ChessError> {
+        let start = game.start_board()?;
+        let mut table = TranspositionTable::new(config.hash_mb);
+        let mut board = start;
+        let mut positions = vec![GameAnalysis::search(&board, config, &mut table)];
+        for next_move in &game.moves {
+            if !board.legal_moves().contains(next_move) {
+                return Err(ChessError::InvalidPgn(format!(
+                    "illegal move {}",
+                    next_move
+                )));
+            }
+            board.make_move(*next_move);
+            positions.push(GameAnalysis::search(&board, config, &mut table));
+        }
+
+        let mut board = start;
+        let mut moves = Vec::new();
+        for (i, played) in game.moves.iter().enumerate() {
+            let (best_move, best_score) = positions[i];
+            let played_score = -positions[i + 1].1;
+            //the deeper look at the played move can beat the search of the position
+            let best_score = if best_move == Some(*played) {
+                played_score
+            } else {
+                best_score.max(played_score)
+            };
+            let mut analysis = MoveAnalysis {
+                color: board.active_player(),
+                played: *played,
+                best_move,
+                best_score,
+                played_score,
+                judgment: None,
+            };
+            analysis.judgment = Judgment::from_loss(analysis.loss());
+            moves.push(analysis);
+            board.make_move(*played);
+        }
+        Ok(GameAnalysis {
+            start,
+            moves,
+            depth: config.depth,
+        })
+    }
+
+    fn search(
+        board: &Board,
+        config: &EngineConfig,
+        table: &mut TranspositionTable,
+    ) -> (Option<Move>, i32) {
+        if board.legal_moves().is_empty() {
+            let score = if board.is_in_check(board.active_player()) {
+                -MATE_SCORE
+            } else {
+                0
+            };
+            return (None, score);
+        }
+        let report = engine::search(board, config, table);
+        (report.best_move, report.score)
+    }
+
+    pub fn summary(&self, color: Color) -> PlayerSummary {
+        let moves: Vec<&MoveAnalysis> = self.moves.iter().filter(|c| c.color == color).collect();
+        let count = |judgment: Judgment| {
+            moves
+                .iter()
+                .filter(|c| c.judgment == Some(judgment))
+                .count()
+        };
+        let total = moves.len().max(1) as f64;
+        PlayerSummary {
+            color,
+            moves: moves.len(),
+            average_loss: moves.iter().fold(0.0, |sum, c| sum + c.loss() as f64) / total,
+            accuracy: if moves.is_empty() {
+                100.0
+            } else {
+                moves.iter().fold(0.0, |sum, c| sum + c.accuracy()) / total
+            },
+            inaccuracies: count(Judgment::Inaccuracy),
+            mistakes: count(Judgment::Mistake),
+            blunders: count(Judgment::Blunder),
+        }
+    }
+
+    //the judged moves with the better alternative, scores from the view of white
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let mut board = self.start;
+        for analysis in &self.moves {
+            if let Some(judgment) = analysis.judgment {
+                let number = if board.active_player() == Color::White {
+                    format!("{}.", board.fullmove_number())
+                } else {
+                    format!("{}...", board.fullmove_number())
+                };
+                report.push_str(&format!(
+                    "{} {} {}: {}",
+                    number,
+                    to_san(&board, analysis.played),
+                    format_score(self.white_view(analysis, analysis.played_score)),
+                    judgment
+                ));
+                if let Some(best_move) = analysis.best_move {
+                    report.push_str(&format!(
+                        ", {} was better ({})",
+                        to_san(&board, best_move),
+                        format_score(self.white_view(analysis, analysis.best_score))
+                    ));
+                }
+                report.push('\n');
+            }
+            board.make_move(analysis.played);
+        }
+        for color in [Color::White, Color::Black] {
+            report.push_str(&format!("{}\n", self.summary(color)));
+        }
+        report
+    }
+
+    fn white_view(&self, analysis: &MoveAnalysis, score: i32) -> i32 {
+        if analysis.color == Color::White {
+            score
+        } else {
+            -score
+        }
+    }
+
+    //the score after every move as comment, judged moves get a glyph and the better move
+    pub fn annotate(&self, game: &mut PgnGame) {
+        let mut board = self.start;
+        for (i, analysis) in self.moves.iter().enumerate() {
+            let mut comment = format_score(self.white_view(analysis, analysis.played_score));
+            if let Some(judgment) = analysis.judgment {
+                game.nags.push((i, judgment.nag()));
+                comment.push_str(&format!(" {}", judgment));
+                if let Some(best_move) = analysis.best_move {
+                    comment.push_str(&format!(
+                        ", {} was better ({})",
+                        to_san(&board, best_move),
+                        format_score(self.white_view(analysis, analysis.best_score))
+                    ));
+                }
+            }
+            game.move_comments.push((i, comment));
+            board.make_move(analysis.played);
+        }
+        game.set_tag("Annotator", &format!("Builtin depth {}", self.depth));
+        for color in [Color::White, Color::Black] {
+            let summary = self.summary(color);
+            game.set_tag(
+                &format!("{}Accuracy", color),
+                &format!("{:.1}", summary.accuracy),
+            );
+        }
+    }
+}
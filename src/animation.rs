@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::fs;
+
+use crate::board::Board;
+use crate::diagram::{self, DiagramOptions};
+use crate::field::{ChessError, Color, Move};
+use crate::pgn::to_san;
+
+//colors of the diagrams first, the rest of the GIF palette is a color cube
+const PALETTE_COLORS: [[u8; 3]; 8] = [
+    [255, 255, 255],
+    [0, 0, 0],
+    [240, 217, 181],
+    [181, 136, 99],
+    [248, 226, 120],
+    [218, 186, 79],
+    [40, 40, 40],
+    [60, 60, 60],
+];
+const CODE_LIMIT: u16 = 4096;
+
+//one position of the replay
+pub struct Frame {
+    pub board: Board,
+    pub last_move: Option<Move>,
+    pub caption: Option<String>,
+}
+
+pub struct AnimationOptions {
+    pub diagram: DiagramOptions,
+    //milliseconds each position is shown
+    pub delay: u32,
+    pub captions: bool,
+}
+
+impl AnimationOptions {
+    //"delay <ms>", "nocaptions" and the diagram options
+    pub fn parse(tokens: &[&str]) -> Result<AnimationOptions, ChessError> {
+        let usage = || {
+            ChessError::InvalidCommand(String::from(
+                "animation <file.gif|file.svg> [delay <ms>] [nocaptions] [size <pixels>] [flip] [nocoords]",
+            ))
+        };
+        let mut options = AnimationOptions {
+            diagram: DiagramOptions::new(),
+            delay: 1000,
+            captions: true,
+        };
+        let mut rest = Vec::new();
+        let mut tokens = tokens.iter();
+        while let Some(token) = tokens.next() {
+            match *token {
+                "delay" => {
+                    options.delay = tokens
+                        .next()
+                        .and_then(|c| c.parse().ok())
+                        .filter(|c| *c >= 10)
+                        .ok_or_else(usage)?
+                }
+                "nocaptions" => options.captions = false,
+                _ => rest.push(*token),
+            }
+        }
+        options.diagram = DiagramOptions::parse(&rest).map_err(|_| usage())?;
+        Ok(options)
+    }
+
+    //the diagram of a frame with its last move highlighted
+    fn diagram(&self, frame: &Frame) -> DiagramOptions {
+        let mut options = DiagramOptions {
+            arrows: self.diagram.arrows.clone(),
+            highlights: self.diagram.highlights.clone(),
+            caption: None,
+            ..self.diagram
+        };
+        if let Some(last) = frame.last_move {
+            options.highlights.push(last.from);
+            options.highlights.push(last.to);
+        }
+        if self.captions {
+            options.caption = Some(frame.caption.clone().unwrap_or_default());
+        }
+        options
+    }
+}
+
+//the positions of a game, the boards hold one position more than the moves
+pub fn frames(boards: &[Board], moves: &[Move]) -> Vec<Frame> {
+    boards
+        .iter()
+        .enumerate()
+        .map(|(i, board)| {
+            let played = i.checked_sub(1).map(|c| (&boards[c], moves[c]));
+            Frame {
+                board: *board,
+                last_move: played.map(|c| c.1),
+                caption: played.map(|(before, next_move)| {
+                    let dots = if before.active_player() == Color::White {
+                        "."
+                    } else {
+                        "..."
+                    };
+                    format!(
+                        "{}{} {}",
+                        before.fullmove_number(),
+                        dots,
+                        to_san(before, next_move)
+                    )
+                }),
+            }
+        })
+        .collect()
+}
+
+//every frame is a group which is only visible during its part of the loop
+pub fn svg(frames: &[Frame], options: &AnimationOptions) -> String {
+    let total = frames.len().max(1);
+    let duration = options.delay as f32 * total as f32 / 1000.0;
+    let mut text = diagram::svg_header(&options.diagram(&Frame {
+        board: Board::new(),
+        last_move: None,
+        caption: None,
+    }));
+    for (i, frame) in frames.iter().enumerate() {
+        let (start, end) = (i as f32 / total as f32, (i + 1) as f32 / total as f32);
+        let values = if i + 1 == total {
+            format!("values=\"hidden;visible\" keyTimes=\"0;{:.4}\"", start)
+        } else {
+            format!(
+                "values=\"hidden;visible;hidden\" keyTimes=\"0;{:.4};{:.4}\"",
+                start, end
+            )
+        };
+        text.push_str(&format!(
+            "<g visibility=\"hidden\">\n<animate attributeName=\"visibility\" {} dur=\"{}s\" calcMode=\"discrete\" repeatCount=\"indefinite\"/>\n",
+            values, duration
+        ));
+        text.push_str(&diagram::svg_elements(
+            &frame.board,
+            &options.diagram(frame),
+        ));
+        text.push_str("</g>\n");
+    }
+    text.push_str("</svg>\n");
+    text
+}
+
+fn palette() -> Vec<[u8; 3]> {
+    let mut colors = PALETTE_COLORS.to_vec();
+    for r in 0..6 {
+        for g in 0..6 {
+            for b in 0..6 {
+                colors.push([r * 51, g * 51, b * 51]);
+            }
+        }
+    }
+    //grays fill up the 256 colors
+    let grays = 256 - colors.len();
+    for i in 0..grays {
+        colors.push([((i + 1) * 255 / (grays + 1)) as u8; 3]);
+    }
+    colors
+}
+
+fn nearest(palette: &[[u8; 3]], color: [u8; 3]) -> u8 {
+    let distance =
+        |c: &[u8; 3]| -> i32 { (0..3).map(|i| (c[i] as i32 - color[i] as i32).pow(2)).sum() };
+    (0..palette.len())
+        .min_by_key(|i| distance(&palette[*i]))
+        .unwrap_or(0) as u8
+}
+
+//variable length codes, packed from the lowest bit
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+//LZW with 8 bit colors, the table starts again when it is full
+pub fn lzw(indexes: &[u8]) -> Vec<u8> {
+    let (clear, end) = (256u16, 257u16);
+    let mut writer = BitWriter {
+        bytes: Vec::new(),
+        buffer: 0,
+        bits: 0,
+    };
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let (mut next, mut size) = (258u16, 9u32);
+    writer.write(clear, size);
+    let mut prefix = match indexes.first() {
+        Some(c) => *c as u16,
+        None => {
+            writer.write(end, size);
+            return writer.finish();
+        }
+    };
+    for index in &indexes[1..] {
+        if let Some(code) = table.get(&(prefix, *index)) {
+            prefix = *code;
+            continue;
+        }
+        writer.write(prefix, size);
+        if next < CODE_LIMIT {
+            if next >= 1 << size && size < 12 {
+                size += 1;
+            }
+            table.insert((prefix, *index), next);
+            next += 1;
+        } else {
+            writer.write(clear, size);
+            table.clear();
+            next = 258;
+            size = 9;
+        }
+        prefix = *index as u16;
+    }
+    writer.write(prefix, size);
+    if next >= 1 << size && size < 12 {
+        size += 1;
+    }
+    writer.write(end, size);
+    writer.finish()
+}
+
+//looping GIF89a with one image per frame
+pub fn gif(frames: &[Frame], options: &AnimationOptions) -> Vec<u8> {
+    let colors = palette();
+    let mut cache: HashMap<[u8; 3], u8> = HashMap::new();
+    let mut data = b"GIF89a".to_vec();
+    let mut header_written = false;
+    for frame in frames {
+        let raster = diagram::raster(&frame.board, &options.diagram(frame));
+        if !header_written {
+            data.extend((raster.width as u16).to_le_bytes());
+            data.extend((raster.height as u16).to_le_bytes());
+            //global color table of 256 colors
+            data.extend([0xf7, 0, 0]);
+            data.extend(colors.iter().flatten());
+            data.extend([0x21, 0xff, 0x0b]);
+            data.extend(b"NETSCAPE2.0");
+            data.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+            header_written = true;
+        }
+        //delay in hundredths of a second
+        let delay = (options.delay / 10) as u16;
+        data.extend([0x21, 0xf9, 0x04, 0x04]);
+        data.extend(delay.to_le_bytes());
+        data.extend([0x00, 0x00]);
+        data.push(0x2c);
+        data.extend([0, 0, 0, 0]);
+        data.extend((raster.width as u16).to_le_bytes());
+        data.extend((raster.height as u16).to_le_bytes());
+        data.push(0);
+        let indexes: Vec<u8> = raster
+            .pixels
+            .iter()
+            .map(|c| *cache.entry(*c).or_insert_with(|| nearest(&colors, *c)))
+            .collect();
+        data.push(8);
+        for block in lzw(&indexes).chunks(255) {
+            data.push(block.len() as u8);
+            data.extend_from_slice(block);
+        }
+        data.push(0);
+    }
+    data.push(0x3b);
+    data
+}
+
+//the format is chosen by the file ending
+pub fn save(frames: &[Frame], options: &AnimationOptions, path: &str) -> Result<(), ChessError> {
+    let lower = path.to_lowercase();
+    let data = if lower.ends_with(".svg") {
+        svg(frames, options).into_bytes()
+    } else if lower.ends_with(".gif") {
+        gif(frames, options)
+    } else {
+        return Err(ChessError::FileError(format!(
+            "{} needs the ending .gif or .svg",
+            path
+        )));
+    };
+    fs::write(path, data).map_err(|e| ChessError::FileError(e.to_string()))
+}
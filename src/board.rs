@@ -1,6 +1,8 @@
 use std::fmt::{self, Display};
 
-use crate::field::{ChessError, Color, Field, FieldType};
+use crate::field::{ChessError, Color, Field, FieldType, Move, Outcome};
+use crate::square::{File, Rank, Square};
+use crate::zobrist::POLYGLOT_KEYS;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Board {
@@ -10,435 +12,445 @@ pub struct Board {
     white_can_small_castle: bool,
     black_can_large_castle: bool,
     black_can_small_castle: bool,
-    en_passant_pos: (i32, i32),
+    //files of the castling towers for white small, white large, black small and black large
+    castle_towers: [File; 4],
+    //castling is written as the king taking its own tower
+    chess960: bool,
+    //taken figures go to the pocket of the taker and can be dropped back
+    crazyhouse: bool,
+    //pawns, knights, bishops, towers and queens of white and black
+    pockets: [[i32; 5]; 2],
+    //fields of promoted figures, they go back to the pocket as pawns
+    promoted: u64,
+    en_passant_pos: Option<Square>,
+    halfmove_clock: i32,
+    fullmove_number: i32,
+    //Zobrist key kept up to date by the moves and the setters
+    key: u64,
 }
 
+//what make_move changed besides the moved figures, unmake_move puts it back
+#[derive(Clone, Copy, Debug)]
+pub struct Undo {
+    next_move: Move,
+    moved: Field,
+    //en passant takes a pawn next to the target field
+    captured: Field,
+    captured_on: Square,
+    //Some(true) for small and Some(false) for large castling
+    castling: Option<bool>,
+    castling_rights: [bool; 4],
+    en_passant_pos: Option<Square>,
+    halfmove_clock: i32,
+    fullmove_number: i32,
+    pockets: [[i32; 5]; 2],
+    promoted: u64,
+    key: u64,
+}
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+const KNIGHT_STEPS: [(i32, i32); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_STEPS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const TOWER_DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+//figures in a pocket in the order of Board::pockets
+const POCKET_FIGURES: [FieldType; 5] = [
+    FieldType::Pawn,
+    FieldType::Knight,
+    FieldType::Bishop,
+    FieldType::Tower,
+    FieldType::Queen,
+];
+const PROMOTIONS: [FieldType; 4] = [
+    FieldType::Queen,
+    FieldType::Tower,
+    FieldType::Bishop,
+    FieldType::Knight,
+];
+
 impl Board {
     pub fn new() -> Board {
-        Board {
+        let mut board = Board {
             state: [Field::new(); 64],
             active_player: Color::White,
             white_can_large_castle: true,
             white_can_small_castle: true,
             black_can_large_castle: true,
             black_can_small_castle: true,
-            en_passant_pos: (0, 0),
-        }
-    }
-
-    pub fn is_king_attacked(
-        &mut self,
-        color: Color,
-        from_x: i32,
-        from_y: i32,
-        to_x: i32,
-        to_y: i32,
-    ) -> bool {
-        let mut result = false;
-        let old_state = self.state;
-        self.finalize_move(from_x, from_y, to_x, to_y);
-        let king = self.get_king(color);
-        match king {
-            Some(k) => {
-                for field in self
-                    .state
-                    .into_iter()
-                    .filter(|c| c.get_color() == color.enemy_color())
-                {
-                    match self.validate_move(
-                        field.get_x(),
-                        field.get_y(),
-                        k.get_x(),
-                        k.get_y(),
-                        field.get_color(),
-                    ) {
-                        Ok(_) => {
-                            //king can be attacked by any figure other than pawns moving straigt
-                            if !(field.get_type() == FieldType::Pawn && field.get_y() == k.get_y())
-                            {
-                                result = true;
-                            }
-                        }
-                        Err(_) => {
-                            //special case: pawns can threaten a king even when self.validate_move fails
-                            if field.get_type() == FieldType::Pawn
-                                && (field.get_y() == k.get_y() + 1
-                                    || field.get_y() == k.get_y() - 1)
-                            {
-                                if (field.get_color() == Color::White
-                                    && field.get_x() == k.get_x() - 1)
-                                    || (field.get_color() == Color::Black
-                                        && field.get_x() == k.get_x() + 1)
-                                {
-                                    result = true;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            None => {}
-        }
-        //return to previous state
-        self.state = old_state;
-        result
+            castle_towers: [File::H, File::A, File::H, File::A],
+            chess960: false,
+            crazyhouse: false,
+            pockets: [[0; 5]; 2],
+            promoted: 0,
+            en_passant_pos: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            key: 0,
+        };
+        board.key = board.position_key(!0);
+        board
     }
 
-    fn get_king(&self, king_color: Color) -> Option<Field> {
-        self.state
-            .into_iter()
-            .filter(|x| x.get_type() == FieldType::King && x.get_color() == king_color)
-            .last()
+    pub fn get_king(&self, king_color: Color) -> Option<Square> {
+        Square::all().rev().find(|c| {
+            let field = self.get(*c);
+            field.get_type() == FieldType::King && field.get_color() == king_color
+        })
     }
 
-    pub fn validate_move(
-        &self,
-        from_x: i32,
-        from_y: i32,
-        to_x: i32,
-        to_y: i32,
-        color: Color,
-    ) -> Result<(), ChessError> {
-        let figure = self.get(from_x, from_y);
+    pub fn validate_move(&self, from: Square, to: Square, color: Color) -> Result<(), ChessError> {
+        let figure = self.get(from);
 
         //not the current player's figure
         if figure.get_color() != color {
             return Err(ChessError::FigureWrongColor(figure.get_type()));
         }
         //empty board validation
-        figure.validate_move(to_x, to_y)?;
+        figure.validate_move(from, to)?;
 
         match figure.get_type() {
-            FieldType::King => {
-                let target_field = self.get(to_x, to_y);
+            FieldType::King | FieldType::Knight => {
+                let target_field = self.get(to);
                 if target_field.get_color() == figure.get_color() {
-                    return Err(ChessError::FieldAlreadyOwned(to_x, to_y));
+                    return Err(ChessError::FieldAlreadyOwned(to));
                 }
             }
-            FieldType::Queen => {
-                //queen moves like a tower
-                if (figure.get_x() == to_x) || (figure.get_y() == to_y) {
-                    if figure.get_x() != to_x {
-                        let step_x = if figure.get_x() < to_x { 1 } else { -1 };
-                        let steps = if figure.get_x() - to_x > 0 {
-                            figure.get_x() - to_x + 1
-                        } else {
-                            to_x - figure.get_x() + 1
-                        };
-                        for n in 1..steps {
-                            let next_field = self.get(figure.get_x() + step_x * n, to_y);
-                            if next_field.get_type() != FieldType::None {
-                                //blocked by owned figure
-                                if next_field.get_color() == figure.get_color() {
-                                    return Err(ChessError::FieldAlreadyOwned(
-                                        next_field.get_x(),
-                                        next_field.get_y(),
-                                    ));
-                                }
-                                //blocked by enemy figure but not the target
-                                if next_field.get_x() != to_x {
-                                    return Err(ChessError::MoveBlockedByEnemyFigure(
-                                        next_field.get_x(),
-                                        to_y,
-                                    ));
-                                }
-                            }
-                        }
-                    } else {
-                        let step_y = if figure.get_y() < to_y { 1 } else { -1 };
-                        let steps = if figure.get_y() - to_y > 0 {
-                            figure.get_y() - to_y + 1
-                        } else {
-                            to_y - figure.get_y() + 1
-                        };
-                        for n in 1..steps {
-                            let next_field = self.get(to_x, figure.get_y() + step_y * n);
-                            if next_field.get_type() != FieldType::None {
-                                //blocked by owned figure
-                                if next_field.get_color() == figure.get_color() {
-                                    return Err(ChessError::FieldAlreadyOwned(
-                                        next_field.get_x(),
-                                        next_field.get_y(),
-                                    ));
-                                }
-                                //blocked by enemy figure but not the target
-                                if next_field.get_y() != to_y {
-                                    return Err(ChessError::MoveBlockedByEnemyFigure(
-                                        to_x,
-                                        next_field.get_y(),
-                                    ));
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    //queen moves like a bishop
-                    let step_x = if figure.get_x() < to_x { 1 } else { -1 };
-                    let step_y = if figure.get_y() < to_y { 1 } else { -1 };
-                    let steps = if figure.get_x() - to_x > 0 {
-                        figure.get_x() - to_x + 1
-                    } else {
-                        to_x - figure.get_x() + 1
-                    };
-                    for n in 1..steps {
-                        let next_field =
-                            self.get(figure.get_x() + step_x * n, figure.get_y() + step_y * n);
-                        if next_field.get_type() != FieldType::None {
-                            //blocked by owned figure
-                            if next_field.get_color() == figure.get_color() {
-                                return Err(ChessError::FieldAlreadyOwned(
-                                    next_field.get_x(),
-                                    next_field.get_y(),
-                                ));
-                            }
-                            //blocked by enemy figure but not the target
-                            if next_field.get_x() != to_x {
-                                return Err(ChessError::MoveBlockedByEnemyFigure(
-                                    next_field.get_x(),
-                                    next_field.get_y(),
-                                ));
-                            }
-                        }
-                    }
-                }
-            }
-            FieldType::Bishop => {
-                let step_x = if figure.get_x() < to_x { 1 } else { -1 };
-                let step_y = if figure.get_y() < to_y { 1 } else { -1 };
-                let steps = if figure.get_x() - to_x > 0 {
-                    figure.get_x() - to_x + 1
-                } else {
-                    to_x - figure.get_x() + 1
-                };
-                for n in 1..steps {
-                    let next_field =
-                        self.get(figure.get_x() + step_x * n, figure.get_y() + step_y * n);
+            FieldType::Queen | FieldType::Bishop | FieldType::Tower => {
+                //walk from the start to the target, the figure moves on a line
+                let step_x = (to.rank().number() - from.rank().number()).signum();
+                let step_y = (to.file().number() - from.file().number()).signum();
+                let mut next = from.offset(step_x, step_y);
+                while let Some(square) = next {
+                    let next_field = self.get(square);
                     if next_field.get_type() != FieldType::None {
                         //blocked by owned figure
                         if next_field.get_color() == figure.get_color() {
-                            return Err(ChessError::FieldAlreadyOwned(
-                                next_field.get_x(),
-                                next_field.get_y(),
-                            ));
+                            return Err(ChessError::FieldAlreadyOwned(square));
                         }
                         //blocked by enemy figure but not the target
-                        if next_field.get_x() != to_x {
-                            return Err(ChessError::MoveBlockedByEnemyFigure(
-                                next_field.get_x(),
-                                next_field.get_y(),
-                            ));
-                        }
-                    }
-                }
-            }
-            FieldType::Knight => {
-                let next_field = self.get(to_x, to_y);
-                //field already owned
-                if next_field.get_color() == figure.get_color() {
-                    return Err(ChessError::FieldAlreadyOwned(
-                        next_field.get_x(),
-                        next_field.get_y(),
-                    ));
-                }
-            }
-            FieldType::Tower => {
-                if figure.get_x() != to_x {
-                    let step_x = if figure.get_x() < to_x { 1 } else { -1 };
-                    let steps = if figure.get_x() - to_x > 0 {
-                        figure.get_x() - to_x + 1
-                    } else {
-                        to_x - figure.get_x() + 1
-                    };
-                    for n in 1..steps {
-                        let next_field = self.get(figure.get_x() + step_x * n, to_y);
-                        if next_field.get_type() != FieldType::None {
-                            //blocked by owned figure
-                            if next_field.get_color() == figure.get_color() {
-                                return Err(ChessError::FieldAlreadyOwned(
-                                    next_field.get_x(),
-                                    next_field.get_y(),
-                                ));
-                            }
-                            //blocked by enemy figure but not the target
-                            if next_field.get_x() != to_x {
-                                return Err(ChessError::MoveBlockedByEnemyFigure(
-                                    next_field.get_x(),
-                                    to_y,
-                                ));
-                            }
+                        if square != to {
+                            return Err(ChessError::MoveBlockedByEnemyFigure(square));
                         }
                     }
-                } else {
-                    let step_y = if figure.get_y() < to_y { 1 } else { -1 };
-                    let steps = if figure.get_y() - to_y > 0 {
-                        figure.get_y() - to_y + 1
-                    } else {
-                        to_y - figure.get_y() + 1
-                    };
-                    for n in 1..steps {
-                        let next_field = self.get(to_x, figure.get_y() + step_y * n);
-                        if next_field.get_type() != FieldType::None {
-                            //blocked by owned figure
-                            if next_field.get_color() == figure.get_color() {
-                                return Err(ChessError::FieldAlreadyOwned(
-                                    next_field.get_x(),
-                                    next_field.get_y(),
-                                ));
-                            }
-                            //blocked by enemy figure but not the target
-                            if next_field.get_y() != to_y {
-                                return Err(ChessError::MoveBlockedByEnemyFigure(
-                                    to_x,
-                                    next_field.get_y(),
-                                ));
-                            }
-                        }
+                    if square == to {
+                        break;
                     }
+                    next = square.offset(step_x, step_y);
                 }
             }
             FieldType::Pawn => {
                 //pawn doesn't take another figure
-                if figure.get_y() == to_y {
+                if from.file() == to.file() {
                     //target free
-                    if self.get(to_x, to_y).get_type() != FieldType::None {
-                        return Err(ChessError::PawnMoveBlocked(to_x, to_y));
+                    if self.get(to).get_type() != FieldType::None {
+                        return Err(ChessError::PawnMoveBlocked(to));
                     }
-                    //white pawn move by 2
-                    if to_x - figure.get_x() == 2 {
-                        if self.get(to_x - 1, to_y).get_type() != FieldType::None {
-                            return Err(ChessError::PawnMoveBlocked(to_x - 1, to_y));
-                        }
-                    }
-                    //black pawn moveby 2
-                    if figure.get_x() - to_x == 2 {
-                        if self.get(to_x + 1, to_y).get_type() != FieldType::None {
-                            return Err(ChessError::PawnMoveBlocked(to_x + 1, to_y));
+                    //pawn move by 2, the passed field is free
+                    let distance = to.rank().number() - from.rank().number();
+                    if distance.abs() == 2 {
+                        if let Some(passed) = from.offset(distance / 2, 0) {
+                            if self.get(passed).get_type() != FieldType::None {
+                                return Err(ChessError::PawnMoveBlocked(passed));
+                            }
                         }
                     }
                 } else {
                     //pawn takes another figure
-                    let target_field = self.get(to_x, to_y);
+                    let target_field = self.get(to);
                     //pawn can't take empty field
                     if target_field.get_type() == FieldType::None {
                         //unless it's en passant
-                        if (target_field.get_x(), target_field.get_y()) != self.en_passant_pos {
+                        if Some(to) != self.en_passant_pos {
                             return Err(ChessError::PawnCantTakeEmptyField);
                         }
                     }
                     //field already owned
                     if target_field.get_color() == figure.get_color() {
-                        return Err(ChessError::FieldAlreadyOwned(
-                            figure.get_x(),
-                            figure.get_y(),
-                        ));
+                        return Err(ChessError::FieldAlreadyOwned(from));
                     }
                 }
             }
             FieldType::None => {
                 //entry in state found but no figure there
-                return Err(ChessError::EmptyStartingField(from_x, from_y));
+                return Err(ChessError::EmptyStartingField(from));
             }
         }
         Ok(())
     }
 
-    fn get(&self, x: i32, y: i32) -> Field {
-        match self
-            .state
-            .into_iter()
-            .filter(|c| (c.get_x() == x) && (c.get_y() == y))
-            .last()
-        {
-            Some(c) => c,
-            None => Field::new(),
+    pub fn get(&self, square: Square) -> Field {
+        self.state[square.index()]
+    }
+
+    //the field the given number of ranks and files away, empty outside of the board
+    fn get_relative(&self, square: Square, ranks: i32, files: i32) -> Field {
+        square
+            .offset(ranks, files)
+            .map_or(Field::new(), |c| self.get(c))
+    }
+
+    //all fields with their figures, a1 first
+    pub fn fields(&self) -> impl Iterator<Item = (Square, Field)> + '_ {
+        Square::all().map(|c| (c, self.get(c)))
+    }
+
+    fn back_rank(color: Color) -> Rank {
+        if color == Color::White {
+            Rank::First
+        } else {
+            Rank::Eighth
         }
     }
-    pub fn finalize_move(
+
+    //promotion: figure a pawn reaching the last rank turns into, None asks the user
+    fn finalize_move_with_promotion(
         &mut self,
-        from_x: i32,
-        from_y: i32,
-        to_x: i32,
-        to_y: i32,
-    ) -> Option<String> {
-        //for en passant cleanup we need to know the figure type early
-        let moved_figure = self.get(from_x, from_y);
-        let last_enpassant_pos = self.en_passant_pos;
-        let mut response: Option<String> = None;
-        for field in self.state.as_mut() {
-            //previous en passant
-            if (to_x, to_y) == last_enpassant_pos && moved_figure.get_type() == FieldType::Pawn {
-                if field.get_color() != moved_figure.get_color()
-                    && field.get_type() == FieldType::Pawn
-                {
-                    if field.get_color() == Color::White
-                        && (field.get_x(), field.get_y()) == (to_x + 1, to_y)
-                    {
-                        field.set_empty_with_pos(field.get_x(), field.get_y());
-                    }
-                    if field.get_color() == Color::Black
-                        && (field.get_x(), field.get_y()) == (to_x - 1, to_y)
-                    {
-                        field.set_empty_with_pos(field.get_x(), field.get_y());
-                    }
-                }
+        from: Square,
+        to: Square,
+        promotion: Option<FieldType>,
+    ) {
+        let mut field = self.get(from);
+
+        //previous en passant, the taken pawn stands next to the moved one
+        if Some(to) == self.en_passant_pos
+            && field.get_type() == FieldType::Pawn
+            && from.file() != to.file()
+        {
+            let taken = Square::new(to.file(), from.rank());
+            if self.get(taken).get_type() == FieldType::Pawn
+                && self.get(taken).get_color() != field.get_color()
+            {
+                self.state[taken.index()].set_empty();
             }
+        }
 
-            //cleanup taken figure
-            if field.get_x() == to_x && field.get_y() == to_y {
-                field.set_empty_with_pos(from_x, from_y);
-            } else if field.get_x() == from_x && field.get_y() == from_y {
-                //moved figure
-                field.finalize_move(to_x, to_y);
+        //a tower taken on its starting field can't castle anymore
+        self.disable_castling_on(to);
 
-                field.check_promote_pawn();
+        field.check_promote_pawn(to, promotion);
 
-                //disable castling on King move
-                if field.get_type() == FieldType::King {
-                    if field.get_color() == Color::White {
-                        self.white_can_large_castle = false;
-                        self.white_can_small_castle = false;
-                    }
-                    if field.get_color() == Color::Black {
-                        self.black_can_large_castle = false;
-                        self.black_can_small_castle = false;
-                    }
-                }
-                //disable castling on Tower move
-                if field.get_type() == FieldType::Tower {
-                    match (from_x, from_y) {
-                        (1, 8) => self.white_can_small_castle = false,
-                        (1, 1) => self.white_can_large_castle = false,
-                        (8, 8) => self.black_can_small_castle = false,
-                        (8, 1) => self.black_can_large_castle = false,
-                        _ => {}
-                    }
-                }
+        //disable castling on King move
+        if field.get_type() == FieldType::King {
+            if field.get_color() == Color::White {
+                self.white_can_large_castle = false;
+                self.white_can_small_castle = false;
+            }
+            if field.get_color() == Color::Black {
+                self.black_can_large_castle = false;
+                self.black_can_small_castle = false;
+            }
+        }
+        //disable castling on Tower move
+        if field.get_type() == FieldType::Tower {
+            self.disable_castling_on(from);
+        }
 
-                //new en passant
-                self.en_passant_pos = (0, 0);
-                if field.get_type() == FieldType::Pawn {
-                    if to_x - from_x == 2 {
-                        //white pawn moved by 2
-                        self.en_passant_pos = (3, field.get_y())
-                    } else if from_x - to_x == 2 {
-                        //black pawn moved by 2
-                        self.en_passant_pos = (6, field.get_y())
-                    }
-                }
+        //new en passant on the field the pawn passed
+        self.en_passant_pos = None;
+        let distance = to.rank().number() - from.rank().number();
+        if field.get_type() == FieldType::Pawn && distance.abs() == 2 {
+            self.en_passant_pos = from.offset(distance / 2, 0);
+        }
 
-                response = Some(format!(
-                    "{} moved {} from {}{} to {}{}",
-                    self.active_player,
-                    field.get_type(),
-                    Field::y_to_letter(&from_y),
-                    from_x,
-                    Field::y_to_letter(&field.get_y()),
-                    field.get_x()
-                ));
+        //cleanup taken figure
+        self.state[to.index()] = field;
+        self.state[from.index()].set_empty();
+    }
+
+    fn disable_castling_on(&mut self, square: Square) {
+        let towers = self.castle_towers;
+        if square == Square::new(towers[0], Rank::First) {
+            self.white_can_small_castle = false;
+        }
+        if square == Square::new(towers[1], Rank::First) {
+            self.white_can_large_castle = false;
+        }
+        if square == Square::new(towers[2], Rank::Eighth) {
+            self.black_can_small_castle = false;
+        }
+        if square == Square::new(towers[3], Rank::Eighth) {
+            self.black_can_large_castle = false;
+        }
+    }
+
+    //keeps the Zobrist key up to date while the figures on the given fields change
+    fn update_key(&mut self, squares: u64, change: impl FnOnce(&mut Board)) {
+        self.key ^= self.position_key(squares);
+        change(self);
+        self.key ^= self.position_key(squares);
+    }
+
+    fn castle_index(color: Color, small: bool) -> usize {
+        match (color, small) {
+            (Color::White, true) => 0,
+            (Color::White, false) => 1,
+            (_, true) => 2,
+            (_, false) => 3,
+        }
+    }
+
+    fn castling_rights(&self) -> [bool; 4] {
+        [
+            self.white_can_small_castle,
+            self.white_can_large_castle,
+            self.black_can_small_castle,
+            self.black_can_large_castle,
+        ]
+    }
+
+    fn set_castling_rights(&mut self, rights: [bool; 4]) {
+        [
+            self.white_can_small_castle,
+            self.white_can_large_castle,
+            self.black_can_small_castle,
+            self.black_can_large_castle,
+        ] = rights;
+    }
+
+    fn can_castle(&self, color: Color, small: bool) -> bool {
+        self.castling_rights()[Board::castle_index(color, small)]
+    }
+
+    //Some(true) for small and Some(false) for large castling, given as the king taking its
+    //own tower or moving two files
+    pub fn castling_side(&self, next_move: Move) -> Option<bool> {
+        let (from, to) = (next_move.from, next_move.to);
+        let king = self.get(from);
+        let target = self.get(to);
+        if king.get_type() != FieldType::King || from.rank() != to.rank() {
+            return None;
+        }
+        if (target.get_type() == FieldType::Tower && target.get_color() == king.get_color())
+            || (to.file().number() - from.file().number()).abs() == 2
+        {
+            return Some(to.file() > from.file());
+        }
+        None
+    }
+
+    //the castling move as it is generated, Chess960 boards let the king take its tower
+    pub fn castling_move(&self, color: Color, small: bool) -> Option<Move> {
+        let rank = Board::back_rank(color);
+        let king = self.get_king(color).filter(|c| c.rank() == rank)?;
+        let to_file = if self.chess960 {
+            self.castle_towers[Board::castle_index(color, small)]
+        } else if small {
+            File::G
+        } else {
+            File::C
+        };
+        Some(Move::new(king, Square::new(to_file, rank)))
+    }
+
+    //whatever the start files, the king ends on the g or c file and the tower next to it
+    fn check_castling(&self, color: Color, small: bool) -> Result<(), ChessError> {
+        let rank = Board::back_rank(color);
+        let king_file = match self.get_king(color) {
+            Some(k) if k.rank() == rank && (self.chess960 || k.file() == File::E) => k.file(),
+            _ => return Err(ChessError::CastlingNoPossibleAlreadyMoved),
+        };
+        let tower_file = self.castle_towers[Board::castle_index(color, small)];
+        let tower = self.get(Square::new(tower_file, rank));
+        if !self.can_castle(color, small)
+            || tower.get_type() != FieldType::Tower
+            || tower.get_color() != color
+        {
+            return Err(ChessError::CastlingNoPossibleAlreadyMoved);
+        }
+        let (king_to, tower_to) = if small {
+            (File::G, File::F)
+        } else {
+            (File::C, File::D)
+        };
+        //figures blocking the path, the king and the tower don't block each other
+        let files = [king_file, king_to, tower_file, tower_to];
+        let (first, last) = (
+            files.iter().min().copied().unwrap_or(king_file),
+            files.iter().max().copied().unwrap_or(king_file),
+        );
+        for file in File::ALL.into_iter().filter(|c| (first..=last).contains(c)) {
+            let square = Square::new(file, rank);
+            if file != king_file
+                && file != tower_file
+                && self.get(square).get_type() != FieldType::None
+            {
+                return Err(ChessError::CastlingBlockedByFigure(square));
+            }
+        }
+        //castling isn't allowed when king is attacked
+        let attacked = self.attack_map(color.enemy_color());
+        let path = king_file.min(king_to)..=king_file.max(king_to);
+        for file in File::ALL.into_iter().filter(|c| path.contains(c)) {
+            let square = Square::new(file, rank);
+            if attacked & square.bit() != 0 {
+                return Err(ChessError::CastlingNotPossibleWhenKingUnderAttack(square));
             }
         }
+        Ok(())
+    }
 
-        response
+    //the tower, then the fields of king and tower after castling
+    fn castling_squares(&self, color: Color, small: bool) -> (Square, Square, Square) {
+        let rank = Board::back_rank(color);
+        let tower = Square::new(self.castle_towers[Board::castle_index(color, small)], rank);
+        let (king_to, tower_to) = if small {
+            (File::G, File::F)
+        } else {
+            (File::C, File::D)
+        };
+        (
+            tower,
+            Square::new(king_to, rank),
+            Square::new(tower_to, rank),
+        )
+    }
+
+    fn castling_message(&self, color: Color, king: Square, small: bool) -> String {
+        let (tower, king_to, tower_to) = self.castling_squares(color, small);
+        let side = if small { "King" } else { "Queen" };
+        format!(
+            "{} {} side castling: Moved King from {} to {} and Tower from {} to {}",
+            color, side, king, king_to, tower, tower_to
+        )
+    }
+
+    //moves king and tower to their castling fields
+    fn castle(&mut self, king: Square, small: bool) {
+        let color = self.get(king).get_color();
+        let (tower, king_to, tower_to) = self.castling_squares(color, small);
+        let (king_field, tower_field) = (self.get(king), self.get(tower));
+        for square in [king, tower] {
+            self.state[square.index()].set_empty();
+        }
+        self.state[king_to.index()] = king_field;
+        self.state[tower_to.index()] = tower_field;
+        if color == Color::White {
+            self.white_can_small_castle = false;
+            self.white_can_large_castle = false;
+        } else {
+            self.black_can_small_castle = false;
+            self.black_can_large_castle = false;
+        }
+        self.en_passant_pos = None;
     }
 
     pub fn castling(
@@ -446,190 +458,1201 @@ impl Board {
         castle_type_str: &str,
         check_only: bool,
     ) -> Result<bool, ChessError> {
-        let x = if self.active_player == Color::White {
-            1
+        let small = match castle_type_str.to_lowercase().trim() {
+            "o-o" => true,
+            "o-o-o" => false,
+            _ => {
+                return Ok(false);
+            } //failure
+        };
+        let color = self.active_player;
+        self.check_castling(color, small)?;
+        if !check_only {
+            if let Some(king) = self.get_king(color) {
+                println!("{}", self.castling_message(color, king, small));
+                let (tower, king_to, tower_to) = self.castling_squares(color, small);
+                let squares = king.bit() | tower.bit() | king_to.bit() | tower_to.bit();
+                self.update_key(squares, |board| board.castle(king, small));
+            }
+        }
+        Ok(true) //success
+    }
+
+    pub fn set_board_empty(&mut self) {
+        self.active_player = Color::White;
+        self.white_can_small_castle = true;
+        self.white_can_large_castle = true;
+        self.black_can_small_castle = true;
+        self.black_can_large_castle = true;
+        self.castle_towers = [File::H, File::A, File::H, File::A];
+        self.chess960 = false;
+        self.crazyhouse = false;
+        self.pockets = [[0; 5]; 2];
+        self.promoted = 0;
+        self.en_passant_pos = None;
+        self.halfmove_clock = 0;
+        self.fullmove_number = 1;
+
+        for field in &mut self.state {
+            field.set_empty();
+        }
+        self.key = self.position_key(!0);
+    }
+
+    pub fn set_board_start(&mut self) {
+        self.set_board_empty();
+        let back_rank = [
+            FieldType::Tower,
+            FieldType::Knight,
+            FieldType::Bishop,
+            FieldType::Queen,
+            FieldType::King,
+            FieldType::Bishop,
+            FieldType::Knight,
+            FieldType::Tower,
+        ];
+        for (file, figure_type) in File::ALL.into_iter().zip(back_rank) {
+            for (rank, figure_type, color) in [
+                (Rank::First, figure_type, Color::White),
+                (Rank::Second, FieldType::Pawn, Color::White),
+                (Rank::Seventh, FieldType::Pawn, Color::Black),
+                (Rank::Eighth, figure_type, Color::Black),
+            ] {
+                self.set_figure(Square::new(file, rank), figure_type, color);
+            }
+        }
+    }
+
+    //start position number 0 to 959 of Chess960, 518 is the standard start position
+    pub fn set_board_960(&mut self, number: u32) -> Result<(), ChessError> {
+        if number > 959 {
+            return Err(ChessError::InvalidCommand(format!(
+                "Chess960 start position {} is not in 0 to 959",
+                number
+            )));
+        }
+        let mut back_rank = [FieldType::None; 8];
+        let mut n = number as usize;
+        //bishops on a light and a dark field
+        back_rank[2 * (n % 4) + 1] = FieldType::Bishop;
+        n /= 4;
+        back_rank[2 * (n % 4)] = FieldType::Bishop;
+        n /= 4;
+        //the other figures fill the empty fields from the a file on
+        let empty = |back_rank: &[FieldType; 8], i: usize| {
+            back_rank
+                .iter()
+                .enumerate()
+                .filter(|c| *c.1 == FieldType::None)
+                .nth(i)
+                .map(|c| c.0)
+                .unwrap_or(0)
+        };
+        let queen = empty(&back_rank, n % 6);
+        back_rank[queen] = FieldType::Queen;
+        n /= 6;
+        let (first, second) = [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ][n];
+        let (first, second) = (empty(&back_rank, first), empty(&back_rank, second));
+        back_rank[first] = FieldType::Knight;
+        back_rank[second] = FieldType::Knight;
+        for figure_type in [FieldType::Tower, FieldType::King, FieldType::Tower] {
+            let i = empty(&back_rank, 0);
+            back_rank[i] = figure_type;
+        }
+
+        self.set_board_start();
+        for (file, figure_type) in File::ALL.into_iter().zip(back_rank) {
+            for (rank, color) in [(Rank::First, Color::White), (Rank::Eighth, Color::Black)] {
+                self.set_figure(Square::new(file, rank), figure_type, color);
+            }
+        }
+        let towers: Vec<File> = File::ALL
+            .into_iter()
+            .zip(back_rank)
+            .filter(|c| c.1 == FieldType::Tower)
+            .map(|c| c.0)
+            .collect();
+        self.castle_towers = [towers[1], towers[0], towers[1], towers[0]];
+        self.chess960 = true;
+        Ok(())
+    }
+
+    //takes a figure off the board, a tower on its starting field loses its castling
+    pub fn remove_figure(&mut self, square: Square) {
+        self.update_key(square.bit(), |board| {
+            board.state[square.index()].set_empty();
+            board.disable_castling_on(square);
+        });
+    }
+
+    pub fn clear_en_passant(&mut self) {
+        self.update_key(0, |board| board.en_passant_pos = None);
+    }
+
+    //puts a figure on the field, whatever stood there is replaced
+    pub fn set_figure(&mut self, square: Square, figure_type: FieldType, color: Color) {
+        self.update_key(square.bit(), |board| {
+            board.state[square.index()].set_figure(figure_type, color)
+        });
+    }
+
+    //field behind a pawn which just moved two fields
+    pub fn set_en_passant(&mut self, square: Square) {
+        self.update_key(0, |board| board.en_passant_pos = Some(square));
+    }
+
+    //castling takes the outermost tower next to the king like KQkq in a FEN
+    pub fn set_castling(&mut self, color: Color, small: bool, allowed: bool) {
+        let index = Board::castle_index(color, small);
+        if allowed {
+            let king_file = self.get_king(color).map_or(File::E, |k| k.file());
+            self.castle_towers[index] = self
+                .outer_tower(color, king_file, small)
+                .unwrap_or(if small { File::H } else { File::A });
+        }
+        let mut rights = self.castling_rights();
+        rights[index] = allowed;
+        self.update_key(0, |board| board.set_castling_rights(rights));
+    }
+
+    //a set up position can be played: one king each, no pawns on the first and last row,
+    //the side not to move isn't in check and castling and en passant fit the figures
+    pub fn validate_position(&self) -> Result<(), ChessError> {
+        let error = |text: String| Err(ChessError::InvalidPosition(text));
+        for color in [Color::White, Color::Black] {
+            let kings = self
+                .state
+                .iter()
+                .filter(|c| c.get_type() == FieldType::King && c.get_color() == color)
+                .count();
+            if kings != 1 {
+                return error(format!("{} needs exactly one king, not {}", color, kings));
+            }
+        }
+        if let Some((square, _)) = self.fields().find(|(square, field)| {
+            field.get_type() == FieldType::Pawn
+                && (square.rank() == Rank::First || square.rank() == Rank::Eighth)
+        }) {
+            return error(format!("pawn on {}", square));
+        }
+        let waiting = self.active_player.enemy_color();
+        if self.is_in_check(waiting) {
+            return error(format!(
+                "{} is in check with {} to move",
+                waiting, self.active_player
+            ));
+        }
+        for (color, small, name) in [
+            (Color::White, true, 'K'),
+            (Color::White, false, 'Q'),
+            (Color::Black, true, 'k'),
+            (Color::Black, false, 'q'),
+        ] {
+            if !self.can_castle(color, small) {
+                continue;
+            }
+            let rank = Board::back_rank(color);
+            let tower_file = self.castle_towers[Board::castle_index(color, small)];
+            let tower = self.get(Square::new(tower_file, rank));
+            let king_placed = self
+                .get_king(color)
+                .is_some_and(|k| k.rank() == rank && (self.chess960 || k.file() == File::E));
+            if !king_placed || tower.get_type() != FieldType::Tower || tower.get_color() != color {
+                return error(format!(
+                    "castling {} needs the king and tower on their starting fields",
+                    name
+                ));
+            }
+        }
+        if let Some(square) = self.en_passant_pos {
+            //the pawn stands in front of the field, the fields it passed are empty
+            let (passed, pawn, start) = if self.active_player == Color::White {
+                (Rank::Sixth, Rank::Fifth, Rank::Seventh)
+            } else {
+                (Rank::Third, Rank::Fourth, Rank::Second)
+            };
+            let file = square.file();
+            let pawn = self.get(Square::new(file, pawn));
+            if square.rank() != passed
+                || pawn.get_type() != FieldType::Pawn
+                || pawn.get_color() != waiting
+                || self.get(square).get_type() != FieldType::None
+                || self.get(Square::new(file, start)).get_type() != FieldType::None
+            {
+                return error(format!(
+                    "en passant on {} needs a {} pawn which just moved two fields",
+                    square, waiting
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    //how many figures of the type the color can drop
+    pub fn pocket(&self, color: Color, figure_type: FieldType) -> i32 {
+        match POCKET_FIGURES.iter().position(|c| *c == figure_type) {
+            Some(i) => self.pockets[usize::from(color == Color::Black)][i],
+            None => 0,
+        }
+    }
+
+    fn add_to_pocket(&mut self, color: Color, figure_type: FieldType, count: i32) {
+        if let Some(i) = POCKET_FIGURES.iter().position(|c| *c == figure_type) {
+            self.pockets[usize::from(color == Color::Black)][i] += count;
+        }
+    }
+
+    //the taken figure goes to the pocket, promoted figures keep their mark when moving
+    fn update_pockets(&mut self, next_move: Move, figure: Field) {
+        let (from, to) = (next_move.from, next_move.to);
+        let taken_at = if self.annotate(next_move).flags.en_passant {
+            Square::new(to.file(), from.rank())
         } else {
-            8
+            to
         };
-        match castle_type_str.to_lowercase().trim() {
-            "o-o" => {
-                if (x == 1 && !self.white_can_small_castle)
-                    || (x == 8 && !self.black_can_small_castle)
-                {
-                    return Err(ChessError::CastlingNoPossibleAlreadyMoved);
+        let taken = self.get(taken_at);
+        if taken.get_color() == figure.get_color().enemy_color() {
+            let taken_type = if self.promoted & taken_at.bit() != 0 {
+                FieldType::Pawn
+            } else {
+                taken.get_type()
+            };
+            self.add_to_pocket(figure.get_color(), taken_type, 1);
+        }
+        let from = from.bit();
+        let was_promoted = self.promoted & from != 0;
+        self.promoted &= !(from | taken_at.bit());
+        self.promoted &= !to.bit();
+        if was_promoted {
+            self.promoted |= to.bit();
+        }
+    }
+
+    fn drop_figure(&mut self, figure_type: FieldType, square: Square) {
+        let color = self.active_player;
+        self.state[square.index()].set_figure(figure_type, color);
+        self.add_to_pocket(color, figure_type, -1);
+        self.en_passant_pos = None;
+    }
+
+    //the figures of a pocket as shown next to the board
+    pub fn pocket_line(&self, color: Color) -> String {
+        let mut figures = Vec::new();
+        for figure_type in POCKET_FIGURES {
+            let mut field = Field::new();
+            field.set_figure(figure_type, color);
+            for _ in 0..self.pocket(color, figure_type) {
+                figures.push(field.to_string());
+            }
+        }
+        format!("{} pocket: {}", color, figures.join(" "))
+    }
+
+    pub fn is_crazyhouse(&self) -> bool {
+        self.crazyhouse
+    }
+
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.chess960 = chess960;
+    }
+
+    pub fn active_player(&self) -> Color {
+        self.active_player
+    }
+
+    pub fn set_active_player(&mut self, active_player: Color) {
+        self.update_key(0, |board| board.active_player = active_player);
+    }
+
+    pub fn fullmove_number(&self) -> i32 {
+        self.fullmove_number
+    }
+
+    pub fn halfmove_clock(&self) -> i32 {
+        self.halfmove_clock
+    }
+
+    pub fn has_castling_rights(&self) -> bool {
+        self.white_can_small_castle
+            || self.white_can_large_castle
+            || self.black_can_small_castle
+            || self.black_can_large_castle
+    }
+
+    //fields of the figures of the given color which could take on the field,
+    //a pawn which just moved two fields can be taken en passant
+    pub fn attackers(&self, square: Square, by: Color) -> Vec<Square> {
+        let mut attackers = Vec::new();
+        let mut add = |from: Option<Square>, figure_types: &[FieldType]| {
+            if let Some(from) = from {
+                let field = self.get(from);
+                if field.get_color() == by && figure_types.contains(&field.get_type()) {
+                    attackers.push(from);
                 }
-                //figures blocking the path
-                for y in 6..8 {
-                    if self.get(x, y).get_type() != FieldType::None {
-                        return Err(ChessError::CastlingBlockedByFigure(x, y));
+            }
+        };
+        //pawns take diagonally forward
+        let pawn_step = if by == Color::White { -1 } else { 1 };
+        for step_y in [-1, 1] {
+            add(square.offset(pawn_step, step_y), &[FieldType::Pawn]);
+        }
+        let target = self.get(square);
+        let passed = self.en_passant_pos.and_then(|c| c.offset(pawn_step, 0));
+        if passed == Some(square) && target.get_color() == by.enemy_color() {
+            for step_y in [-1, 1] {
+                add(square.offset(0, step_y), &[FieldType::Pawn]);
+            }
+        }
+        for (steps, figure_type) in [
+            (KNIGHT_STEPS, FieldType::Knight),
+            (KING_STEPS, FieldType::King),
+        ] {
+            for (step_x, step_y) in steps {
+                add(square.offset(step_x, step_y), &[figure_type]);
+            }
+        }
+        for (directions, figure_type) in [
+            (TOWER_DIRECTIONS, FieldType::Tower),
+            (BISHOP_DIRECTIONS, FieldType::Bishop),
+        ] {
+            for (step_x, step_y) in directions {
+                let mut next = square.offset(step_x, step_y);
+                while let Some(next_square) = next {
+                    if self.get(next_square).get_type() != FieldType::None {
+                        add(Some(next_square), &[figure_type, FieldType::Queen]);
+                        break;
                     }
+                    next = next_square.offset(step_x, step_y);
                 }
-                //castling isn't allowed when king is attacked
-                for y in 5..7 {
-                    if self.is_king_attacked(self.active_player, x, 5, x, y) {
-                        return Err(ChessError::CastlingNotPossibleWhenKingUnderAttack(x, y));
+            }
+        }
+        attackers
+    }
+
+    pub fn is_square_attacked(&self, square: Square, by: Color) -> bool {
+        !self.attackers(square, by).is_empty()
+    }
+
+    //fields the figure on the square could take on, one bit per Square::index,
+    //including the ones of its own figures
+    pub fn attacks(&self, square: Square) -> u64 {
+        let field = self.get(square);
+        let pawn_step = if field.get_color() == Color::White {
+            1
+        } else {
+            -1
+        };
+        let (steps, slide): (&[(i32, i32)], bool) = match field.get_type() {
+            FieldType::Pawn => (&[(pawn_step, -1), (pawn_step, 1)], false),
+            FieldType::Knight => (&KNIGHT_STEPS, false),
+            FieldType::King => (&KING_STEPS, false),
+            FieldType::Bishop => (&BISHOP_DIRECTIONS, true),
+            FieldType::Tower => (&TOWER_DIRECTIONS, true),
+            FieldType::Queen => (&KING_STEPS, true),
+            FieldType::None => return 0,
+        };
+        let mut map = 0;
+        for (step_x, step_y) in steps {
+            let mut next = square.offset(*step_x, *step_y);
+            while let Some(next_square) = next {
+                map |= next_square.bit();
+                if !slide || self.get(next_square).get_type() != FieldType::None {
+                    break;
+                }
+                next = next_square.offset(*step_x, *step_y);
+            }
+        }
+        map
+    }
+
+    //every field a figure of the given color could take on, one bit per Square::index
+    pub fn attack_map(&self, by: Color) -> u64 {
+        let pawn_step = if by == Color::White { 1 } else { -1 };
+        let mut map = self
+            .fields()
+            .filter(|c| c.1.get_color() == by)
+            .fold(0, |map, (square, _)| map | self.attacks(square));
+        //the pawn which can be taken en passant
+        if let Some(passed) = self.en_passant_pos.and_then(|c| c.offset(-pawn_step, 0)) {
+            let beside = |step_y| {
+                let field = self.get_relative(passed, 0, step_y);
+                field.get_type() == FieldType::Pawn && field.get_color() == by
+            };
+            if self.get(passed).get_color() == by.enemy_color() && (beside(-1) || beside(1)) {
+                map |= passed.bit();
+            }
+        }
+        map
+    }
+
+    pub fn is_in_check(&self, color: Color) -> bool {
+        match self.get_king(color) {
+            Some(k) => self.is_square_attacked(k, color.enemy_color()),
+            None => false,
+        }
+    }
+
+    //plays a move without validation and hands the turn to the other player,
+    //castling is given as the king moving two files
+    pub fn make_move(&mut self, next_move: Move) -> Undo {
+        let figure = self.get(next_move.from);
+        let flags = self.annotate(next_move).flags;
+        let castling = self.castling_side(next_move).filter(|_| flags.castling);
+        let captured_on = if flags.en_passant {
+            Square::new(next_move.to.file(), next_move.from.rank())
+        } else {
+            next_move.to
+        };
+        let undo = Undo {
+            next_move,
+            moved: figure,
+            captured: if castling.is_some() {
+                Field::new()
+            } else {
+                self.get(captured_on)
+            },
+            captured_on,
+            castling,
+            castling_rights: self.castling_rights(),
+            en_passant_pos: self.en_passant_pos,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            pockets: self.pockets,
+            promoted: self.promoted,
+            key: self.key,
+        };
+        //only the fields the move touches change their figures
+        let mut squares = next_move.from.bit() | next_move.to.bit() | captured_on.bit();
+        if let Some(small) = castling {
+            let (tower, king_to, tower_to) = self.castling_squares(figure.get_color(), small);
+            squares |= tower.bit() | king_to.bit() | tower_to.bit();
+        }
+        self.key ^= self.position_key(squares);
+        if self.crazyhouse && next_move.drop.is_none() {
+            self.update_pockets(next_move, figure);
+        }
+        if let Some(figure_type) = next_move.drop {
+            self.drop_figure(figure_type, next_move.to);
+        } else if let Some(small) = castling {
+            self.castle(next_move.from, small);
+        } else {
+            self.finalize_move_with_promotion(next_move.from, next_move.to, next_move.promotion);
+        }
+
+        //interactive promotions of pawns are only known after the move
+        let moved = self.get(next_move.to);
+        if self.crazyhouse
+            && figure.get_type() == FieldType::Pawn
+            && moved.get_type() != FieldType::Pawn
+        {
+            self.promoted |= next_move.to.bit();
+        }
+
+        //the king taking its own tower is castling
+        if figure.get_type() == FieldType::Pawn
+            || next_move.drop == Some(FieldType::Pawn)
+            || flags.capture
+        {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.active_player == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.active_player = self.active_player.enemy_color();
+        self.key ^= self.position_key(squares);
+        undo
+    }
+
+    //takes back the last move made with make_move
+    pub fn unmake_move(&mut self, undo: Undo) {
+        let next_move = undo.next_move;
+        self.active_player = self.active_player.enemy_color();
+        if let Some(small) = undo.castling {
+            let color = undo.moved.get_color();
+            let (tower, king_to, tower_to) = self.castling_squares(color, small);
+            let tower_field = self.get(tower_to);
+            for square in [king_to, tower_to] {
+                self.state[square.index()].set_empty();
+            }
+            self.state[tower.index()] = tower_field;
+            self.state[next_move.from.index()] = undo.moved;
+        } else {
+            self.state[next_move.to.index()].set_empty();
+            if next_move.drop.is_none() {
+                self.state[next_move.from.index()] = undo.moved;
+            }
+            self.state[undo.captured_on.index()] = undo.captured;
+        }
+        self.set_castling_rights(undo.castling_rights);
+        self.en_passant_pos = undo.en_passant_pos;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+        self.pockets = undo.pockets;
+        self.promoted = undo.promoted;
+        self.key = undo.key;
+    }
+
+    //the move in words for the player, called right after make_move
+    pub fn describe_move(&self, undo: &Undo) -> String {
+        let next_move = undo.next_move;
+        let color = self.active_player.enemy_color();
+        if let Some(figure_type) = next_move.drop {
+            format!("{} dropped {} on {}", color, figure_type, next_move.to)
+        } else if let Some(small) = undo.castling {
+            self.castling_message(color, next_move.from, small)
+        } else {
+            format!(
+                "{} moved {} from {} to {}",
+                color,
+                self.get(next_move.to).get_type(),
+                next_move.from,
+                next_move.to
+            )
+        }
+    }
+
+    //enemy figures giving check to the player to move
+    pub fn checkers(&self) -> Vec<Square> {
+        let color = self.active_player;
+        self.get_king(color)
+            .map_or(Vec::new(), |k| self.attackers(k, color.enemy_color()))
+    }
+
+    //fields strictly between two fields on a line, empty when they aren't on one
+    fn between(from: Square, to: Square) -> u64 {
+        let ranks = to.rank().number() - from.rank().number();
+        let files = to.file().number() - from.file().number();
+        if ranks != 0 && files != 0 && ranks.abs() != files.abs() {
+            return 0;
+        }
+        let (step_x, step_y) = (ranks.signum(), files.signum());
+        let mut fields = 0;
+        let mut next = from.offset(step_x, step_y);
+        while let Some(square) = next.filter(|c| *c != to) {
+            fields |= square.bit();
+            next = square.offset(step_x, step_y);
+        }
+        fields
+    }
+
+    //own figures standing alone between the king and an enemy tower, bishop or queen,
+    //with the fields up to the pinning figure they may still move to
+    pub fn pins(&self) -> Vec<(Square, u64)> {
+        let color = self.active_player;
+        let mut pins = Vec::new();
+        let king = match self.get_king(color) {
+            Some(k) => k,
+            None => return pins,
+        };
+        for (directions, figure_type) in [
+            (TOWER_DIRECTIONS, FieldType::Tower),
+            (BISHOP_DIRECTIONS, FieldType::Bishop),
+        ] {
+            for (step_x, step_y) in directions {
+                let mut pinned = None;
+                let mut next = king.offset(step_x, step_y);
+                while let Some(square) = next {
+                    let field = self.get(square);
+                    if field.get_color() == color && pinned.is_none() {
+                        pinned = Some(square);
+                    } else if field.get_color() == color.enemy_color() {
+                        let pinning =
+                            field.get_type() == figure_type || field.get_type() == FieldType::Queen;
+                        if let Some(pinned) = pinned.filter(|_| pinning) {
+                            pins.push((pinned, Board::between(king, square) | square.bit()));
+                        }
+                        break;
+                    } else if field.get_type() != FieldType::None {
+                        break;
                     }
+                    next = square.offset(step_x, step_y);
                 }
-                if !check_only {
-                    self.finalize_move(x, 5, x, 7);
-                    self.finalize_move(x, 8, x, 6);
-                    println!("{} King side castling: Moved King from {}{} to {}{} and Tower from {}{} to {}{}", self.active_player, Field::y_to_letter(&5), x, Field::y_to_letter(&7), x, Field::y_to_letter(&8), x, Field::y_to_letter(&6), x)
+            }
+        }
+        pins
+    }
+
+    //checks and pins are looked at once, only castling and en passant are tried out
+    //as they move two figures, en passant can uncover a check along the rank
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let color = self.active_player;
+        let king = match self.get_king(color) {
+            Some(k) => k,
+            None => return self.pseudo_legal_moves(),
+        };
+        //fields which take the single checking figure or block its line
+        let stops = match self.checkers().as_slice() {
+            [] => u64::MAX,
+            [checker] => checker.bit() | Board::between(king, *checker),
+            _ => 0,
+        };
+        let pins = self.pins();
+        //the king can't step back along the line of a sliding checker
+        let mut without_king = *self;
+        without_king.state[king.index()].set_empty();
+        let king_danger = without_king.attack_map(color.enemy_color());
+        let mut next = *self;
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|m| {
+                if m.flags.castling || m.flags.en_passant {
+                    let undo = next.make_move(*m);
+                    let legal = !next.is_in_check(color);
+                    next.unmake_move(undo);
+                    return legal;
+                }
+                if m.drop.is_none() && m.from == king {
+                    return king_danger & m.to.bit() == 0;
+                }
+                stops & m.to.bit() != 0
+                    && !pins
+                        .iter()
+                        .any(|(pinned, ray)| *pinned == m.from && ray & m.to.bit() == 0)
+            })
+            .collect()
+    }
+
+    //moves following the figure rules without looking at the own king
+    pub fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let color = self.active_player;
+        let mut moves = Vec::new();
+        for (square, field) in self.fields().filter(|c| c.1.get_color() == color) {
+            match field.get_type() {
+                FieldType::Pawn => self.pawn_moves(square, color, &mut moves),
+                FieldType::Knight => self.step_moves(square, &KNIGHT_STEPS, &mut moves),
+                FieldType::King => {
+                    self.step_moves(square, &KING_STEPS, &mut moves);
+                    self.castling_moves(color, &mut moves);
+                }
+                FieldType::Bishop => self.slide_moves(square, &BISHOP_DIRECTIONS, &mut moves),
+                FieldType::Tower => self.slide_moves(square, &TOWER_DIRECTIONS, &mut moves),
+                FieldType::Queen => {
+                    self.slide_moves(square, &BISHOP_DIRECTIONS, &mut moves);
+                    self.slide_moves(square, &TOWER_DIRECTIONS, &mut moves);
                 }
+                FieldType::None => {}
+            }
+        }
+        if self.crazyhouse {
+            self.drop_moves(&mut moves);
+        }
+        moves.into_iter().map(|m| self.annotate(m)).collect()
+    }
+
+    //pawns aren't dropped on the first and the last row
+    fn drop_moves(&self, moves: &mut Vec<Move>) {
+        for figure_type in POCKET_FIGURES {
+            if self.pocket(self.active_player, figure_type) == 0 {
+                continue;
             }
-            "o-o-o" => {
-                if (x == 1 && !self.white_can_large_castle)
-                    || (x == 8 && !self.black_can_large_castle)
+            for (square, _) in self.fields().filter(|c| c.1.get_type() == FieldType::None) {
+                if figure_type == FieldType::Pawn
+                    && (square.rank() == Rank::First || square.rank() == Rank::Eighth)
                 {
-                    return Err(ChessError::CastlingNoPossibleAlreadyMoved);
+                    continue;
                 }
-                //figures blocking the path
-                for y in 2..5 {
-                    if self.get(x, y).get_type() != FieldType::None {
-                        return Err(ChessError::CastlingBlockedByFigure(x, y));
-                    }
+                moves.push(Move::new_drop(figure_type, square));
+            }
+        }
+    }
+
+    //the flags of a move follow from the position it is played in
+    pub fn annotate(&self, next_move: Move) -> Move {
+        let mut result = next_move;
+        if next_move.drop.is_some() {
+            return result;
+        }
+        let figure = self.get(next_move.from);
+        let target = self.get(next_move.to);
+        result.flags.castling = self.castling_side(next_move).is_some();
+        result.flags.en_passant = figure.get_type() == FieldType::Pawn
+            && next_move.from.file() != next_move.to.file()
+            && Some(next_move.to) == self.en_passant_pos;
+        result.flags.capture = result.flags.en_passant
+            || (!result.flags.castling && target.get_color() == figure.get_color().enemy_color());
+        result
+    }
+
+    fn pawn_moves(&self, from: Square, color: Color, moves: &mut Vec<Move>) {
+        let (step, start_rank, last_rank) = if color == Color::White {
+            (1, Rank::Second, Rank::Eighth)
+        } else {
+            (-1, Rank::Seventh, Rank::First)
+        };
+        let empty = |c: &Square| self.get(*c).get_type() == FieldType::None;
+        let mut targets = Vec::new();
+        if let Some(to) = from.offset(step, 0).filter(empty) {
+            targets.push(to);
+            if let Some(to) = from.offset(2 * step, 0).filter(empty) {
+                if from.rank() == start_rank {
+                    moves.push(Move::new(from, to));
                 }
-                //castling isn't allowed when king is attacked
-                for y in 3..6 {
-                    if self.is_king_attacked(self.active_player, x, 5, x, y) {
-                        return Err(ChessError::CastlingNotPossibleWhenKingUnderAttack(x, y));
-                    }
+            }
+        }
+        for step_y in [-1, 1] {
+            if let Some(to) = from.offset(step, step_y) {
+                if self.get(to).get_color() == color.enemy_color()
+                    || Some(to) == self.en_passant_pos
+                {
+                    targets.push(to);
+                }
+            }
+        }
+        for to in targets {
+            if to.rank() == last_rank {
+                for figure_type in PROMOTIONS {
+                    let mut promotion = Move::new(from, to);
+                    promotion.promotion = Some(figure_type);
+                    moves.push(promotion);
                 }
+            } else {
+                moves.push(Move::new(from, to));
+            }
+        }
+    }
 
-                if !check_only {
-                    self.finalize_move(x, 5, x, 3);
-                    self.finalize_move(x, 1, x, 4);
-                    println!("{} Queen side castling: Moved King from {}{} to {}{} and Tower from {}{} to {}{}", self.active_player, Field::y_to_letter(&5), x, Field::y_to_letter(&3), x, Field::y_to_letter(&1), x, Field::y_to_letter(&4), x)
+    fn step_moves(&self, from: Square, steps: &[(i32, i32)], moves: &mut Vec<Move>) {
+        let color = self.get(from).get_color();
+        for (step_x, step_y) in steps {
+            if let Some(to) = from.offset(*step_x, *step_y) {
+                if self.get(to).get_color() != color {
+                    moves.push(Move::new(from, to));
                 }
             }
-            _ => {
-                return Ok(false);
-            } //failure
         }
+    }
 
-        Ok(true) //success
+    fn slide_moves(&self, from: Square, directions: &[(i32, i32)], moves: &mut Vec<Move>) {
+        let color = self.get(from).get_color();
+        for (step_x, step_y) in directions {
+            let mut next = from.offset(*step_x, *step_y);
+            while let Some(to) = next {
+                let target = self.get(to);
+                if target.get_color() == color {
+                    break;
+                }
+                moves.push(Move::new(from, to));
+                if target.get_type() != FieldType::None {
+                    break;
+                }
+                next = to.offset(*step_x, *step_y);
+            }
+        }
     }
 
-    pub fn set_board_empty(&mut self) {
-        let mut curr_x = 1;
-        let mut curr_y = 1;
+    fn castling_moves(&self, color: Color, moves: &mut Vec<Move>) {
+        for small in [true, false] {
+            if self.check_castling(color, small).is_ok() {
+                moves.extend(self.castling_move(color, small));
+            }
+        }
+    }
 
-        self.active_player = Color::White;
-        self.white_can_small_castle = true;
-        self.white_can_large_castle = true;
-        self.black_can_small_castle = true;
-        self.black_can_large_castle = true;
-        self.en_passant_pos = (0, 0);
+    //checkmate, stalemate, fifty move rule and insufficient material, repetitions
+    //need the game history and are checked with count_repetitions
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.legal_moves().is_empty() {
+            if self.is_in_check(self.active_player) {
+                return Some(Outcome::Checkmate(self.active_player.enemy_color()));
+            }
+            return Some(Outcome::Stalemate);
+        }
+        if self.halfmove_clock >= 100 {
+            return Some(Outcome::FiftyMoveRule);
+        }
+        if self.has_insufficient_material() {
+            return Some(Outcome::InsufficientMaterial);
+        }
+        None
+    }
 
-        for field in &mut self.state {
-            field.set_empty_with_pos(curr_x, curr_y);
-            if curr_y > 7 {
-                curr_y = 1;
-                curr_x += 1;
+    fn has_insufficient_material(&self) -> bool {
+        let figures: Vec<(Square, Field)> = self
+            .fields()
+            .filter(|c| c.1.get_type() != FieldType::None && c.1.get_type() != FieldType::King)
+            .collect();
+        let shade = |c: Square| (c.rank().number() + c.file().number()) % 2;
+        match figures.len() {
+            0 => true,
+            1 => matches!(
+                figures[0].1.get_type(),
+                FieldType::Bishop | FieldType::Knight
+            ),
+            //bishops on fields of the same color can't mate
+            _ => figures.iter().all(|(square, field)| {
+                field.get_type() == FieldType::Bishop && shade(*square) == shade(figures[0].0)
+            }),
+        }
+    }
+
+    //the history holds the Zobrist keys of the positions,
+    //the move counters don't count
+    pub fn count_repetitions(&self, history: impl Iterator<Item = u64>) -> usize {
+        history.filter(|c| *c == self.key).count()
+    }
+
+    //Zobrist hash with the keys of the Polyglot book format
+    pub fn zobrist_key(&self) -> u64 {
+        self.key
+    }
+
+    //the part of the Zobrist key for the figures on the given fields, the castling rights,
+    //en passant and the side to move
+    fn position_key(&self, squares: u64) -> u64 {
+        let mut key = 0;
+        for (square, field) in self.fields().filter(|c| squares & c.0.bit() != 0) {
+            let piece = match field.get_type() {
+                FieldType::Pawn => 0,
+                FieldType::Knight => 1,
+                FieldType::Bishop => 2,
+                FieldType::Tower => 3,
+                FieldType::Queen => 4,
+                FieldType::King => 5,
+                FieldType::None => continue,
+            };
+            let kind = 2 * piece + usize::from(field.get_color() == Color::White);
+            key ^= POLYGLOT_KEYS[64 * kind + square.index()];
+        }
+        for (i, castling) in [
+            self.white_can_small_castle,
+            self.white_can_large_castle,
+            self.black_can_small_castle,
+            self.black_can_large_castle,
+        ]
+        .iter()
+        .enumerate()
+        {
+            if *castling {
+                key ^= POLYGLOT_KEYS[768 + i];
+            }
+        }
+        //the en passant file only counts when a pawn of the side to move could take
+        if let Some(square) = self.en_passant_pos {
+            let pawn_step = if self.active_player == Color::White {
+                -1
             } else {
-                curr_y += 1;
+                1
+            };
+            let can_take = [-1, 1].iter().any(|step_y| {
+                let pawn = self.get_relative(square, pawn_step, *step_y);
+                pawn.get_type() == FieldType::Pawn && pawn.get_color() == self.active_player
+            });
+            if can_take {
+                key ^= POLYGLOT_KEYS[772 + square.file() as usize];
             }
         }
+        if self.active_player == Color::White {
+            key ^= POLYGLOT_KEYS[780];
+        }
+        key
     }
 
-    pub fn set_board_start(&mut self) {
-        self.set_board_empty();
-        for field in &mut self.state {
-            if field.get_x() == 1 {
-                match field.get_y() {
-                    1 => {
-                        field.set_figure(FieldType::Tower, Color::White);
-                    }
-                    2 => {
-                        field.set_figure(FieldType::Knight, Color::White);
-                    }
-                    3 => {
-                        field.set_figure(FieldType::Bishop, Color::White);
-                    }
-                    4 => {
-                        field.set_figure(FieldType::Queen, Color::White);
-                    }
-                    5 => {
-                        field.set_figure(FieldType::King, Color::White);
-                    }
-                    6 => {
-                        field.set_figure(FieldType::Bishop, Color::White);
-                    }
-                    7 => {
-                        field.set_figure(FieldType::Knight, Color::White);
-                    }
-                    8 => {
-                        field.set_figure(FieldType::Tower, Color::White);
-                    }
-                    _ => {}
+    pub fn set_board_fen(&mut self, fen: &str) -> Result<(), ChessError> {
+        self.parse_fen(fen, true)
+    }
+
+    //variants like Horde and Antichess have no or more kings
+    pub fn set_board_fen_unchecked(&mut self, fen: &str) -> Result<(), ChessError> {
+        self.parse_fen(fen, false)
+    }
+
+    fn parse_fen(&mut self, fen: &str, one_king: bool) -> Result<(), ChessError> {
+        let error = || ChessError::InvalidFen(fen.to_string());
+        let parts: Vec<&str> = fen.split_whitespace().collect();
+        if parts.len() < 4 {
+            return Err(error());
+        }
+        let mut board = Board::new();
+        board.set_board_empty();
+
+        //Crazyhouse pockets are written in brackets or as a ninth row
+        let (placement, pocket) = match parts[0].split_once('[') {
+            Some((placement, pocket)) => (placement, Some(pocket.trim_end_matches(']'))),
+            None => (parts[0], None),
+        };
+        let mut ranks: Vec<&str> = placement.split('/').collect();
+        let pocket = if ranks.len() == 9 {
+            ranks.pop()
+        } else {
+            pocket
+        };
+        if ranks.len() != 8 {
+            return Err(error());
+        }
+        if let Some(pocket) = pocket {
+            board.crazyhouse = true;
+            for c in pocket.chars() {
+                let figure_type = FieldType::from_letter(c)
+                    .filter(|c| POCKET_FIGURES.contains(c))
+                    .ok_or_else(error)?;
+                let color = if c.is_ascii_uppercase() {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                board.add_to_pocket(color, figure_type, 1);
+            }
+        }
+        for (i, rank) in ranks.iter().enumerate() {
+            let x = 8 - i as i32;
+            let mut y = 1;
+            for c in rank.chars() {
+                if let Some(n) = c.to_digit(10) {
+                    y += n as i32;
+                    continue;
+                }
+                //a promoted figure is marked after its letter
+                if c == '~' {
+                    let square = Square::from_coords(x, y - 1).ok_or_else(error)?;
+                    board.promoted |= square.bit();
+                    continue;
                 }
+                let figure_type = FieldType::from_letter(c).ok_or_else(error)?;
+                let color = if c.is_ascii_uppercase() {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                let square = Square::from_coords(x, y).ok_or_else(error)?;
+                board.set_figure(square, figure_type, color);
+                y += 1;
             }
-            if field.get_x() == 2 {
-                field.set_figure(FieldType::Pawn, Color::White);
+            if y != 9 {
+                return Err(error());
             }
-            if field.get_x() == 7 {
-                field.set_figure(FieldType::Pawn, Color::Black);
+        }
+        for color in [Color::White, Color::Black] {
+            let kings = board
+                .state
+                .iter()
+                .filter(|c| c.get_type() == FieldType::King && c.get_color() == color)
+                .count();
+            if one_king && kings != 1 {
+                return Err(error());
             }
-            if field.get_x() == 8 {
-                match field.get_y() {
-                    1 => {
-                        field.set_figure(FieldType::Tower, Color::Black);
-                    }
-                    2 => {
-                        field.set_figure(FieldType::Knight, Color::Black);
-                    }
-                    3 => {
-                        field.set_figure(FieldType::Bishop, Color::Black);
-                    }
-                    4 => {
-                        field.set_figure(FieldType::Queen, Color::Black);
-                    }
-                    5 => {
-                        field.set_figure(FieldType::King, Color::Black);
-                    }
-                    6 => {
-                        field.set_figure(FieldType::Bishop, Color::Black);
-                    }
-                    7 => {
-                        field.set_figure(FieldType::Knight, Color::Black);
-                    }
-                    8 => {
-                        field.set_figure(FieldType::Tower, Color::Black);
-                    }
-                    _ => {}
+        }
+
+        board.active_player = match parts[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(error()),
+        };
+
+        board.white_can_small_castle = false;
+        board.white_can_large_castle = false;
+        board.black_can_small_castle = false;
+        board.black_can_large_castle = false;
+        //KQkq take the outermost tower, X-FEN and Shredder-FEN name the file of the tower
+        for c in parts[2].chars() {
+            if c == '-' {
+                continue;
+            }
+            let color = if c.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let rank = Board::back_rank(color);
+            let king_file = board
+                .get_king(color)
+                .filter(|k| k.rank() == rank)
+                .map(|k| k.file())
+                .ok_or_else(error)?;
+            let (small, tower_file) = match c.to_ascii_lowercase() {
+                'k' => (
+                    true,
+                    board.outer_tower(color, king_file, true).unwrap_or(File::H),
+                ),
+                'q' => (
+                    false,
+                    board
+                        .outer_tower(color, king_file, false)
+                        .unwrap_or(File::A),
+                ),
+                letter => {
+                    let tower_file = File::from_letter(letter).ok_or_else(error)?;
+                    board.chess960 = true;
+                    (tower_file > king_file, tower_file)
                 }
+            };
+            if king_file != File::E || tower_file != if small { File::H } else { File::A } {
+                board.chess960 = true;
+            }
+            let index = Board::castle_index(color, small);
+            board.castle_towers[index] = tower_file;
+            match index {
+                0 => board.white_can_small_castle = true,
+                1 => board.white_can_large_castle = true,
+                2 => board.black_can_small_castle = true,
+                _ => board.black_can_large_castle = true,
             }
         }
+
+        if parts[3] != "-" {
+            board.en_passant_pos = Some(Square::parse(parts[3]).ok_or_else(error)?);
+        }
+
+        if parts.len() > 5 {
+            board.halfmove_clock = parts[4].parse().map_err(|_| error())?;
+            board.fullmove_number = parts[5].parse().map_err(|_| error())?;
+        }
+
+        board.key = board.position_key(!0);
+        *self = board;
+        Ok(())
     }
 
-    pub fn active_player(&self) -> Color {
-        self.active_player
+    //the tower farthest from the king on the back rank
+    fn outer_tower(&self, color: Color, king_file: File, small: bool) -> Option<File> {
+        let rank = Board::back_rank(color);
+        let files: Vec<File> = if small {
+            File::ALL
+                .into_iter()
+                .filter(|c| *c > king_file)
+                .rev()
+                .collect()
+        } else {
+            File::ALL.into_iter().filter(|c| *c < king_file).collect()
+        };
+        files.into_iter().find(|file| {
+            let field = self.get(Square::new(*file, rank));
+            field.get_type() == FieldType::Tower && field.get_color() == color
+        })
     }
 
-    pub fn set_active_player(&mut self, active_player: Color) {
-        self.active_player = active_player;
+    //X-FEN names the file only when another tower stands outside the castling tower
+    fn castling_letter(&self, color: Color, small: bool, letter: char) -> char {
+        let tower_file = self.castle_towers[Board::castle_index(color, small)];
+        let king_file = self.get_king(color).map_or(File::E, |k| k.file());
+        if !self.chess960 || self.outer_tower(color, king_file, small) == Some(tower_file) {
+            return letter;
+        }
+        let file = tower_file.letter();
+        if color == Color::White {
+            file.to_ascii_uppercase()
+        } else {
+            file
+        }
+    }
+
+    pub fn fen(&self) -> String {
+        let mut fen = String::new();
+        for rank in Rank::ALL.into_iter().rev() {
+            let mut empty = 0;
+            for file in File::ALL {
+                let square = Square::new(file, rank);
+                let field = self.get(square);
+                if field.get_type() == FieldType::None {
+                    empty += 1;
+                    continue;
+                }
+                if empty > 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                let letter = field.get_type().letter();
+                if field.get_color() == Color::White {
+                    fen.push(letter);
+                } else {
+                    fen.push(letter.to_ascii_lowercase());
+                }
+                if self.promoted & square.bit() != 0 {
+                    fen.push('~');
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank != Rank::First {
+                fen.push('/');
+            }
+        }
+
+        if self.crazyhouse {
+            fen.push('[');
+            for color in [Color::White, Color::Black] {
+                for figure_type in POCKET_FIGURES.iter().rev() {
+                    let letter = if color == Color::White {
+                        figure_type.letter()
+                    } else {
+                        figure_type.letter().to_ascii_lowercase()
+                    };
+                    for _ in 0..self.pocket(color, *figure_type) {
+                        fen.push(letter);
+                    }
+                }
+            }
+            fen.push(']');
+        }
+
+        fen.push_str(if self.active_player == Color::Black {
+            " b "
+        } else {
+            " w "
+        });
+        let castling: String = [
+            (Color::White, true, 'K'),
+            (Color::White, false, 'Q'),
+            (Color::Black, true, 'k'),
+            (Color::Black, false, 'q'),
+        ]
+        .iter()
+        .filter(|c| self.can_castle(c.0, c.1))
+        .map(|c| self.castling_letter(c.0, c.1, c.2))
+        .collect();
+        if castling.is_empty() {
+            fen.push('-');
+        } else {
+            fen.push_str(&castling);
+        }
+
+        match self.en_passant_pos {
+            Some(square) => fen.push_str(&format!(" {}", square)),
+            None => fen.push_str(" -"),
+        }
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+        fen
     }
 }
 
 impl Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.crazyhouse {
+            writeln!(f, "    {}", self.pocket_line(Color::Black))?;
+        }
         writeln!(f, "    _______________________  ",)?;
-        for i in (1..9).rev() {
-            writeln!(
-                f,
-                "{} | {}  {}  {}  {}  {}  {}  {}  {} |",
-                i,
-                self.get(i, 1),
-                self.get(i, 2),
-                self.get(i, 3),
-                self.get(i, 4),
-                self.get(i, 5),
-                self.get(i, 6),
-                self.get(i, 7),
-                self.get(i, 8)
-            )?;
+        for rank in Rank::ALL.into_iter().rev() {
+            let fields: Vec<String> = File::ALL
+                .iter()
+                .map(|file| self.get(Square::new(*file, rank)).to_string())
+                .collect();
+            writeln!(f, "{} | {} |", rank, fields.join("  "))?;
         }
         writeln!(f, "    _______________________  ",)?;
         writeln!(f, "    a  b  c  d  e  f  g  h   ",)?;
+        if self.crazyhouse {
+            writeln!(f, "    {}", self.pocket_line(Color::White))?;
+        }
 
         Ok(())
     }
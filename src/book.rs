@@ -0,0 +1,121 @@
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::board::Board;
+use crate::field::{ChessError, FieldType, Move};
+use crate::square::{File, Rank, Square};
+
+//one 16 byte record of a Polyglot book, all numbers are big endian
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    pub raw_move: u16,
+    pub weight: u16,
+}
+
+#[derive(Clone)]
+pub struct OpeningBook {
+    //sorted by key like in the file
+    entries: Vec<BookEntry>,
+}
+
+impl OpeningBook {
+    pub fn open(path: &str) -> Result<OpeningBook, ChessError> {
+        let bytes = fs::read(path).map_err(|e| ChessError::FileError(e.to_string()))?;
+        OpeningBook::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<OpeningBook, ChessError> {
+        if !bytes.len().is_multiple_of(16) {
+            return Err(ChessError::FileError(String::from(
+                "a Polyglot book consists of 16 byte entries",
+            )));
+        }
+        let mut entries: Vec<BookEntry> = bytes
+            .chunks(16)
+            .map(|c| BookEntry {
+                key: u64::from_be_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]),
+                raw_move: u16::from_be_bytes([c[8], c[9]]),
+                weight: u16::from_be_bytes([c[10], c[11]]),
+            })
+            .collect();
+        //books written by other tools aren't always sorted
+        entries.sort_by_key(|c| c.key);
+        Ok(OpeningBook { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    //legal book moves of the position with their weights, the most played first
+    pub fn moves(&self, board: &Board) -> Vec<(Move, u16)> {
+        let key = board.zobrist_key();
+        let start = self.entries.partition_point(|c| c.key < key);
+        let legal_moves = board.legal_moves();
+        let mut moves: Vec<(Move, u16)> = self.entries[start..]
+            .iter()
+            .take_while(|c| c.key == key)
+            .filter_map(|c| {
+                let next_move = OpeningBook::decode_move(board, c.raw_move);
+                legal_moves
+                    .contains(&next_move)
+                    .then_some((next_move, c.weight))
+            })
+            .collect();
+        moves.sort_by_key(|c| std::cmp::Reverse(c.1));
+        moves
+    }
+
+    //a random book move, moves are picked in proportion to their weight
+    pub fn choose(&self, board: &Board) -> Option<Move> {
+        let moves = self.moves(board);
+        let total: u64 = moves.iter().map(|c| c.1 as u64).sum();
+        if total == 0 {
+            return moves.first().map(|c| c.0);
+        }
+        let mut pick = random_u64() % total;
+        for (next_move, weight) in moves {
+            if pick < weight as u64 {
+                return Some(next_move);
+            }
+            pick -= weight as u64;
+        }
+        None
+    }
+
+    //to file, to row, from file, from row and promotion in 3 bits each,
+    //castling is written as the king taking its own tower
+    fn decode_move(board: &Board, raw_move: u16) -> Move {
+        let bits = |shift: u16| ((raw_move >> shift) & 7) as usize;
+        let square = |shift: u16| Square::new(File::ALL[bits(shift)], Rank::ALL[bits(shift + 3)]);
+        let mut next_move = Move::new(square(6), square(0));
+        let figure = board.get(next_move.from);
+        let target = board.get(next_move.to);
+        if figure.get_type() == FieldType::King
+            && target.get_type() == FieldType::Tower
+            && target.get_color() == figure.get_color()
+        {
+            next_move = board
+                .castling_move(
+                    figure.get_color(),
+                    next_move.to.file() > next_move.from.file(),
+                )
+                .unwrap_or(next_move);
+        }
+        next_move.promotion = match bits(12) {
+            1 => Some(FieldType::Knight),
+            2 => Some(FieldType::Bishop),
+            3 => Some(FieldType::Tower),
+            4 => Some(FieldType::Queen),
+            _ => None,
+        };
+        next_move
+    }
+}
+
+//every RandomState gets new random keys, which is enough for picking book moves
+pub fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
@@ -0,0 +1,707 @@
+use std::fs;
+
+use crate::board::Board;
+use crate::field::{ChessError, Color, FieldType, Move};
+use crate::square::{File, Rank, Square};
+
+type Rgba = (u8, u8, u8, f32);
+
+const LIGHT: Rgba = (240, 217, 181, 1.0);
+const DARK: Rgba = (181, 136, 99, 1.0);
+const HIGHLIGHT: Rgba = (255, 235, 59, 0.5);
+const ARROW: Rgba = (21, 120, 27, 0.8);
+const WHITE_FIGURE: Rgba = (255, 255, 255, 1.0);
+const BLACK_FIGURE: Rgba = (40, 40, 40, 1.0);
+const OUTLINE: Rgba = (0, 0, 0, 1.0);
+const MARGIN: Rgba = (255, 255, 255, 1.0);
+const LABEL: Rgba = (60, 60, 60, 1.0);
+//samples per pixel and direction for the anti-aliasing of the PNG
+const SAMPLES: usize = 4;
+
+//parts of the figures in a field of size 1, y goes down
+enum Part {
+    Polygon(&'static [(f32, f32)]),
+    Circle(f32, f32, f32),
+}
+
+const BASE: Part = Part::Polygon(&[(0.25, 0.75), (0.75, 0.75), (0.75, 0.85), (0.25, 0.85)]);
+const PAWN: [Part; 3] = [
+    BASE,
+    Part::Polygon(&[(0.4, 0.42), (0.6, 0.42), (0.67, 0.75), (0.33, 0.75)]),
+    Part::Circle(0.5, 0.32, 0.12),
+];
+const TOWER: [Part; 3] = [
+    BASE,
+    Part::Polygon(&[(0.34, 0.75), (0.66, 0.75), (0.63, 0.38), (0.37, 0.38)]),
+    Part::Polygon(&[
+        (0.3, 0.38),
+        (0.7, 0.38),
+        (0.7, 0.18),
+        (0.62, 0.18),
+        (0.62, 0.25),
+        (0.54, 0.25),
+        (0.54, 0.18),
+        (0.46, 0.18),
+        (0.46, 0.25),
+        (0.38, 0.25),
+        (0.38, 0.18),
+        (0.3, 0.18),
+    ]),
+];
+const KNIGHT: [Part; 2] = [
+    BASE,
+    Part::Polygon(&[
+        (0.3, 0.75),
+        (0.72, 0.75),
+        (0.7, 0.58),
+        (0.66, 0.38),
+        (0.58, 0.22),
+        (0.48, 0.15),
+        (0.45, 0.22),
+        (0.36, 0.27),
+        (0.22, 0.45),
+        (0.27, 0.53),
+        (0.38, 0.48),
+        (0.46, 0.46),
+        (0.34, 0.62),
+    ]),
+];
+const BISHOP: [Part; 3] = [
+    BASE,
+    Part::Polygon(&[
+        (0.36, 0.75),
+        (0.64, 0.75),
+        (0.58, 0.56),
+        (0.64, 0.42),
+        (0.5, 0.24),
+        (0.36, 0.42),
+        (0.42, 0.56),
+    ]),
+    Part::Circle(0.5, 0.19, 0.05),
+];
+const QUEEN: [Part; 6] = [
+    BASE,
+    Part::Polygon(&[
+        (0.3, 0.75),
+        (0.7, 0.75),
+        (0.78, 0.3),
+        (0.64, 0.52),
+        (0.6, 0.25),
+        (0.5, 0.5),
+        (0.4, 0.25),
+        (0.36, 0.52),
+        (0.22, 0.3),
+    ]),
+    Part::Circle(0.22, 0.28, 0.045),
+    Part::Circle(0.4, 0.23, 0.045),
+    Part::Circle(0.6, 0.23, 0.045),
+    Part::Circle(0.78, 0.28, 0.045),
+];
+const KING: [Part; 3] = [
+    BASE,
+    Part::Polygon(&[
+        (0.3, 0.75),
+        (0.7, 0.75),
+        (0.75, 0.46),
+        (0.6, 0.38),
+        (0.5, 0.43),
+        (0.4, 0.38),
+        (0.25, 0.46),
+    ]),
+    Part::Polygon(&[
+        (0.47, 0.12),
+        (0.53, 0.12),
+        (0.53, 0.18),
+        (0.59, 0.18),
+        (0.59, 0.24),
+        (0.53, 0.24),
+        (0.53, 0.38),
+        (0.47, 0.38),
+        (0.47, 0.24),
+        (0.41, 0.24),
+        (0.41, 0.18),
+        (0.47, 0.18),
+    ]),
+];
+
+fn parts(figure_type: FieldType) -> &'static [Part] {
+    match figure_type {
+        FieldType::Pawn => &PAWN,
+        FieldType::Tower => &TOWER,
+        FieldType::Knight => &KNIGHT,
+        FieldType::Bishop => &BISHOP,
+        FieldType::Queen => &QUEEN,
+        FieldType::King => &KING,
+        FieldType::None => &[],
+    }
+}
+
+//5x7 pixel letters of the coordinates and moves, one byte per row
+fn font(c: char) -> [u8; 7] {
+    match c {
+        'a' => [0, 0, 14, 1, 15, 17, 15],
+        'b' => [16, 16, 30, 17, 17, 17, 30],
+        'c' => [0, 0, 14, 16, 16, 17, 14],
+        'd' => [1, 1, 15, 17, 17, 17, 15],
+        'e' => [0, 0, 14, 17, 31, 16, 14],
+        'f' => [6, 9, 8, 28, 8, 8, 8],
+        'g' => [0, 15, 17, 15, 1, 17, 14],
+        'h' => [16, 16, 22, 25, 17, 17, 17],
+        '1' => [4, 12, 4, 4, 4, 4, 14],
+        '2' => [14, 17, 1, 2, 4, 8, 31],
+        '3' => [31, 2, 4, 2, 1, 17, 14],
+        '4' => [2, 6, 10, 18, 31, 2, 2],
+        '5' => [31, 16, 30, 1, 1, 17, 14],
+        '6' => [6, 8, 16, 30, 17, 17, 14],
+        '7' => [31, 1, 2, 4, 8, 8, 8],
+        '8' => [14, 17, 17, 14, 17, 17, 14],
+        '9' => [14, 17, 17, 15, 1, 2, 12],
+        '0' => [14, 17, 19, 21, 25, 17, 14],
+        'K' => [17, 18, 20, 24, 20, 18, 17],
+        'Q' => [14, 17, 17, 17, 21, 18, 13],
+        'R' => [30, 17, 17, 30, 20, 18, 17],
+        'B' => [30, 17, 17, 30, 17, 17, 30],
+        'N' => [17, 17, 25, 21, 19, 17, 17],
+        'O' => [14, 17, 17, 17, 17, 17, 14],
+        'P' => [30, 17, 17, 30, 16, 16, 16],
+        'x' => [0, 0, 17, 10, 4, 10, 17],
+        '+' => [0, 4, 4, 31, 4, 4, 0],
+        '#' => [10, 10, 31, 10, 31, 10, 10],
+        '=' => [0, 0, 31, 0, 31, 0, 0],
+        '-' => [0, 0, 0, 31, 0, 0, 0],
+        '.' => [0, 0, 0, 0, 0, 12, 12],
+        '@' => [14, 17, 23, 21, 23, 16, 14],
+        _ => [0; 7],
+    }
+}
+
+enum Shape {
+    Rect(f32, f32, f32, f32),
+    Polygon(Vec<(f32, f32)>),
+    Circle(f32, f32, f32),
+    //centered text with its height
+    Text(f32, f32, f32, String),
+}
+
+//a shape in pixels with its fill and outline
+struct Item {
+    shape: Shape,
+    fill: Rgba,
+    stroke: Option<(Rgba, f32)>,
+}
+
+pub struct DiagramOptions {
+    //width and height in pixels including the coordinates
+    pub size: u32,
+    pub flipped: bool,
+    pub coordinates: bool,
+    pub arrows: Vec<Move>,
+    pub highlights: Vec<Square>,
+    //text in a band below the board
+    pub caption: Option<String>,
+}
+
+impl DiagramOptions {
+    pub fn new() -> DiagramOptions {
+        DiagramOptions {
+            size: 400,
+            flipped: false,
+            coordinates: true,
+            arrows: Vec::new(),
+            highlights: Vec::new(),
+            caption: None,
+        }
+    }
+
+    //the caption band adds a tenth of the size
+    pub fn height(&self) -> u32 {
+        match self.caption {
+            Some(_) => self.size + self.size / 10,
+            None => self.size,
+        }
+    }
+
+    //"size <pixels>", "flip", "nocoords", "arrow <e2e4>" and "highlight <e4>" in any order
+    pub fn parse(tokens: &[&str]) -> Result<DiagramOptions, ChessError> {
+        let usage = || {
+            ChessError::InvalidCommand(String::from(
+                "diagram <file.svg|file.png> [size <pixels>] [flip] [nocoords] [arrow <e2e4>] [highlight <e4>]",
+            ))
+        };
+        let mut options = DiagramOptions::new();
+        let mut tokens = tokens.iter();
+        while let Some(token) = tokens.next() {
+            match *token {
+                "flip" => options.flipped = true,
+                "nocoords" => options.coordinates = false,
+                "size" => {
+                    options.size = tokens
+                        .next()
+                        .and_then(|c| c.parse().ok())
+                        .filter(|c| (80..=4000).contains(c))
+                        .ok_or_else(usage)?
+                }
+                "arrow" => {
+                    let arrow = tokens.next().ok_or_else(usage)?;
+                    options
+                        .arrows
+                        .push(Move::from_uci(arrow).map_err(|_| usage())?);
+                }
+                "highlight" => {
+                    let square = tokens.next().and_then(|c| Square::parse(c));
+                    options.highlights.push(square.ok_or_else(usage)?);
+                }
+                _ => return Err(usage()),
+            }
+        }
+        Ok(options)
+    }
+
+    fn margin(&self) -> f32 {
+        if self.coordinates {
+            self.size as f32 / 20.0
+        } else {
+            0.0
+        }
+    }
+
+    fn field_size(&self) -> f32 {
+        (self.size as f32 - self.margin()) / 8.0
+    }
+
+    //top left corner of a field in pixels, the coordinates are left of and below the board
+    fn corner(&self, square: Square) -> (f32, f32) {
+        let (x, y) = (square.rank().number(), square.file().number());
+        let (row, column) = if self.flipped {
+            (x - 1, 8 - y)
+        } else {
+            (8 - x, y - 1)
+        };
+        let field = self.field_size();
+        (self.margin() + column as f32 * field, row as f32 * field)
+    }
+
+    fn center(&self, square: Square) -> (f32, f32) {
+        let (left, top) = self.corner(square);
+        let half = self.field_size() / 2.0;
+        (left + half, top + half)
+    }
+}
+
+fn arrow(options: &DiagramOptions, next_move: Move) -> Vec<(f32, f32)> {
+    let field = options.field_size();
+    let from = options.center(next_move.from);
+    let to = options.center(next_move.to);
+    let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2))
+        .sqrt()
+        .max(1.0);
+    let direction = ((to.0 - from.0) / length, (to.1 - from.1) / length);
+    let normal = (-direction.1, direction.0);
+    let head = (field * 0.35).min(length);
+    let neck = (to.0 - direction.0 * head, to.1 - direction.1 * head);
+    let point = |base: (f32, f32), width: f32| {
+        (
+            base.0 + normal.0 * width / 2.0,
+            base.1 + normal.1 * width / 2.0,
+        )
+    };
+    let (shaft, head_width) = (field * 0.15, field * 0.4);
+    vec![
+        point(from, shaft),
+        point(neck, shaft),
+        point(neck, head_width),
+        to,
+        point(neck, -head_width),
+        point(neck, -shaft),
+        point(from, -shaft),
+    ]
+}
+
+//everything drawn, from the back to the front
+fn scene(board: &Board, options: &DiagramOptions) -> Vec<Item> {
+    let field = options.field_size();
+    let mut items = vec![Item {
+        shape: Shape::Rect(0.0, 0.0, options.size as f32, options.height() as f32),
+        fill: MARGIN,
+        stroke: None,
+    }];
+    for (square, figure) in board.fields() {
+        let (left, top) = options.corner(square);
+        let shade = square.rank().number() + square.file().number();
+        let mut fills = vec![if shade % 2 == 0 { DARK } else { LIGHT }];
+        if options.highlights.contains(&square) {
+            fills.push(HIGHLIGHT);
+        }
+        for fill in fills {
+            items.push(Item {
+                shape: Shape::Rect(left, top, field, field),
+                fill,
+                stroke: None,
+            });
+        }
+        let fill = if figure.get_color() == Color::White {
+            WHITE_FIGURE
+        } else {
+            BLACK_FIGURE
+        };
+        for part in parts(figure.get_type()) {
+            let shape = match part {
+                Part::Polygon(points) => Shape::Polygon(
+                    points
+                        .iter()
+                        .map(|(px, py)| (left + px * field, top + py * field))
+                        .collect(),
+                ),
+                Part::Circle(cx, cy, r) => {
+                    Shape::Circle(left + cx * field, top + cy * field, r * field)
+                }
+            };
+            items.push(Item {
+                shape,
+                fill,
+                stroke: Some((OUTLINE, (field / 30.0).max(1.0))),
+            });
+        }
+    }
+    if options.coordinates {
+        let margin = options.margin();
+        let text = margin * 0.6;
+        for (rank, file) in Rank::ALL.into_iter().zip(File::ALL) {
+            let (left, top) = options.corner(Square::new(file, rank));
+            let (rank, file) = (rank.to_string(), file.to_string());
+            items.push(Item {
+                shape: Shape::Text(margin / 2.0, top + field / 2.0, text, rank),
+                fill: LABEL,
+                stroke: None,
+            });
+            items.push(Item {
+                shape: Shape::Text(left + field / 2.0, 8.0 * field + margin / 2.0, text, file),
+                fill: LABEL,
+                stroke: None,
+            });
+        }
+    }
+    for next_move in &options.arrows {
+        items.push(Item {
+            shape: Shape::Polygon(arrow(options, *next_move)),
+            fill: ARROW,
+            stroke: None,
+        });
+    }
+    if let Some(caption) = &options.caption {
+        let (size, band) = (
+            options.size as f32,
+            (options.height() - options.size) as f32,
+        );
+        items.push(Item {
+            shape: Shape::Text(size / 2.0, size + band / 2.0, band * 0.6, caption.clone()),
+            fill: LABEL,
+            stroke: None,
+        });
+    }
+    items
+}
+
+fn color(rgba: Rgba) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgba.0, rgba.1, rgba.2)
+}
+
+pub fn svg(board: &Board, options: &DiagramOptions) -> String {
+    format!(
+        "{}{}</svg>\n",
+        svg_header(options),
+        svg_elements(board, options)
+    )
+}
+
+pub fn svg_header(options: &DiagramOptions) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        options.size,
+        options.height()
+    )
+}
+
+//the shapes of the picture without the surrounding svg element
+pub fn svg_elements(board: &Board, options: &DiagramOptions) -> String {
+    let mut text = String::new();
+    for item in scene(board, options) {
+        let mut paint = format!(
+            "fill=\"{}\" fill-opacity=\"{}\"",
+            color(item.fill),
+            item.fill.3
+        );
+        if let Some((stroke, width)) = item.stroke {
+            paint.push_str(&format!(
+                " stroke=\"{}\" stroke-width=\"{:.2}\" stroke-linejoin=\"round\"",
+                color(stroke),
+                width
+            ));
+        }
+        let element = match item.shape {
+            Shape::Rect(x, y, w, h) => format!(
+                "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" {}/>",
+                x, y, w, h, paint
+            ),
+            Shape::Polygon(points) => {
+                let points: Vec<String> = points
+                    .iter()
+                    .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+                    .collect();
+                format!("<polygon points=\"{}\" {}/>", points.join(" "), paint)
+            }
+            Shape::Circle(x, y, r) => format!(
+                "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" {}/>",
+                x, y, r, paint
+            ),
+            Shape::Text(x, y, height, label) => format!(
+                "<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{:.2}\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\" {}>{}</text>",
+                x, y, height, paint, label
+            ),
+        };
+        text.push_str(&element);
+        text.push('\n');
+    }
+    text
+}
+
+fn inside_polygon(points: &[(f32, f32)], x: f32, y: f32) -> bool {
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        if (a.1 > y) != (b.1 > y) && x < (b.0 - a.0) * (y - a.1) / (b.1 - a.1) + a.0 {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+fn segment_distance(a: (f32, f32), b: (f32, f32), x: f32, y: f32) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((x - a.0) * dx + (y - a.1) * dy) / length).clamp(0.0, 1.0)
+    };
+    ((a.0 + t * dx - x).powi(2) + (a.1 + t * dy - y).powi(2)).sqrt()
+}
+
+//RGB pixels, rows from the top
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Raster {
+    fn blend(&mut self, x: usize, y: usize, rgba: Rgba, coverage: f32) {
+        let alpha = rgba.3 * coverage;
+        if alpha <= 0.0 || x >= self.width || y >= self.height {
+            return;
+        }
+        let pixel = &mut self.pixels[y * self.width + x];
+        for (channel, value) in pixel.iter_mut().zip([rgba.0, rgba.1, rgba.2]) {
+            *channel = (*channel as f32 * (1.0 - alpha) + value as f32 * alpha).round() as u8;
+        }
+    }
+
+    //fill and outline coverage of every pixel from SAMPLES x SAMPLES points
+    fn paint(
+        &mut self,
+        bounds: (f32, f32, f32, f32),
+        item: &Item,
+        inside: impl Fn(f32, f32) -> bool,
+        edge: impl Fn(f32, f32) -> f32,
+    ) {
+        let reach = item.stroke.map_or(0.0, |c| c.1 / 2.0);
+        let left = (bounds.0 - reach).floor().max(0.0) as usize;
+        let top = (bounds.1 - reach).floor().max(0.0) as usize;
+        let right = ((bounds.2 + reach).ceil() as usize).min(self.width);
+        let bottom = ((bounds.3 + reach).ceil() as usize).min(self.height);
+        let total = (SAMPLES * SAMPLES) as f32;
+        for y in top..bottom {
+            for x in left..right {
+                let (mut filled, mut stroked) = (0, 0);
+                for sample in 0..SAMPLES * SAMPLES {
+                    let px = x as f32 + ((sample % SAMPLES) as f32 + 0.5) / SAMPLES as f32;
+                    let py = y as f32 + ((sample / SAMPLES) as f32 + 0.5) / SAMPLES as f32;
+                    if inside(px, py) {
+                        filled += 1;
+                    }
+                    if reach > 0.0 && edge(px, py) <= reach {
+                        stroked += 1;
+                    }
+                }
+                self.blend(x, y, item.fill, filled as f32 / total);
+                if let Some((stroke, _)) = item.stroke {
+                    self.blend(x, y, stroke, stroked as f32 / total);
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, item: &Item) {
+        match &item.shape {
+            Shape::Rect(x, y, w, h) => {
+                let (x, y, w, h) = (*x, *y, *w, *h);
+                self.paint(
+                    (x, y, x + w, y + h),
+                    item,
+                    |px, py| px >= x && px < x + w && py >= y && py < y + h,
+                    |_, _| f32::MAX,
+                );
+            }
+            Shape::Polygon(points) => {
+                let bounds = points
+                    .iter()
+                    .fold((f32::MAX, f32::MAX, f32::MIN, f32::MIN), |c, p| {
+                        (c.0.min(p.0), c.1.min(p.1), c.2.max(p.0), c.3.max(p.1))
+                    });
+                self.paint(
+                    bounds,
+                    item,
+                    |px, py| inside_polygon(points, px, py),
+                    |px, py| {
+                        (0..points.len())
+                            .map(|i| {
+                                segment_distance(points[i], points[(i + 1) % points.len()], px, py)
+                            })
+                            .fold(f32::MAX, f32::min)
+                    },
+                );
+            }
+            Shape::Circle(x, y, r) => {
+                let (x, y, r) = (*x, *y, *r);
+                let distance = move |px: f32, py: f32| ((px - x).powi(2) + (py - y).powi(2)).sqrt();
+                self.paint(
+                    (x - r, y - r, x + r, y + r),
+                    item,
+                    |px, py| distance(px, py) <= r,
+                    |px, py| (distance(px, py) - r).abs(),
+                );
+            }
+            //the bitmap letters scaled to the height
+            Shape::Text(x, y, height, label) => {
+                let pixel = height / 7.0;
+                let width = label.chars().count() as f32 * 6.0 * pixel - pixel;
+                let (left, top) = (x - width / 2.0, y - height / 2.0);
+                for (i, c) in label.chars().enumerate() {
+                    for (row, bits) in font(c).iter().enumerate() {
+                        for column in 0..5 {
+                            if bits & (16 >> column) == 0 {
+                                continue;
+                            }
+                            let dot = Item {
+                                shape: Shape::Rect(
+                                    left + (i * 6 + column) as f32 * pixel,
+                                    top + row as f32 * pixel,
+                                    pixel,
+                                    pixel,
+                                ),
+                                fill: item.fill,
+                                stroke: None,
+                            };
+                            self.draw(&dot);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn raster(board: &Board, options: &DiagramOptions) -> Raster {
+    let (width, height) = (options.size as usize, options.height() as usize);
+    let mut raster = Raster {
+        width,
+        height,
+        pixels: vec![[255; 3]; width * height],
+    };
+    for item in scene(board, options) {
+        raster.draw(&item);
+    }
+    raster
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn chunk(png: &mut Vec<u8>, name: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let mut named = name.to_vec();
+    named.extend_from_slice(data);
+    png.extend_from_slice(&named);
+    png.extend(crc32(&named).to_be_bytes());
+}
+
+//8 bit RGB PNG, the image data is stored without compression
+pub fn png(board: &Board, options: &DiagramOptions) -> Vec<u8> {
+    let raster = raster(board, options);
+    let mut data = Vec::with_capacity(raster.height * (raster.width * 3 + 1));
+    for row in raster.pixels.chunks(raster.width) {
+        //no filter
+        data.push(0);
+        data.extend(row.iter().flatten());
+    }
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = data.chunks(65535).collect();
+    for (i, block) in blocks.iter().enumerate() {
+        zlib.push(u8::from(i == blocks.len() - 1));
+        let length = block.len() as u16;
+        zlib.extend(length.to_le_bytes());
+        zlib.extend((!length).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend(adler32(&data).to_be_bytes());
+
+    let mut header = Vec::new();
+    header.extend((raster.width as u32).to_be_bytes());
+    header.extend((raster.height as u32).to_be_bytes());
+    //bit depth, RGB, compression, filter and interlace method
+    header.extend([8, 2, 0, 0, 0]);
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &zlib);
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+//the format is chosen by the file ending
+pub fn save(board: &Board, options: &DiagramOptions, path: &str) -> Result<(), ChessError> {
+    let lower = path.to_lowercase();
+    let data = if lower.ends_with(".svg") {
+        svg(board, options).into_bytes()
+    } else if lower.ends_with(".png") {
+        png(board, options)
+    } else {
+        return Err(ChessError::FileError(format!(
+            "{} needs the ending .svg or .png",
+            path
+        )));
+    };
+    fs::write(path, data).map_err(|e| ChessError::FileError(e.to_string()))
+}
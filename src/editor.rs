@@ -0,0 +1,105 @@
+use crate::board::Board;
+use crate::field::{ChessError, Color, FieldType};
+use crate::square::Square;
+
+const USAGE: &str = "put <figure><w|b> <field>, clear <field|all>, side <white|black>, castle <K|Q|k|q> <on|off>, ep <field|off>, show, done, cancel";
+
+pub enum EditResult {
+    Editing,
+    //the board passed the checks and can be played
+    Done,
+    Cancelled,
+}
+
+//sets up a position figure by figure, it is only checked when the editing is done
+pub struct PositionEditor {
+    board: Board,
+}
+
+fn parse_field(text: &str) -> Result<Square, ChessError> {
+    Square::parse(text)
+        .ok_or_else(|| ChessError::InvalidCommand(format!("{} is no field like e4", text)))
+}
+
+impl PositionEditor {
+    pub fn new(board: &Board) -> PositionEditor {
+        let mut board = *board;
+        board.set_chess960(false);
+        PositionEditor { board }
+    }
+
+    pub fn usage() -> &'static str {
+        USAGE
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    //one line of the edit mode, like "put Qw d4" or "castle K off"
+    pub fn apply(&mut self, input: &str) -> Result<EditResult, ChessError> {
+        let usage = || ChessError::InvalidCommand(String::from(USAGE));
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["put", figure, field] => {
+                let mut chars = figure.chars();
+                let figure_type = chars
+                    .next()
+                    .and_then(FieldType::from_letter)
+                    .ok_or_else(usage)?;
+                let color = match (chars.next(), chars.next()) {
+                    (Some('w'), None) => Color::White,
+                    (Some('b'), None) => Color::Black,
+                    _ => return Err(usage()),
+                };
+                self.board
+                    .set_figure(parse_field(field)?, figure_type, color);
+            }
+            ["clear", "all"] => {
+                let active = self.board.active_player();
+                self.board.set_board_empty();
+                self.board.set_active_player(active);
+                for (color, small) in [
+                    (Color::White, true),
+                    (Color::White, false),
+                    (Color::Black, true),
+                    (Color::Black, false),
+                ] {
+                    self.board.set_castling(color, small, false);
+                }
+            }
+            ["clear", field] => {
+                self.board.remove_figure(parse_field(field)?);
+            }
+            ["side", "white" | "w"] => self.board.set_active_player(Color::White),
+            ["side", "black" | "b"] => self.board.set_active_player(Color::Black),
+            ["castle", side, allowed] => {
+                let (color, small) = match *side {
+                    "K" => (Color::White, true),
+                    "Q" => (Color::White, false),
+                    "k" => (Color::Black, true),
+                    "q" => (Color::Black, false),
+                    _ => return Err(usage()),
+                };
+                let allowed = match *allowed {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(usage()),
+                };
+                self.board.set_castling(color, small, allowed);
+            }
+            ["ep", "off"] => self.board.clear_en_passant(),
+            ["ep", field] => {
+                self.board.set_en_passant(parse_field(field)?);
+            }
+            ["show"] => {}
+            ["done"] => {
+                self.board.validate_position()?;
+                return Ok(EditResult::Done);
+            }
+            ["cancel"] => return Ok(EditResult::Cancelled),
+            _ => return Err(usage()),
+        }
+        Ok(EditResult::Editing)
+    }
+}
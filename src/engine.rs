@@ -0,0 +1,270 @@
+use crate::board::Board;
+use crate::evaluation::Evaluation;
+use crate::field::{FieldType, Move};
+use crate::tablebase::Tablebase;
+use crate::transposition::{Bound, TranspositionTable, TtStats};
+
+pub const MATE_SCORE: i32 = 100_000;
+//scores beyond this are mates, stored in the table relative to the position
+const MATE_BOUND: i32 = MATE_SCORE - 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EngineConfig {
+    pub depth: u32,
+    //size of the transposition table in megabytes
+    pub hash_mb: usize,
+}
+
+impl EngineConfig {
+    pub fn new() -> EngineConfig {
+        EngineConfig {
+            depth: 3,
+            hash_mb: 16,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchReport {
+    pub best_move: Option<Move>,
+    //centipawns from the view of the player to move
+    pub score: i32,
+    pub nodes: u64,
+}
+
+//the search with the state kept between the moves of a game
+pub struct BuiltinEngine {
+    pub config: EngineConfig,
+    table: TranspositionTable,
+    tablebase: Option<Tablebase>,
+}
+
+impl BuiltinEngine {
+    pub fn new(config: EngineConfig) -> BuiltinEngine {
+        BuiltinEngine {
+            config,
+            table: TranspositionTable::new(config.hash_mb),
+            tablebase: None,
+        }
+    }
+
+    pub fn set_tablebase(&mut self, tablebase: Option<Tablebase>) {
+        self.tablebase = tablebase;
+    }
+
+    pub fn new_game(&mut self) {
+        self.table.clear();
+    }
+
+    //statistics of the transposition table and its use per thousand
+    pub fn table_stats(&self) -> (TtStats, usize) {
+        (self.table.stats(), self.table.hashfull())
+    }
+
+    //positions in the tablebase are played perfectly, the search plays the rest
+    pub fn choose_move(&mut self, board: &Board) -> Option<Move> {
+        if let Some(tablebase) = self.tablebase.as_mut().filter(|c| c.can_probe(board)) {
+            if let Ok(moves) = tablebase.root_moves(board) {
+                if let Some((best_move, _)) = moves.first() {
+                    return Some(*best_move);
+                }
+            }
+        }
+        search(board, &self.config, &mut self.table).best_move
+    }
+}
+
+pub fn figure_value(figure_type: FieldType) -> i32 {
+    match figure_type {
+        FieldType::Pawn => 100,
+        FieldType::Knight => 320,
+        FieldType::Bishop => 330,
+        FieldType::Tower => 500,
+        FieldType::Queen => 900,
+        FieldType::King | FieldType::None => 0,
+    }
+}
+
+//static evaluation from the view of the player to move
+pub fn evaluate(board: &Board) -> i32 {
+    Evaluation::new(board).score(board.active_player())
+}
+
+//iterative deepening, the table keeps the best moves of the previous iterations and searches
+pub fn search(
+    board: &Board,
+    config: &EngineConfig,
+    table: &mut TranspositionTable,
+) -> SearchReport {
+    table.new_search();
+    let mut report = SearchReport {
+        best_move: None,
+        score: -MATE_SCORE,
+        nodes: 0,
+    };
+    let key = board.zobrist_key();
+    //one copy for the whole search, moves are taken back after searching them
+    let mut board = *board;
+    for depth in 1..=config.depth.max(1) {
+        let tt_move = table.probe(key).and_then(|c| c.best_move);
+        let mut alpha = -MATE_SCORE - 1;
+        let mut best_move = None;
+        for next_move in ordered_moves(&board, tt_move) {
+            let undo = board.make_move(next_move);
+            let score = -alpha_beta(
+                &mut board,
+                depth - 1,
+                -MATE_SCORE - 1,
+                -alpha,
+                1,
+                &mut report.nodes,
+                table,
+            );
+            board.unmake_move(undo);
+            if score > alpha {
+                alpha = score;
+                best_move = Some(next_move);
+            }
+        }
+        if best_move.is_none() {
+            break;
+        }
+        report.best_move = best_move;
+        report.score = alpha;
+        table.store(key, depth, to_table(alpha, 0), Bound::Exact, best_move);
+    }
+    report
+}
+
+//mate scores are stored as distance from the position instead of from the root
+fn to_table(score: i32, ply: i32) -> i32 {
+    if score > MATE_BOUND {
+        score + ply
+    } else if score < -MATE_BOUND {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn from_table(score: i32, ply: i32) -> i32 {
+    if score > MATE_BOUND {
+        score - ply
+    } else if score < -MATE_BOUND {
+        score + ply
+    } else {
+        score
+    }
+}
+
+fn alpha_beta(
+    board: &mut Board,
+    depth: u32,
+    mut alpha: i32,
+    beta: i32,
+    ply: i32,
+    nodes: &mut u64,
+    table: &mut TranspositionTable,
+) -> i32 {
+    *nodes += 1;
+    if depth == 0 {
+        return quiescence(board, alpha, beta, ply, nodes);
+    }
+    let key = board.zobrist_key();
+    let mut tt_move = None;
+    if let Some(entry) = table.probe(key) {
+        tt_move = entry.best_move;
+        if entry.depth >= depth {
+            let score = from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score.clamp(alpha, beta),
+                Bound::Lower if score >= beta => return beta,
+                Bound::Upper if score <= alpha => return alpha,
+                _ => {}
+            }
+        }
+    }
+    let moves = ordered_moves(board, tt_move);
+    if moves.is_empty() {
+        //prefer the shortest mate
+        return if board.is_in_check(board.active_player()) {
+            -MATE_SCORE + ply
+        } else {
+            0
+        };
+    }
+    let mut best_move = None;
+    for next_move in moves {
+        let undo = board.make_move(next_move);
+        let score = -alpha_beta(board, depth - 1, -beta, -alpha, ply + 1, nodes, table);
+        board.unmake_move(undo);
+        if score >= beta {
+            table.store(
+                key,
+                depth,
+                to_table(beta, ply),
+                Bound::Lower,
+                Some(next_move),
+            );
+            return beta;
+        }
+        if score > alpha {
+            alpha = score;
+            best_move = Some(next_move);
+        }
+    }
+    let bound = if best_move.is_some() {
+        Bound::Exact
+    } else {
+        Bound::Upper
+    };
+    table.store(key, depth, to_table(alpha, ply), bound, best_move);
+    alpha
+}
+
+//only takes are searched until the position is quiet
+fn quiescence(board: &mut Board, mut alpha: i32, beta: i32, ply: i32, nodes: &mut u64) -> i32 {
+    *nodes += 1;
+    let moves = ordered_moves(board, None);
+    if moves.is_empty() {
+        return if board.is_in_check(board.active_player()) {
+            -MATE_SCORE + ply
+        } else {
+            0
+        };
+    }
+    let stand_pat = evaluate(board);
+    if stand_pat >= beta {
+        return beta;
+    }
+    alpha = alpha.max(stand_pat);
+    for next_move in moves.into_iter().filter(|m| m.flags.capture) {
+        let undo = board.make_move(next_move);
+        let score = -quiescence(board, -beta, -alpha, ply + 1, nodes);
+        board.unmake_move(undo);
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+//the best move from the table, then the most valuable victim taken by the least valuable attacker
+fn ordered_moves(board: &Board, tt_move: Option<Move>) -> Vec<Move> {
+    let mut moves = board.legal_moves();
+    moves.sort_by_key(|m| {
+        let taken = figure_value(board.get(m.to).get_type());
+        let promotion = m.promotion.map(figure_value).unwrap_or(0);
+        if taken == 0 && promotion == 0 {
+            0
+        } else {
+            -(taken * 10 + promotion - figure_value(board.get(m.from).get_type()) / 10)
+        }
+    });
+    if let Some(i) = tt_move.and_then(|m| moves.iter().position(|c| *c == m)) {
+        let best = moves.remove(i);
+        moves.insert(0, best);
+    }
+    moves
+}
@@ -0,0 +1,374 @@
+use std::fmt;
+
+use crate::board::Board;
+use crate::engine::figure_value;
+use crate::field::{Color, Field, FieldType};
+use crate::render::{glyph, PieceSet};
+use crate::square::Square;
+
+//weights of the evaluation terms in centipawns
+const DOUBLED_PAWN: i32 = -10;
+const ISOLATED_PAWN: i32 = -15;
+//a passed pawn gets the base and the bonus for every row it advanced
+const PASSED_PAWN: i32 = 10;
+const PASSED_PAWN_ROW: i32 = 10;
+//own pawns in front of the king
+const PAWN_SHIELD: i32 = 10;
+//files next to the king without own pawns
+const OPEN_KING_FILE: i32 = -15;
+//fields next to the king the enemy attacks
+const KING_ZONE_ATTACK: i32 = -5;
+//per reachable square
+const KNIGHT_MOBILITY: i32 = 4;
+const BISHOP_MOBILITY: i32 = 5;
+const TOWER_MOBILITY: i32 = 2;
+const QUEEN_MOBILITY: i32 = 1;
+
+//piece-square tables seen from white with the eighth row first
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+#[rustfmt::skip]
+const TOWER_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+#[rustfmt::skip]
+const KING_TABLE: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct EvalTerms {
+    pub material: i32,
+    pub placement: i32,
+    pub pawn_structure: i32,
+    pub king_safety: i32,
+    pub mobility: i32,
+}
+
+impl EvalTerms {
+    pub fn total(&self) -> i32 {
+        self.material + self.placement + self.pawn_structure + self.king_safety + self.mobility
+    }
+}
+
+//the static evaluation split into its terms for both colors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Evaluation {
+    pub white: EvalTerms,
+    pub black: EvalTerms,
+}
+
+impl Evaluation {
+    pub fn new(board: &Board) -> Evaluation {
+        Evaluation {
+            white: Evaluation::terms(board, Color::White),
+            black: Evaluation::terms(board, Color::Black),
+        }
+    }
+
+    //centipawns from the view of the given color
+    pub fn score(&self, color: Color) -> i32 {
+        let score = self.white.total() - self.black.total();
+        if color == Color::Black {
+            -score
+        } else {
+            score
+        }
+    }
+
+    fn terms(board: &Board, color: Color) -> EvalTerms {
+        let mut terms = EvalTerms::default();
+        let own = board
+            .fields()
+            .filter(|c| c.1.get_color() == color)
+            .fold(0, |own, (square, _)| own | square.bit());
+        for (square, field) in board.fields() {
+            if field.get_color() != color {
+                continue;
+            }
+            let (x, y) = (square.rank().number(), square.file().number());
+            let figure_type = field.get_type();
+            terms.material += figure_value(figure_type);
+            terms.placement += Evaluation::placement(figure_type, color, x, y);
+            //empty or enemy squares the figure reaches
+            let reach = (board.attacks(square) & !own).count_ones() as i32;
+            terms.mobility += match figure_type {
+                FieldType::Knight => reach * KNIGHT_MOBILITY,
+                FieldType::Bishop => reach * BISHOP_MOBILITY,
+                FieldType::Tower => reach * TOWER_MOBILITY,
+                FieldType::Queen => reach * QUEEN_MOBILITY,
+                _ => 0,
+            };
+            if figure_type == FieldType::Pawn {
+                terms.pawn_structure += Evaluation::pawn_structure(board, color, x, y);
+            }
+            if figure_type == FieldType::King {
+                terms.king_safety = Evaluation::king_safety(board, color, square);
+            }
+        }
+        terms
+    }
+
+    fn placement(figure_type: FieldType, color: Color, x: i32, y: i32) -> i32 {
+        let table = match figure_type {
+            FieldType::Pawn => &PAWN_TABLE,
+            FieldType::Knight => &KNIGHT_TABLE,
+            FieldType::Bishop => &BISHOP_TABLE,
+            FieldType::Tower => &TOWER_TABLE,
+            FieldType::Queen => &QUEEN_TABLE,
+            FieldType::King => &KING_TABLE,
+            FieldType::None => return 0,
+        };
+        //black reads the table upside down
+        let row = if color == Color::White { 8 - x } else { x - 1 };
+        table[(row * 8 + y - 1) as usize]
+    }
+
+    fn count_pawns(board: &Board, color: Color, y: i32, rows: impl Iterator<Item = i32>) -> i32 {
+        let mut count = 0;
+        for square in rows.filter_map(|x| Square::from_coords(x, y)) {
+            let field = board.get(square);
+            if field.get_type() == FieldType::Pawn && field.get_color() == color {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    //every pawn is judged on its own, so doubled pawns cost for each of them
+    fn pawn_structure(board: &Board, color: Color, x: i32, y: i32) -> i32 {
+        let mut score = 0;
+        if Evaluation::count_pawns(board, color, y, 1..9) > 1 {
+            score += DOUBLED_PAWN;
+        }
+        let neighbors: i32 = [y - 1, y + 1]
+            .iter()
+            .map(|c| Evaluation::count_pawns(board, color, *c, 1..9))
+            .sum();
+        if neighbors == 0 {
+            score += ISOLATED_PAWN;
+        }
+        //no enemy pawn in front of it on its own or the neighboring files
+        let ahead: Vec<i32> = if color == Color::White {
+            (x + 1..9).collect()
+        } else {
+            (1..x).collect()
+        };
+        let blockers: i32 = [y - 1, y, y + 1]
+            .iter()
+            .map(|c| Evaluation::count_pawns(board, color.enemy_color(), *c, ahead.iter().copied()))
+            .sum();
+        if blockers == 0 {
+            let advanced = if color == Color::White { x - 2 } else { 7 - x };
+            score += PASSED_PAWN + PASSED_PAWN_ROW * advanced;
+        }
+        score
+    }
+
+    fn king_safety(board: &Board, color: Color, king: Square) -> i32 {
+        let (x, y) = (king.rank().number(), king.file().number());
+        let forward = if color == Color::White { 1 } else { -1 };
+        //the fields around the king the enemy attacks
+        let attacked = board.attacks(king) & board.attack_map(color.enemy_color());
+        let mut score = attacked.count_ones() as i32 * KING_ZONE_ATTACK;
+        for file in (y - 1).max(1)..=(y + 1).min(8) {
+            let shield = [x + forward, x + 2 * forward].into_iter();
+            if Evaluation::count_pawns(board, color, file, shield) > 0 {
+                score += PAWN_SHIELD;
+            }
+            if Evaluation::count_pawns(board, color, file, 1..9) == 0 {
+                score += OPEN_KING_FILE;
+            }
+        }
+        score
+    }
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = [
+            ("Material", self.white.material, self.black.material),
+            ("Placement", self.white.placement, self.black.placement),
+            (
+                "Pawn structure",
+                self.white.pawn_structure,
+                self.black.pawn_structure,
+            ),
+            (
+                "King safety",
+                self.white.king_safety,
+                self.black.king_safety,
+            ),
+            ("Mobility", self.white.mobility, self.black.mobility),
+            ("Total", self.white.total(), self.black.total()),
+        ];
+        writeln!(f, "{:<16}{:>8}{:>8}{:>8}", "", "White", "Black", "Diff")?;
+        for (name, white, black) in rows {
+            writeln!(
+                f,
+                "{:<16}{:>8}{:>8}{:>+8}",
+                name,
+                white,
+                black,
+                white - black
+            )?;
+        }
+        let score = self.score(Color::White);
+        if score == 0 {
+            write!(f, "The position is equal")
+        } else {
+            let better = if score > 0 {
+                Color::White
+            } else {
+                Color::Black
+            };
+            write!(
+                f,
+                "{} is better by {:.2} pawns",
+                better,
+                score.abs() as f64 / 100.0
+            )
+        }
+    }
+}
+
+//the usual piece values for the material balance shown to players
+const MATERIAL_FIGURES: [(FieldType, i32); 5] = [
+    (FieldType::Queen, 9),
+    (FieldType::Tower, 5),
+    (FieldType::Bishop, 3),
+    (FieldType::Knight, 3),
+    (FieldType::Pawn, 1),
+];
+
+//figures taken from both colors since the start position and the difference in points,
+//promoted pawns count as taken pawns
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Material {
+    //figures taken by white and by black, most valuable first
+    pub taken_by_white: Vec<FieldType>,
+    pub taken_by_black: Vec<FieldType>,
+    //points of white minus points of black
+    pub balance: i32,
+}
+
+impl Material {
+    pub fn new(start: &Board, board: &Board) -> Material {
+        let points = |color: Color| -> i32 {
+            MATERIAL_FIGURES
+                .iter()
+                .map(|(figure_type, value)| Material::count(board, color, *figure_type) * value)
+                .sum()
+        };
+        Material {
+            taken_by_white: Material::taken(start, board, Color::Black),
+            taken_by_black: Material::taken(start, board, Color::White),
+            balance: points(Color::White) - points(Color::Black),
+        }
+    }
+
+    fn count(board: &Board, color: Color, figure_type: FieldType) -> i32 {
+        board
+            .fields()
+            .filter(|(_, field)| field.get_color() == color && field.get_type() == figure_type)
+            .count() as i32
+    }
+
+    //figures of the color missing since the start, figures above the start count came
+    //from pawns
+    fn taken(start: &Board, board: &Board, color: Color) -> Vec<FieldType> {
+        let mut promoted = 0;
+        let mut taken = Vec::new();
+        for (figure_type, _) in MATERIAL_FIGURES {
+            let before = Material::count(start, color, figure_type);
+            let now = Material::count(board, color, figure_type);
+            let missing = if figure_type == FieldType::Pawn {
+                before - now - promoted
+            } else {
+                promoted += (now - before).max(0);
+                before - now
+            };
+            for _ in 0..missing.max(0) {
+                taken.push(figure_type);
+            }
+        }
+        taken
+    }
+
+    //"♟ ♟ ♞ +3" for the figures the color took and its lead
+    pub fn line(&self, color: Color) -> String {
+        let (taken, balance, taken_color) = if color == Color::White {
+            (&self.taken_by_white, self.balance, Color::Black)
+        } else {
+            (&self.taken_by_black, -self.balance, Color::White)
+        };
+        let mut figures: Vec<String> = taken
+            .iter()
+            .map(|c| {
+                let mut field = Field::new();
+                field.set_figure(*c, taken_color);
+                glyph(field, PieceSet::Unicode).to_string()
+            })
+            .collect();
+        if balance > 0 {
+            figures.push(format!("+{}", balance));
+        }
+        figures.join(" ")
+    }
+}
@@ -1,5 +1,7 @@
 use core::fmt;
 
+use crate::square::{Rank, Square};
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum FieldType {
     King,
@@ -24,6 +26,33 @@ impl fmt::Display for FieldType {
         }
     }
 }
+impl FieldType {
+    //letter used in FEN and move notation, white figures are upper case
+    pub fn letter(self) -> char {
+        match self {
+            FieldType::King => 'K',
+            FieldType::Queen => 'Q',
+            FieldType::Bishop => 'B',
+            FieldType::Knight => 'N',
+            FieldType::Tower => 'R',
+            FieldType::Pawn => 'P',
+            FieldType::None => ' ',
+        }
+    }
+
+    pub fn from_letter(c: char) -> Option<FieldType> {
+        match c.to_ascii_uppercase() {
+            'K' => Some(FieldType::King),
+            'Q' => Some(FieldType::Queen),
+            'B' => Some(FieldType::Bishop),
+            'N' => Some(FieldType::Knight),
+            'R' => Some(FieldType::Tower),
+            'P' => Some(FieldType::Pawn),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Color {
     White,
@@ -54,26 +83,33 @@ impl fmt::Display for Color {
 pub struct Field {
     color: Color,
     figure_type: FieldType,
-    x: i32,
-    y: i32,
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ChessError {
     InvalidMoveSyntax(String),
     MoveOutsideOfBoard,
     InvalidFigureMove(FieldType),
-    SameStartAndTargetPos(i32, i32),
-    EmptyStartingField(i32, i32),
+    SameStartAndTargetPos(Square),
+    EmptyStartingField(Square),
     FigureWrongColor(FieldType),
     FigureHasNoColor,
-    FieldAlreadyOwned(i32, i32),
-    PawnMoveBlocked(i32, i32),
+    FieldAlreadyOwned(Square),
+    PawnMoveBlocked(Square),
     PawnCantTakeEmptyField,
-    MoveBlockedByEnemyFigure(i32, i32),
-    CastlingBlockedByFigure(i32, i32),
+    MoveBlockedByEnemyFigure(Square),
+    CastlingBlockedByFigure(Square),
     CastlingNoPossibleAlreadyMoved,
-    CastlingNotPossibleWhenKingUnderAttack(i32, i32),
-    CantMoveFromToAsKingWillBeUnderAttack(i32, i32, i32, i32),
+    CastlingNotPossibleWhenKingUnderAttack(Square),
+    InvalidDrop(FieldType),
+    CantMoveFromToAsKingWillBeUnderAttack(Square, Square),
+    EngineError(String),
+    InvalidFen(String),
+    InvalidPgn(String),
+    InvalidCommand(String),
+    FileError(String),
+    TournamentError(String),
+    TerminalError(String),
+    InvalidPosition(String),
 }
 
 impl fmt::Display for ChessError {
@@ -82,125 +118,115 @@ impl fmt::Display for ChessError {
             ChessError::InvalidMoveSyntax(c) => write!(f, "Error: Invalid move syntax: {}", c),
             ChessError::MoveOutsideOfBoard => write!(f, "Error: Move outside of board"),
             ChessError::InvalidFigureMove(c) => write!(f, "Error: {} can't move this way", c),
-            ChessError::SameStartAndTargetPos(x, y) => write!(
-                f,
-                "Error: Can't move figure {}{} to itself.",
-                Field::y_to_letter(y),
-                x
-            ),
-            ChessError::EmptyStartingField(x, y) => write!(
-                f,
-                "Error: Empty starting field {}{}",
-                Field::y_to_letter(y),
-                x
-            ),
+            ChessError::SameStartAndTargetPos(c) => {
+                write!(f, "Error: Can't move figure {} to itself.", c)
+            }
+            ChessError::EmptyStartingField(c) => write!(f, "Error: Empty starting field {}", c),
             ChessError::FigureWrongColor(c) => write!(f, "Error: Figure {} is the wrong color", c),
             ChessError::FigureHasNoColor => write!(f, "Error: Figure has no color"), //Debug Error
-            ChessError::FieldAlreadyOwned(x, y) => write!(
-                f,
-                "Error: You already have a figure on {}{}",
-                Field::y_to_letter(y),
-                x
-            ),
-            ChessError::PawnMoveBlocked(x, y) => write!(
+            ChessError::FieldAlreadyOwned(c) => {
+                write!(f, "Error: You already have a figure on {}", c)
+            }
+            ChessError::PawnMoveBlocked(c) => write!(
                 f,
-                "Error: Can't move pawn forward because a figure is alreay on the field {}{}",
-                Field::y_to_letter(y),
-                x
+                "Error: Can't move pawn forward because a figure is alreay on the field {}",
+                c
             ),
             ChessError::PawnCantTakeEmptyField => {
                 write!(f, "Error: Pawn can't take an empty field")
             }
-            ChessError::MoveBlockedByEnemyFigure(x, y) => write!(
-                f,
-                "Error: Move blocked by enemy figure at {}{}",
-                Field::y_to_letter(y),
-                x
-            ),
+            ChessError::MoveBlockedByEnemyFigure(c) => {
+                write!(f, "Error: Move blocked by enemy figure at {}", c)
+            }
             ChessError::CastlingNoPossibleAlreadyMoved => write!(
                 f,
                 "Error: Castling not possible since figures have already moved"
             ),
-            ChessError::CastlingBlockedByFigure(x, y) => write!(
+            ChessError::CastlingBlockedByFigure(c) => write!(
                 f,
-                "Error: Castling not possible since a figure is blocking it at {}{}",
-                Field::y_to_letter(y),
-                x
+                "Error: Castling not possible since a figure is blocking it at {}",
+                c
             ),
-            ChessError::CastlingNotPossibleWhenKingUnderAttack(x, y) => write!(
+            ChessError::CastlingNotPossibleWhenKingUnderAttack(c) => write!(
                 f,
-                "Error: Castling not possible because King is under attack at {}{}",
-                Field::y_to_letter(y),
-                x
+                "Error: Castling not possible because King is under attack at {}",
+                c
             ),
-            ChessError::CantMoveFromToAsKingWillBeUnderAttack(from_x, from_y, to_x, to_y,) => write!(
+            ChessError::CantMoveFromToAsKingWillBeUnderAttack(from, to) => write!(
                 f,
-                "Error: Moving from {}{} to {}{} is not possible because King would be under attack",
-                Field::y_to_letter(from_y),
-                from_x,
-                Field::y_to_letter(to_y),
-                to_x
+                "Error: Moving from {} to {} is not possible because King would be under attack",
+                from, to
             ),
+            ChessError::InvalidDrop(c) => write!(f, "Error: {} can't be dropped there", c),
+            ChessError::EngineError(c) => write!(f, "Error: Engine: {}", c),
+            ChessError::InvalidFen(c) => write!(f, "Error: Invalid FEN: {}", c),
+            ChessError::InvalidPgn(c) => write!(f, "Error: Invalid PGN: {}", c),
+            ChessError::InvalidCommand(c) => write!(f, "Error: Invalid command, usage: {}", c),
+            ChessError::FileError(c) => write!(f, "Error: File: {}", c),
+            ChessError::TournamentError(c) => write!(f, "Error: Tournament: {}", c),
+            ChessError::TerminalError(c) => write!(f, "Error: Terminal: {}", c),
+            ChessError::InvalidPosition(c) => write!(f, "Error: Invalid position: {}", c),
         }
     }
 }
 
+impl From<std::io::Error> for ChessError {
+    fn from(e: std::io::Error) -> ChessError {
+        ChessError::FileError(e.to_string())
+    }
+}
+
 impl Field {
     pub fn new() -> Field {
         Field {
             color: Color::None,
             figure_type: FieldType::None,
-            x: 0,
-            y: 0,
         }
     }
-    pub fn validate_move(self, x: i32, y: i32) -> Result<(), ChessError> {
+    pub fn validate_move(self, from: Square, to: Square) -> Result<(), ChessError> {
         //basic move checks only (without board state)
-        //can't move out of board
-        if (x < 0) || (y < 0) || (x > 8) || (y > 8) {
-            return Err(ChessError::MoveOutsideOfBoard);
-        }
-
         //can't move to same position
-        if (self.x == x) && (self.y == y) {
-            return Err(ChessError::SameStartAndTargetPos(x, y));
+        if from == to {
+            return Err(ChessError::SameStartAndTargetPos(to));
         }
+        let (from_x, from_y) = (from.rank().number(), from.file().number());
+        let (x, y) = (to.rank().number(), to.file().number());
 
         match self.figure_type {
             FieldType::King => {
                 //basic king move check
-                if (self.x - x > 1) || (x - self.x > 1) || (self.y - y > 1) || (y - self.y > 1) {
+                if (from_x - x > 1) || (x - from_x > 1) || (from_y - y > 1) || (y - from_y > 1) {
                     return Err(ChessError::InvalidFigureMove(self.figure_type));
                 }
             }
             FieldType::Queen => {
                 //basic tower or basic bishop move check
-                if ((self.x - self.y != x - y) && (self.x + self.y != x + y))
-                    && ((self.x != x) && (self.y != y))
+                if ((from_x - from_y != x - y) && (from_x + from_y != x + y))
+                    && ((from_x != x) && (from_y != y))
                 {
                     return Err(ChessError::InvalidFigureMove(self.figure_type));
                 }
             }
             FieldType::Bishop => {
                 //basic bishop move check TODO: needs better check
-                if (self.x - self.y != x - y) && (self.x + self.y != x + y) {
+                if (from_x - from_y != x - y) && (from_x + from_y != x + y) {
                     return Err(ChessError::InvalidFigureMove(self.figure_type));
                 }
             }
             FieldType::Tower => {
                 //basic tower move check
-                if (self.x != x) && (self.y != y) {
+                if (from_x != x) && (from_y != y) {
                     return Err(ChessError::InvalidFigureMove(self.figure_type));
                 }
             }
             FieldType::Knight => {
                 //basic knight move
-                if (self.x + 2 == x) || (self.x - 2 == x) {
-                    if (self.y - y != 1) && (y - self.y != 1) {
+                if (from_x + 2 == x) || (from_x - 2 == x) {
+                    if (from_y - y != 1) && (y - from_y != 1) {
                         return Err(ChessError::InvalidFigureMove(self.figure_type));
                     }
-                } else if (self.y + 2 == y) || (self.y - 2 == y) {
-                    if (self.x - x != 1) && (x - self.x != 1) {
+                } else if (from_y + 2 == y) || (from_y - 2 == y) {
+                    if (from_x - x != 1) && (x - from_x != 1) {
                         return Err(ChessError::InvalidFigureMove(self.figure_type));
                     }
                 } else {
@@ -209,42 +235,42 @@ impl Field {
             }
             FieldType::Pawn => {
                 //can't move more than one y
-                if (self.y - y > 1) || (y - self.y > 1) {
+                if (from_y - y > 1) || (y - from_y > 1) {
                     return Err(ChessError::InvalidFigureMove(self.figure_type));
                 }
                 match self.color {
                     Color::White => {
                         //on pawn starting position
-                        if self.x == 2 {
+                        if from_x == 2 {
                             //can't move by 2 and change y
-                            if (self.y != y) && (x - self.x > 1) {
+                            if (from_y != y) && (x - from_x > 1) {
                                 return Err(ChessError::InvalidFigureMove(self.figure_type));
                             }
                             //can't move by more than 2
-                            if x - self.x > 2 {
+                            if x - from_x > 2 {
                                 return Err(ChessError::InvalidFigureMove(self.figure_type));
                             }
                         } else {
                             //any other position
-                            if x - self.x > 1 {
+                            if x - from_x > 1 {
                                 return Err(ChessError::InvalidFigureMove(self.figure_type));
                             }
                         }
                     }
                     Color::Black => {
                         //on pawn starting position
-                        if self.x == 7 {
+                        if from_x == 7 {
                             //can't move by 2 and change y
-                            if (self.y != y) && (x - self.x > 1) {
+                            if (from_y != y) && (x - from_x > 1) {
                                 return Err(ChessError::InvalidFigureMove(self.figure_type));
                             }
                             //can't move by more than 2
-                            if self.x - x > 2 {
+                            if from_x - x > 2 {
                                 return Err(ChessError::InvalidFigureMove(self.figure_type));
                             }
                         } else {
                             //any other postion
-                            if self.x - x > 1 {
+                            if from_x - x > 1 {
                                 return Err(ChessError::InvalidFigureMove(self.figure_type));
                             }
                         }
@@ -256,79 +282,58 @@ impl Field {
                 }
             }
             FieldType::None => {
-                return Err(ChessError::EmptyStartingField(self.get_x(), self.get_y()));
+                return Err(ChessError::EmptyStartingField(from));
             }
         }
 
         Ok(())
     }
 
-    pub fn finalize_move(&mut self, x: i32, y: i32) {
-        self.x = x;
-        self.y = y;
-    }
-
-    pub fn get_x(&self) -> i32 {
-        self.x
-    }
-    pub fn get_y(&self) -> i32 {
-        self.y
-    }
     pub fn get_color(&self) -> Color {
         self.color
     }
     pub fn get_type(&self) -> FieldType {
         self.figure_type
     }
-    pub fn set_empty_with_pos(&mut self, x: i32, y: i32) {
+    pub fn set_empty(&mut self) {
         self.color = Color::None;
         self.figure_type = FieldType::None;
-        self.x = x;
-        self.y = y;
     }
     pub fn set_figure(&mut self, figure_type: FieldType, color: Color) {
         self.figure_type = figure_type;
         self.color = color;
     }
-    pub fn y_to_letter(y: &i32) -> char {
-        match y {
-            1 => 'a',
-            2 => 'b',
-            3 => 'c',
-            4 => 'd',
-            5 => 'e',
-            6 => 'f',
-            7 => 'g',
-            8 => 'h',
-            _ => ' ',
-        }
-    }
-
-    pub fn check_promote_pawn(&mut self) {
-        if self.figure_type == FieldType::Pawn {
-            if (self.color == Color::White && self.x == 8)
-                || (self.color == Color::Black && self.x == 1)
-            {
-                let mut user_input = String::from("");
-                println!(
-                    "{} Pawn at {}{} can be promoted to queen, bishop, knight or tower (default: queen):",
-                    self.color, Field::y_to_letter(&self.y), self.x
-                );
-                match std::io::stdin().read_line(&mut user_input) {
-                    Ok(_) => match &user_input.to_lowercase().trim()[..] {
-                        "bishop" => self.figure_type = FieldType::Bishop,
-                        "tower" => self.figure_type = FieldType::Tower,
-                        "knight" => self.figure_type = FieldType::Knight,
-                        _ => self.figure_type = FieldType::Queen,
-                    },
-                    Err(_) => self.figure_type = FieldType::Queen,
-                }
 
-                println!(
-                    "{} Pawn has been promoted to {}",
-                    self.color, self.figure_type
-                );
+    //a pawn reaching the last rank on the square is promoted,
+    //promotion: None asks the user for the figure, otherwise the given figure is used silently
+    pub fn check_promote_pawn(&mut self, square: Square, promotion: Option<FieldType>) {
+        if self.figure_type == FieldType::Pawn
+            && ((self.color == Color::White && square.rank() == Rank::Eighth)
+                || (self.color == Color::Black && square.rank() == Rank::First))
+        {
+            if let Some(figure_type) = promotion {
+                self.figure_type = figure_type;
+                return;
             }
+            let mut user_input = String::from("");
+            println!(
+                "{} Pawn at {} can be promoted to queen, bishop, knight or tower (default: queen):",
+                self.color, square
+            );
+            match std::io::stdin().read_line(&mut user_input) {
+                Ok(_) => match user_input.to_lowercase().trim() {
+                    "bishop" => self.figure_type = FieldType::Bishop,
+                    "tower" => self.figure_type = FieldType::Tower,
+                    "knight" => self.figure_type = FieldType::Knight,
+                    _ => self.figure_type = FieldType::Queen,
+                },
+                Err(_) => self.figure_type = FieldType::Queen,
+            }
+
+            println!(
+                "{} Pawn has been promoted to {}",
+                self.color, self.figure_type
+            );
         }
     }
 }
@@ -358,3 +363,176 @@ impl fmt::Display for Field {
         }
     }
 }
+
+//what a move does besides going from one field to another, only the position tells,
+//see Board::annotate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MoveFlags {
+    pub capture: bool,
+    pub en_passant: bool,
+    //the king moving two files or taking its own tower
+    pub castling: bool,
+}
+
+//a single move between two fields, written as "e2e4", "e7e8q" or "N@f3"
+#[derive(Clone, Copy, Debug)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<FieldType>,
+    //figure put from the pocket on the target field, the start field is the target as well
+    pub drop: Option<FieldType>,
+    //empty for parsed moves
+    pub flags: MoveFlags,
+}
+
+//the flags follow from the position, so a parsed move equals the generated one
+impl PartialEq for Move {
+    fn eq(&self, other: &Move) -> bool {
+        self.from == other.from
+            && self.to == other.to
+            && self.promotion == other.promotion
+            && self.drop == other.drop
+    }
+}
+
+impl Eq for Move {}
+
+impl Move {
+    pub fn new(from: Square, to: Square) -> Move {
+        Move {
+            from,
+            to,
+            promotion: None,
+            drop: None,
+            flags: MoveFlags::default(),
+        }
+    }
+
+    pub fn new_drop(figure_type: FieldType, square: Square) -> Move {
+        let mut result = Move::new(square, square);
+        result.drop = Some(figure_type);
+        result
+    }
+
+    pub fn from_uci(input: &str) -> Result<Move, ChessError> {
+        let error = || ChessError::InvalidMoveSyntax(input.to_string());
+        let input = input.trim();
+        //drops like "N@f3"
+        if let Some((figure, square)) = input.split_once('@') {
+            let mut letters = figure.chars();
+            return match (
+                letters.next().and_then(FieldType::from_letter),
+                letters.next(),
+            ) {
+                (Some(FieldType::King), _) | (None, _) | (_, Some(_)) => Err(error()),
+                (Some(figure_type), None) => Ok(Move::new_drop(
+                    figure_type,
+                    Square::parse(square).ok_or_else(error)?,
+                )),
+            };
+        }
+        if !input.is_ascii() || (input.len() != 4 && input.len() != 5) {
+            return Err(error());
+        }
+        let (from, rest) = input.split_at(2);
+        let (to, promotion) = rest.split_at(2);
+        let mut result = Move::new(
+            Square::parse(from).ok_or_else(error)?,
+            Square::parse(to).ok_or_else(error)?,
+        );
+        if let Some(c) = promotion.chars().next() {
+            result.promotion = match FieldType::from_letter(c) {
+                Some(FieldType::King) | Some(FieldType::Pawn) | None => return Err(error()),
+                figure_type => figure_type,
+            };
+        }
+        Ok(result)
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(figure_type) = self.drop {
+            return write!(f, "{}@{}", figure_type.letter(), self.to);
+        }
+        write!(f, "{}{}", self.from, self.to)?;
+        match self.promotion {
+            Some(figure_type) => write!(f, "{}", figure_type.letter().to_ascii_lowercase()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    //the color is the winner
+    Checkmate(Color),
+    Resignation(Color),
+    Forfeit(Color),
+    Stalemate,
+    FiftyMoveRule,
+    ThreefoldRepetition,
+    InsufficientMaterial,
+    MoveLimit,
+    //won by the rules of a variant
+    KingOfTheHill(Color),
+    ThreeChecks(Color),
+    KingExploded(Color),
+    NoMovesLeft(Color),
+    AllFiguresTaken(Color),
+    RaceWon(Color),
+    RaceDrawn,
+}
+
+impl Outcome {
+    pub fn winner(self) -> Option<Color> {
+        match self {
+            Outcome::Checkmate(c)
+            | Outcome::Resignation(c)
+            | Outcome::Forfeit(c)
+            | Outcome::KingOfTheHill(c)
+            | Outcome::ThreeChecks(c)
+            | Outcome::KingExploded(c)
+            | Outcome::NoMovesLeft(c)
+            | Outcome::AllFiguresTaken(c)
+            | Outcome::RaceWon(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    //result as written in PGN
+    pub fn result(self) -> &'static str {
+        match self.winner() {
+            Some(Color::White) => "1-0",
+            Some(Color::Black) => "0-1",
+            _ => "1/2-1/2",
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Checkmate(c) => write!(f, "Checkmate, {} wins", c),
+            Outcome::Resignation(c) => write!(f, "{} resigned, {} wins", c.enemy_color(), c),
+            Outcome::Forfeit(c) => write!(f, "{} forfeited, {} wins", c.enemy_color(), c),
+            Outcome::Stalemate => write!(f, "Stalemate, draw"),
+            Outcome::FiftyMoveRule => write!(f, "Fifty move rule, draw"),
+            Outcome::ThreefoldRepetition => write!(f, "Threefold repetition, draw"),
+            Outcome::InsufficientMaterial => write!(f, "Insufficient material, draw"),
+            Outcome::MoveLimit => write!(f, "Move limit reached, draw"),
+            Outcome::KingOfTheHill(c) => write!(f, "King reached the center, {} wins", c),
+            Outcome::ThreeChecks(c) => write!(f, "Third check, {} wins", c),
+            Outcome::KingExploded(c) => {
+                write!(f, "{} King exploded, {} wins", c.enemy_color(), c)
+            }
+            Outcome::NoMovesLeft(c) => write!(f, "{} has no moves left and wins", c),
+            Outcome::AllFiguresTaken(c) => {
+                write!(f, "All {} figures taken, {} wins", c.enemy_color(), c)
+            }
+            Outcome::RaceWon(c) => write!(f, "King reached the eighth row, {} wins", c),
+            Outcome::RaceDrawn => write!(f, "Both kings reached the eighth row, draw"),
+        }
+    }
+}
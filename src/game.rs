@@ -1,23 +1,93 @@
-use crate::board::Board;
-use crate::field::{ChessError, Color};
-use std::collections::VecDeque;
+use crate::analysis::GameAnalysis;
+use crate::animation::{self, AnimationOptions};
+use crate::board::{Board, START_FEN};
+use crate::book::{self, OpeningBook};
+use crate::diagram::{self, DiagramOptions};
+use crate::editor::{EditResult, PositionEditor};
+use crate::engine::{self, EngineConfig};
+use crate::evaluation::{Evaluation, Material};
+use crate::field::{ChessError, Color, FieldType, Move, Outcome};
+use crate::handicap::Handicap;
+use crate::pgn::{self, PgnGame};
+use crate::player::Player;
+use crate::render::{self, RenderOptions};
+use crate::repertoire::{DrillAnswer, Repertoire, RepertoireDrill, Schedule};
+use crate::square::{File, Rank, Square};
+use crate::tablebase::Tablebase;
+use crate::transposition::TranspositionTable;
+use crate::tui::{Clocks, Key, Terminal, View};
+use crate::uci::{SearchLimit, UciEngine};
+use crate::variant::{Variant, VariantUndo};
+use std::fs;
+use std::time::Duration;
+
+//a move of the game with what is needed to take it back
+struct Played {
+    undo: VariantUndo,
+    //Zobrist key of the position before the move for repetitions
+    key: u64,
+    //the side to move was in check before the move, Three-check counts them
+    checked: Option<Color>,
+}
 
 pub struct Game {
-    history: VecDeque<Board>,
+    history: Vec<Played>,
+    //the position the game started from
+    start: Board,
+    start_fen: String,
+    moves: Vec<Move>,
     board: Board,
     turn: i32,
+    white_player: Player,
+    black_player: Player,
+    //engine players take their moves from the book while it knows the position
+    book: Option<OpeningBook>,
+    //Syzygy tables for the tb command, engine players get the same directory
+    tablebase: Option<Tablebase>,
+    //the suggested move for the position after this many moves
+    hint: Option<(usize, Move)>,
+    //hints given before the move with the index, written to the game record
+    hints: Vec<(usize, String)>,
+    //the last finished game for the analysis after it
+    finished: Option<PgnGame>,
+    //the user's moves are checked against the repertoire, the program plays the other color
+    drill: Option<RepertoireDrill>,
+    //Chess960 start position of the games, None for standard chess
+    chess960: Option<u32>,
+    //rules of the games, engines and hints only know standard chess
+    variant: Variant,
+    //odds and the color giving them, standard chess only
+    handicap: Option<(Handicap, Color)>,
+    //colored board output, None prints the plain board
+    render: Option<RenderOptions>,
 }
 
 impl Game {
     pub fn new() -> Game {
         Game {
-            history: VecDeque::new(),
+            history: Vec::new(),
+            start: Board::new(),
+            start_fen: START_FEN.to_string(),
+            moves: Vec::new(),
             board: Board::new(),
             turn: 0,
+            white_player: Player::Human,
+            black_player: Player::Human,
+            book: None,
+            tablebase: None,
+            hint: None,
+            hints: Vec::new(),
+            finished: None,
+            drill: None,
+            chess960: None,
+            variant: Variant::Standard,
+            handicap: None,
+            render: None,
         }
     }
 
-    pub fn concede(&mut self) -> Result<(), std::io::Error> {
+    //Some when the active player really concedes
+    pub fn concede(&mut self) -> Result<Option<Outcome>, std::io::Error> {
         println!(
             "Are you sure you want to concede ({})? (yes, no)",
             self.board.active_player()
@@ -25,206 +95,1251 @@ impl Game {
 
         let mut user_input = String::from("");
         std::io::stdin().read_line(&mut user_input)?;
-        if &user_input == "yes" {
-            if self.board.active_player() == Color::White {
-                println!("Player White conceded!");
-                println!("Player Black wins the game after {} turns!", self.turn);
-                println!("");
-            } else {
-                println!("Player White conceded!");
-                println!("Player Black wins the game after {} turns!", self.turn);
-                println!("");
+        if user_input.trim() == "yes" {
+            return Ok(Some(Outcome::Resignation(
+                self.board.active_player().enemy_color(),
+            )));
+        }
+        println!("Cancelled...");
+        Ok(None)
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.moves.clear();
+        self.hint = None;
+        self.hints.clear();
+        self.turn = 0;
+        let start = match (self.variant, self.chess960) {
+            (Variant::Standard, Some(number)) => self.board.set_board_960(number),
+            (Variant::Standard, None) => {
+                match self.handicap {
+                    Some((handicap, giver)) => self.board = handicap.start_board(giver),
+                    None => self.board.set_board_start(),
+                }
+                Ok(())
+            }
+            (variant, _) => variant.start_board().map(|c| self.board = c),
+        };
+        if let Err(e) = start {
+            println!("{}", e);
+        }
+        self.start = self.board;
+        self.start_fen = self.board.fen();
+        for player in [&mut self.white_player, &mut self.black_player] {
+            if let Err(e) = player.new_game() {
+                println!("{}", e);
+                *player = Player::Human;
             }
-        } else {
-            println!("Cancelled...")
         }
+    }
 
-        Ok(())
+    //game end rules including repetitions of the current position
+    pub fn outcome(&self) -> Option<Outcome> {
+        let checked = self.history.iter().filter_map(|c| c.checked);
+        if let Some(outcome) = self.variant.outcome(&self.board, checked) {
+            return Some(outcome);
+        }
+        //the current position isn't part of the history
+        if self
+            .board
+            .count_repetitions(self.history.iter().map(|c| c.key))
+            >= 2
+        {
+            return Some(Outcome::ThreefoldRepetition);
+        }
+        None
+    }
+
+    //the game so far, outcome None is written as an unfinished game
+    pub fn record(&self, outcome: Option<Outcome>) -> PgnGame {
+        let mut game = PgnGame::from_game(
+            "Casual game",
+            &self.white_player.name(),
+            &self.black_player.name(),
+            &self.start_fen,
+            &self.moves,
+            outcome,
+        );
+        game.move_comments = self.hints.clone();
+        if self.chess960.is_some() {
+            game.set_tag("Variant", "Chess960");
+        }
+        if self.variant != Variant::Standard {
+            game.set_tag("Variant", &self.variant.to_string());
+        }
+        if let Some((handicap, giver)) = self.handicap {
+            game.set_tag("Handicap", &format!("{} given by {}", handicap, giver));
+        }
+        game
     }
 
     pub fn game_loop(&mut self) -> Result<(), std::io::Error> {
+        self.run(false)?;
+        Ok(())
+    }
+
+    //plays a single game, None when the players left before it ended
+    pub fn play_single(&mut self) -> Result<Option<Outcome>, std::io::Error> {
+        self.run(true)
+    }
+
+    fn run(&mut self, single: bool) -> Result<Option<Outcome>, std::io::Error> {
         let mut user_input = String::from("");
-        self.history.clear();
-        self.board.set_board_start();
-        println!("{}", self.board);
+        self.reset();
+        self.show_board();
         loop {
-            user_input.clear();
-            std::io::stdin().read_line(&mut user_input)?;
-            if user_input.starts_with("exit") {
-                break;
-            }
-            if user_input.starts_with("concede") {
-                self.concede()?;
-                self.history.clear();
-                self.board.set_board_start();
+            let mut outcome = self.outcome();
+            if !self.player(self.board.active_player()).is_human() && outcome.is_none() {
+                match self.engine_move() {
+                    Ok(_) => self.show_board(),
+                    Err(e) => {
+                        //hand the color back to the user instead of asking the engine again
+                        println!("{}", e);
+                        self.set_player(self.board.active_player(), Player::Human);
+                    }
+                }
                 continue;
             }
-            if user_input.starts_with("undo") {
-                self.undo();
-                continue;
+
+            if outcome.is_none() {
+                user_input.clear();
+                std::io::stdin().read_line(&mut user_input)?;
+                if user_input.starts_with("exit") {
+                    return Ok(None);
+                }
+                if user_input.starts_with("edit") {
+                    self.edit()?;
+                    continue;
+                }
+                if user_input.starts_with("concede") {
+                    outcome = self.concede()?;
+                } else {
+                    self.command(&user_input);
+                    continue;
+                }
             }
-            match self.eval(&user_input) {
-                Ok(_) => {
-                    println!("{}", self.board)
+
+            if let Some(outcome) = outcome {
+                println!("Game over after {} turns: {}", self.turn, outcome);
+                println!("Type analysis for a review of the game");
+                println!();
+                self.finished = Some(self.record(Some(outcome)));
+                if single {
+                    return Ok(Some(outcome));
                 }
-                Err(e) => {
-                    println!("{}", e)
+                //two engines would start the next game right away
+                if !self.white_player.is_human() && !self.black_player.is_human() {
+                    self.white_player = Player::Human;
+                    self.black_player = Player::Human;
                 }
+                self.reset();
+                self.show_board();
             }
         }
-        Ok(())
     }
 
-    fn eval(&mut self, input: &String) -> Result<(), ChessError> {
-        for entry in input.split("\n") {
-            if entry == "" {
-                continue;
+    //the edit mode sets up a position from the current one, play starts from it once it
+    //passes the checks and "cancel" goes back to the game
+    fn edit(&mut self) -> Result<(), std::io::Error> {
+        let mut editor = PositionEditor::new(&self.board);
+        println!("Edit mode: {}", PositionEditor::usage());
+        let mut input = String::new();
+        loop {
+            input.clear();
+            if std::io::stdin().read_line(&mut input)? == 0 {
+                return Ok(());
             }
-            if self.board.castling(entry, true)? {
-                self.history.push_back(self.board);
-                self.board.castling(entry, false)?;
-                self.turn += 1;
+            match editor.apply(&input) {
+                Ok(EditResult::Editing) => {
+                    match &self.render {
+                        Some(options) => println!("{}", render::render(editor.board(), options)),
+                        None => println!("{}", editor.board()),
+                    }
+                    println!("{}", editor.board().fen());
+                }
+                Ok(EditResult::Done) => {
+                    self.start_from(*editor.board());
+                    println!("Playing from {}", self.start_fen);
+                    self.show_board();
+                    return Ok(());
+                }
+                Ok(EditResult::Cancelled) => {
+                    self.show_board();
+                    return Ok(());
+                }
+                Err(e) => println!("{}", e),
+            }
+        }
+    }
 
-                self.board
-                    .set_active_player(self.board.active_player().enemy_color());
+    //a set up position is played with the standard rules
+    pub fn start_from(&mut self, board: Board) {
+        self.variant = Variant::Standard;
+        self.chess960 = None;
+        self.handicap = None;
+        self.drill = None;
+        self.board = board;
+        self.start = board;
+        self.start_fen = self.board.fen();
+        self.history.clear();
+        self.moves.clear();
+        self.hint = None;
+        self.hints.clear();
+        self.turn = 0;
+    }
 
-                return Ok(());
+    //full-screen mode with the board kept on the screen, figures are moved with the cursor
+    //and everything else is typed into the command line
+    pub fn tui_loop(&mut self) -> Result<(), ChessError> {
+        let terminal = Terminal::open()?;
+        self.reset();
+        let mut clocks = Clocks::new();
+        let mut flipped = !self.white_player.is_human() && self.black_player.is_human();
+        let mut cursor = Square::new(File::E, if flipped { Rank::Seventh } else { Rank::Second });
+        let mut selected: Option<Square> = None;
+        //a promotion waiting for the figure
+        let mut promotion: Option<Move> = None;
+        let mut command = String::new();
+        let mut status = String::from("White to move");
+        let mut scroll = 0;
+        let mut over = false;
+        loop {
+            let color = self.board.active_player();
+            if !over {
+                if let Some(outcome) = self.outcome() {
+                    over = true;
+                    self.finished = Some(self.record(Some(outcome)));
+                    status = format!("Game over: {}, type new, analysis or exit", outcome);
+                }
+            }
+            clocks.run(if over { None } else { Some(color) });
+            let targets: Vec<Square> = match selected {
+                Some(from) => self
+                    .variant
+                    .legal_moves(&self.board)
+                    .into_iter()
+                    .filter(|m| m.from == from && m.drop.is_none())
+                    .map(|m| m.to)
+                    .collect(),
+                None => Vec::new(),
             };
-
-            let next_move = entry.trim().split_once(" ");
-            match next_move {
-                Some(c) => {
-                    if c.0.len() != 2 || c.1.len() != 2 {
-                        return Err(ChessError::InvalidMoveSyntax(entry.to_string()));
+            //black starting from a position gets an empty first half move
+            let mut moves: Vec<String> = replay(self.variant, self.start, &self.moves)
+                .iter()
+                .zip(self.moves.iter())
+                .map(|(board, next_move)| pgn::to_san(board, *next_move))
+                .collect();
+            if self.start.active_player() == Color::Black {
+                moves.insert(0, String::from(".."));
+            }
+            let thinking = !over && !self.player(color).is_human();
+            let material = self.material();
+            let view = View {
+                board: &self.board,
+                flipped,
+                cursor,
+                selected,
+                targets: targets.clone(),
+                last_move: self.moves.last().copied(),
+                moves,
+                scroll,
+                clocks: clocks.times(),
+                material: [material.line(Color::White), material.line(Color::Black)],
+                status: if thinking {
+                    format!("{} is thinking", self.player(color).name())
+                } else {
+                    status.clone()
+                },
+                command: command.clone(),
+            };
+            terminal.draw(&view.render());
+            if thinking {
+                status = match self.engine_move() {
+                    Ok(_) => format!("{} to move", self.board.active_player()),
+                    Err(e) => {
+                        self.set_player(color, Player::Human);
+                        e.to_string()
                     }
-                    let mut start_pos = c.0.chars();
-                    let from_y = match start_pos.next() {
-                        Some(c) => match c.to_digit(10) {
-                            Some(n) => n as i32,
-                            None => match c {
-                                'a' => 1,
-                                'b' => 2,
-                                'c' => 3,
-                                'd' => 4,
-                                'e' => 5,
-                                'f' => 6,
-                                'g' => 7,
-                                'h' => 8,
-                                _ => 0,
-                            },
-                        },
-                        None => 0,
-                    };
-                    let from_x = match start_pos.next() {
-                        Some(c) => match c.to_digit(10) {
-                            Some(n) => n as i32,
-                            None => match c {
-                                'a' => 1,
-                                'b' => 2,
-                                'c' => 3,
-                                'd' => 4,
-                                'e' => 5,
-                                'f' => 6,
-                                'g' => 7,
-                                'h' => 8,
-                                _ => 0,
-                            },
-                        },
-                        None => 0,
-                    };
+                };
+                scroll = 0;
+                continue;
+            }
 
-                    let mut target_pos = c.1.chars();
-                    let to_y = match target_pos.next() {
-                        Some(c) => match c.to_digit(10) {
-                            Some(n) => n as i32,
-                            None => match c {
-                                'a' => 1,
-                                'b' => 2,
-                                'c' => 3,
-                                'd' => 4,
-                                'e' => 5,
-                                'f' => 6,
-                                'g' => 7,
-                                'h' => 8,
-                                _ => 0,
-                            },
-                        },
-                        None => 0,
+            let key = match terminal.key(Duration::from_millis(250)) {
+                Some(c) => c,
+                None => continue,
+            };
+            let before = self.board;
+            let step = if flipped { -1 } else { 1 };
+            let played = match key {
+                Key::Up => {
+                    cursor = cursor.offset(step, 0).unwrap_or(cursor);
+                    None
+                }
+                Key::Down => {
+                    cursor = cursor.offset(-step, 0).unwrap_or(cursor);
+                    None
+                }
+                Key::Right => {
+                    cursor = cursor.offset(0, step).unwrap_or(cursor);
+                    None
+                }
+                Key::Left => {
+                    cursor = cursor.offset(0, -step).unwrap_or(cursor);
+                    None
+                }
+                Key::PageUp => {
+                    scroll += 1;
+                    None
+                }
+                Key::PageDown => {
+                    scroll = scroll.saturating_sub(1);
+                    None
+                }
+                Key::Escape => {
+                    selected = None;
+                    promotion = None;
+                    command.clear();
+                    None
+                }
+                Key::Backspace => {
+                    command.pop();
+                    None
+                }
+                Key::Char(c) if promotion.is_some() => {
+                    let figure_type = match c.to_ascii_lowercase() {
+                        'q' => Some(FieldType::Queen),
+                        'r' => Some(FieldType::Tower),
+                        'b' => Some(FieldType::Bishop),
+                        'n' => Some(FieldType::Knight),
+                        _ => None,
                     };
-                    let to_x = match target_pos.next() {
-                        Some(c) => match c.to_digit(10) {
-                            Some(n) => n as i32,
-                            None => match c {
-                                'a' => 1,
-                                'b' => 2,
-                                'c' => 3,
-                                'd' => 4,
-                                'e' => 5,
-                                'f' => 6,
-                                'g' => 7,
-                                'h' => 8,
-                                _ => 0,
-                            },
+                    match figure_type {
+                        Some(figure_type) => promotion.take().map(|m| Move {
+                            promotion: Some(figure_type),
+                            ..m
+                        }),
+                        None => None,
+                    }
+                }
+                Key::Char(c) => {
+                    command.push(c);
+                    None
+                }
+                Key::Enter if !command.is_empty() => {
+                    let input = command.trim().to_string();
+                    command.clear();
+                    match input.as_str() {
+                        "exit" | "quit" => return Ok(()),
+                        "flip" => {
+                            flipped = !flipped;
+                            None
+                        }
+                        "new" => {
+                            self.reset();
+                            clocks = Clocks::new();
+                            over = false;
+                            status = String::from("New game");
+                            None
+                        }
+                        "undo" => {
+                            self.undo();
+                            over = false;
+                            None
+                        }
+                        //the editor reads whole lines, a set up position restarts the clocks
+                        "edit" => {
+                            let start_fen = self.start_fen.clone();
+                            terminal.suspend();
+                            self.edit()?;
+                            terminal.resume()?;
+                            if self.start_fen != start_fen {
+                                clocks = Clocks::new();
+                                over = false;
+                                status = format!("Playing from {}", self.start_fen);
+                            }
+                            None
+                        }
+                        _ => match self.parse_typed_move(&input) {
+                            Some(next_move) => Some(next_move),
+                            //other commands print their output in the line mode
+                            None => {
+                                terminal.suspend();
+                                self.command(&input);
+                                println!("Press enter to return to the board");
+                                while terminal.key(Duration::from_secs(60)) != Some(Key::Enter) {}
+                                terminal.resume()?;
+                                over = false;
+                                None
+                            }
                         },
-                        None => 0,
-                    };
-                    if (from_x * from_y * to_x * to_y == 0)
-                        || from_x > 8
-                        || from_y > 8
-                        || to_x > 8
-                        || to_y > 8
-                    {
-                        return Err(ChessError::MoveOutsideOfBoard);
                     }
-                    self.board.validate_move(
-                        from_x,
-                        from_y,
-                        to_x,
-                        to_y,
-                        self.board.active_player(),
-                    )?;
-                    if self.board.is_king_attacked(
-                        self.board.active_player(),
-                        from_x,
-                        from_y,
-                        to_x,
-                        to_y,
-                    ) {
-                        return Err(ChessError::CantMoveFromToAsKingWillBeUnderAttack(
-                            from_x, from_y, to_x, to_y,
-                        ));
+                }
+                Key::Enter if over => None,
+                Key::Enter => {
+                    let field = self.board.get(cursor);
+                    if targets.contains(&cursor) {
+                        let from = selected.take().unwrap_or(cursor);
+                        let next_move = Move::new(from, cursor);
+                        let promotes = self
+                            .variant
+                            .legal_moves(&self.board)
+                            .iter()
+                            .any(|m| (m.from, m.to) == (from, cursor) && m.promotion.is_some());
+                        if promotes {
+                            promotion = Some(next_move);
+                            status = String::from("Promote to q, r, b or n");
+                            None
+                        } else {
+                            Some(next_move)
+                        }
+                    } else if field.get_color() == color && selected != Some(cursor) {
+                        selected = Some(cursor);
+                        None
                     } else {
-                        self.history.push_back(self.board);
-                        match self.board.finalize_move(from_x, from_y, to_x, to_y) {
-                            Some(c) => {
-                                println!("{}", c)
-                            }
-                            None => {}
+                        selected = None;
+                        None
+                    }
+                }
+            };
+            if let Some(next_move) = played {
+                selected = None;
+                if over {
+                    status = String::from("The game is over, type new for another one");
+                    continue;
+                }
+                status = match self.play_move(next_move) {
+                    Ok(_) => {
+                        scroll = 0;
+                        match self.drill_answer(&before) {
+                            Ok(_) => format!("{} to move", self.board.active_player()),
+                            Err(e) => e.to_string(),
                         }
                     }
+                    Err(e) => e.to_string(),
+                };
+            }
+        }
+    }
+
+    //"e4", "Nf3", "e7e8q" or "e2 e4", pawns reaching the last row become queens
+    fn parse_typed_move(&self, input: &str) -> Option<Move> {
+        let mut next_move = pgn::from_san(&self.board, input)
+            .or_else(|_| Move::from_uci(&input.replace(' ', "")))
+            .ok()?;
+        let figure = self.board.get(next_move.from);
+        if figure.get_type() == FieldType::Pawn
+            && matches!(next_move.to.rank(), Rank::First | Rank::Eighth)
+            && next_move.promotion.is_none()
+        {
+            next_move.promotion = Some(FieldType::Queen);
+        }
+        Some(next_move)
+    }
+
+    fn command(&mut self, user_input: &str) {
+        if user_input.starts_with("undo") {
+            self.undo();
+            return;
+        }
+        if user_input.starts_with("engine") || user_input.starts_with("human") {
+            if let Err(e) = self.assign_player(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("book") {
+            if let Err(e) = self.book_command(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("hint") {
+            match self.hint() {
+                Ok(hint) => println!("{}", hint),
+                Err(e) => println!("{}", e),
+            }
+            return;
+        }
+        if user_input.starts_with("analysis") {
+            if let Err(e) = self.analysis(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("eval") {
+            println!("{}", Evaluation::new(&self.board));
+            return;
+        }
+        if user_input.starts_with("tb") {
+            if let Err(e) = self.tablebase_command(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("analyze") {
+            if let Err(e) = self.analyze(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("variant") {
+            if let Err(e) = self.variant_command(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("chess960") {
+            if let Err(e) = self.chess960_command(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("board") {
+            if let Err(e) = self.board_command(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("animation") {
+            if let Err(e) = self.animation_command(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("diagram") {
+            if let Err(e) = self.diagram_command(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("handicap") {
+            if let Err(e) = self.handicap_command(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        if user_input.starts_with("repertoire") {
+            if let Err(e) = self.repertoire_command(user_input) {
+                println!("{}", e);
+            }
+            return;
+        }
+        let before = self.board;
+        match self.eval(user_input) {
+            Ok(_) => {
+                if let Err(e) = self.drill_answer(&before) {
+                    println!("{}", e);
                 }
-                None => return Err(ChessError::InvalidMoveSyntax(entry.to_string())),
+                self.show_board();
             }
-            self.turn += 1;
+            Err(e) => {
+                println!("{}", e)
+            }
+        }
+    }
 
-            self.board
-                .set_active_player(self.board.active_player().enemy_color());
+    fn player(&self, color: Color) -> &Player {
+        if color == Color::White {
+            &self.white_player
+        } else {
+            &self.black_player
+        }
+    }
+
+    fn player_mut(&mut self, color: Color) -> &mut Player {
+        if color == Color::White {
+            &mut self.white_player
+        } else {
+            &mut self.black_player
+        }
+    }
+
+    pub fn set_player(&mut self, color: Color, player: Player) {
+        *self.player_mut(color) = player;
+    }
+
+    //"engine <white|black> <builtin[:depth]|path> [depth n|movetime ms|nodes n]"
+    //or "human <white|black>"
+    fn assign_player(&mut self, input: &str) -> Result<(), ChessError> {
+        let usage = || {
+            ChessError::InvalidCommand(String::from(
+                "engine <white|black> <builtin[:depth]|path> [depth n|movetime ms|nodes n] or human <white|black>",
+            ))
+        };
+        let mut tokens: Vec<&str> = input.split_whitespace().collect();
+        let color = match tokens.get(1) {
+            Some(&"white") => Color::White,
+            Some(&"black") => Color::Black,
+            _ => return Err(usage()),
+        };
+        if tokens[0] == "human" {
+            self.set_player(color, Player::Human);
+            println!("{} is played by a human", color);
+            return Ok(());
+        }
+
+        let limit = Game::parse_limit(&tokens[tokens.len().saturating_sub(2)..]);
+        if limit.is_some() {
+            tokens.truncate(tokens.len() - 2);
         }
+        if tokens.len() < 3 {
+            return Err(usage());
+        }
+        self.standard_only()?;
+        let mut player = Player::from_spec(&tokens[2..].join(" "), limit)?;
+        if let Some(tablebase) = &self.tablebase {
+            player.set_tablebase(tablebase.directory())?;
+        }
+        player.new_game()?;
+        println!("{} is played by {}", color, player.name());
+        self.set_player(color, player);
         Ok(())
     }
 
-    pub fn undo(&mut self) {
-        match self.history.pop_back() {
-            Some(c) => {
-                self.board = c;
+    fn parse_limit(tokens: &[&str]) -> Option<SearchLimit> {
+        if tokens.len() != 2 {
+            return None;
+        }
+        match (tokens[0], tokens[1].parse()) {
+            ("depth", Ok(n)) => Some(SearchLimit::Depth(n as u32)),
+            ("movetime", Ok(n)) => Some(SearchLimit::MoveTime(n)),
+            ("nodes", Ok(n)) => Some(SearchLimit::Nodes(n)),
+            _ => None,
+        }
+    }
+
+    //"analyze <path> [depth n|movetime ms|nodes n]" prints the engine's view of the position
+    fn analyze(&mut self, input: &str) -> Result<(), ChessError> {
+        let mut tokens: Vec<&str> = input.split_whitespace().collect();
+        let limit = Game::parse_limit(&tokens[tokens.len().saturating_sub(2)..]);
+        if limit.is_some() {
+            tokens.truncate(tokens.len() - 2);
+        }
+        if tokens.len() < 2 {
+            return Err(ChessError::InvalidCommand(String::from(
+                "analyze <path> [depth n|movetime ms|nodes n]",
+            )));
+        }
+        let mut engine = UciEngine::new(&tokens[1..].join(" "))?;
+        if let Some(limit) = limit {
+            engine.set_limit(limit);
+        }
+        let result = engine.go(&self.start_fen, &self.moves)?;
+        for info in &result.info {
+            println!("{}: {}", engine.name(), info);
+        }
+        match result.best_move {
+            Some(m) => println!("{}: best move {}", engine.name(), m),
+            None => println!("{}: no legal move", engine.name()),
+        }
+        Ok(())
+    }
+
+    //"book <path>" loads a Polyglot book, "book off" stops using it and "book" lists the book moves
+    fn book_command(&mut self, input: &str) -> Result<(), ChessError> {
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["book"] => {
+                let book = self.book.as_ref().ok_or_else(|| {
+                    ChessError::InvalidCommand(String::from("book <path>, book off or book"))
+                })?;
+                let moves = book.moves(&self.board);
+                if moves.is_empty() {
+                    println!("No book moves in this position");
+                }
+                let total: u32 = moves.iter().map(|c| c.1 as u32).sum();
+                for (next_move, weight) in moves {
+                    println!(
+                        "{} ({}) weight {} ({:.1}%)",
+                        pgn::to_san(&self.board, next_move),
+                        next_move,
+                        weight,
+                        weight as f64 * 100.0 / total.max(1) as f64
+                    );
+                }
+            }
+            ["book", "off"] => {
+                self.book = None;
+                println!("Engines don't use a book");
+            }
+            ["book", ..] => {
+                let book = OpeningBook::open(&tokens[1..].join(" "))?;
+                println!("Book with {} entries loaded", book.len());
+                self.book = Some(book);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    //"tb <directory>" loads Syzygy tables, "tb" probes the current position
+    fn tablebase_command(&mut self, input: &str) -> Result<(), ChessError> {
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        if tokens.len() > 1 {
+            let tablebase = Tablebase::open(&tokens[1..].join(" "))?;
+            println!(
+                "Tablebase with {} tables for up to {} pieces loaded",
+                tablebase.table_count(),
+                tablebase.max_pieces()
+            );
+            self.white_player.set_tablebase(tablebase.directory())?;
+            self.black_player.set_tablebase(tablebase.directory())?;
+            self.tablebase = Some(tablebase);
+            return Ok(());
+        }
+        let tablebase = self
+            .tablebase
+            .as_mut()
+            .ok_or_else(|| ChessError::InvalidCommand(String::from("tb <directory> or tb")))?;
+        if !tablebase.can_probe(&self.board) {
+            println!("The position is not in the tablebase");
+            return Ok(());
+        }
+        let wdl = tablebase.probe_wdl(&self.board)?;
+        let dtz = tablebase.probe_dtz(&self.board)?;
+        println!("{} for {}, DTZ {}", wdl, self.board.active_player(), dtz);
+        for (next_move, dtz) in tablebase.root_moves(&self.board)? {
+            println!("{} DTZ {}", pgn::to_san(&self.board, next_move), dtz);
+        }
+        Ok(())
+    }
+
+    //"analysis [depth n] [file]" reviews the running game or the last finished one,
+    //the annotated PGN is written to the file
+    fn analysis(&mut self, input: &str) -> Result<(), ChessError> {
+        let mut tokens: Vec<&str> = input.split_whitespace().skip(1).collect();
+        let mut config = EngineConfig::new();
+        if let Some(i) = tokens.iter().position(|c| *c == "depth") {
+            config.depth = tokens
+                .get(i + 1)
+                .and_then(|c| c.parse().ok())
+                .ok_or_else(|| {
+                    ChessError::InvalidCommand(String::from("analysis [depth n] [file]"))
+                })?;
+            tokens.drain(i..i + 2);
+        }
+        let mut game = if self.moves.is_empty() {
+            self.finished.clone().ok_or_else(|| {
+                ChessError::InvalidCommand(String::from(
+                    "analysis [depth n] [file] after some moves",
+                ))
+            })?
+        } else {
+            self.record(self.outcome())
+        };
+        let analysis = GameAnalysis::new(&game, &config)?;
+        print!("{}", analysis.report());
+        if !tokens.is_empty() {
+            let path = tokens.join(" ");
+            analysis.annotate(&mut game);
+            fs::write(&path, game.to_pgn()?).map_err(|e| ChessError::FileError(e.to_string()))?;
+            println!("Annotated game written to {}", path);
+        }
+        Ok(())
+    }
+
+    //"chess960 [number]" starts a new game from the numbered or a random Chess960 position,
+    //"chess960 off" goes back to standard chess
+    fn chess960_command(&mut self, input: &str) -> Result<(), ChessError> {
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        self.variant = Variant::Standard;
+        self.handicap = None;
+        self.chess960 = match tokens.get(1) {
+            Some(&"off") => None,
+            Some(number) => match number.parse() {
+                Ok(n) if n < 960 => Some(n),
+                _ => {
+                    return Err(ChessError::InvalidCommand(String::from(
+                        "chess960 [0 to 959], chess960 off",
+                    )))
+                }
+            },
+            None => Some((book::random_u64() % 960) as u32),
+        };
+        self.reset();
+        match self.chess960 {
+            Some(number) => println!("New Chess960 game from start position {}", number),
+            None => println!("New standard game"),
+        }
+        self.show_board();
+        Ok(())
+    }
+
+    //"variant <name>" starts a new game of the variant, "variant" lists them
+    fn variant_command(&mut self, input: &str) -> Result<(), ChessError> {
+        let name = input.trim().trim_start_matches("variant").trim();
+        if name.is_empty() {
+            let names: Vec<String> = Variant::ALL.iter().map(|c| c.to_string()).collect();
+            println!("Playing {}, variants: {}", self.variant, names.join(", "));
+            return Ok(());
+        }
+        let variant = Variant::from_name(name).ok_or_else(|| {
+            ChessError::InvalidCommand(String::from(
+                "variant <standard|king of the hill|three-check|antichess|atomic|horde|racing kings>",
+            ))
+        })?;
+        if variant != Variant::Standard
+            && !(self.white_player.is_human() && self.black_player.is_human())
+        {
+            return Err(ChessError::InvalidCommand(String::from(
+                "human white and human black before playing a variant",
+            )));
+        }
+        self.variant = variant;
+        self.chess960 = None;
+        self.handicap = None;
+        self.reset();
+        println!("New {} game", self.variant);
+        self.show_board();
+        Ok(())
+    }
+
+    //figures taken since the start of the game
+    fn material(&self) -> Material {
+        Material::new(&self.start, &self.board)
+    }
+
+    //the taken figures are shown above and below the board next to the color taking them
+    fn show_board(&self) {
+        let material = self.material();
+        let flipped = self.render.is_some_and(|c| c.flipped(&self.board));
+        let (top, bottom) = if flipped {
+            (Color::White, Color::Black)
+        } else {
+            (Color::Black, Color::White)
+        };
+        let line = |color: Color| {
+            let taken = material.line(color);
+            if !taken.is_empty() {
+                println!("{} took {}", color, taken);
+            }
+        };
+        line(top);
+        match &self.render {
+            Some(options) => println!("{}", render::render(&self.board, options)),
+            None => println!("{}", self.board),
+        }
+        line(bottom);
+    }
+
+    //"board colors on|off", "board flip white|black|auto" and "board pieces
+    //unicode|solid|letters" change the board output, "board plain" goes back to the plain one
+    fn board_command(&mut self, input: &str) -> Result<(), ChessError> {
+        let arguments = input.trim().trim_start_matches("board").trim();
+        if arguments == "plain" {
+            self.render = None;
+        } else if !arguments.is_empty() {
+            let mut options = self.render.unwrap_or(RenderOptions::new());
+            options.apply(arguments)?;
+            self.render = Some(options);
+        }
+        self.show_board();
+        Ok(())
+    }
+
+    //"diagram <file.svg|file.png> [size <pixels>] [flip] [nocoords] [arrow <e2e4>]
+    //[highlight <e4>]" writes the current position as picture
+    fn diagram_command(&self, input: &str) -> Result<(), ChessError> {
+        let tokens: Vec<&str> = input.split_whitespace().skip(1).collect();
+        let path = tokens.first().ok_or_else(|| {
+            ChessError::InvalidCommand(String::from(
+                "diagram <file.svg|file.png> [size <pixels>] [flip] [nocoords] [arrow <e2e4>] [highlight <e4>]",
+            ))
+        })?;
+        let options = DiagramOptions::parse(&tokens[1..])?;
+        diagram::save(&self.board, &options, path)?;
+        println!("Diagram written to {}", path);
+        Ok(())
+    }
+
+    //"animation <file.gif|file.svg> [delay <ms>] [nocaptions] [size <pixels>] [flip]
+    //[nocoords]" replays the current or the last finished game
+    fn animation_command(&self, input: &str) -> Result<(), ChessError> {
+        let usage = |text: &str| {
+            ChessError::InvalidCommand(format!(
+                "animation <file.gif|file.svg> [delay <ms>] [nocaptions] [size <pixels>] [flip] [nocoords]{}",
+                text
+            ))
+        };
+        let tokens: Vec<&str> = input.split_whitespace().skip(1).collect();
+        let path = tokens.first().ok_or_else(|| usage(""))?;
+        let options = AnimationOptions::parse(&tokens[1..])?;
+        let (boards, moves) = if !self.moves.is_empty() {
+            (
+                replay(self.variant, self.start, &self.moves),
+                self.moves.clone(),
+            )
+        } else {
+            let game = self
+                .finished
+                .as_ref()
+                .ok_or_else(|| usage(" after some moves"))?;
+            let variant = game
+                .tag("Variant")
+                .and_then(Variant::from_name)
+                .unwrap_or(Variant::Standard);
+            (
+                replay(variant, game.start_board()?, &game.moves),
+                game.moves.clone(),
+            )
+        };
+        let frames = animation::frames(&boards, &moves);
+        animation::save(&frames, &options, path)?;
+        println!(
+            "Animation of {} positions written to {}",
+            frames.len(),
+            path
+        );
+        Ok(())
+    }
+
+    pub fn set_handicap(&mut self, handicap: Option<(Handicap, Color)>) {
+        self.handicap = handicap;
+        self.variant = Variant::Standard;
+        self.chess960 = None;
+    }
+
+    //"handicap <name> [white|black]" starts a new game with the odds given by the color,
+    //"handicap off" goes back to even games
+    fn handicap_command(&mut self, input: &str) -> Result<(), ChessError> {
+        let arguments = input.trim().trim_start_matches("handicap").trim();
+        let handicap = match arguments {
+            "off" => None,
+            _ => Some(Handicap::parse(arguments)?),
+        };
+        self.set_handicap(handicap);
+        self.reset();
+        match handicap {
+            Some((handicap, giver)) => println!("New game, {} given by {}", handicap, giver),
+            None => println!("New standard game"),
+        }
+        self.show_board();
+        Ok(())
+    }
+
+    //engines and hints search with the standard rules
+    fn standard_only(&self) -> Result<(), ChessError> {
+        if self.variant != Variant::Standard {
+            return Err(ChessError::InvalidCommand(format!(
+                "variant standard, {} is played by humans only",
+                self.variant
+            )));
+        }
+        Ok(())
+    }
+
+    //"repertoire <path> <white|black>" drills the lines of a PGN, "repertoire off" stops it
+    //and "repertoire" shows how many lines are due
+    fn repertoire_command(&mut self, input: &str) -> Result<(), ChessError> {
+        let usage = || {
+            ChessError::InvalidCommand(String::from(
+                "repertoire <path> <white|black>, repertoire off or repertoire",
+            ))
+        };
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["repertoire"] => {
+                let drill = self.drill.as_ref().ok_or_else(usage)?;
+                let (lines, due, weak) = drill.status();
                 println!(
-                    "Last move was undone... now is {}'s turn",
-                    self.board.active_player()
+                    "{} lines, {} due today, {} to learn again",
+                    lines, due, weak
                 );
-                println!("{}", self.board);
+            }
+            ["repertoire", "off"] => {
+                self.drill = None;
+                println!("Repertoire training stopped");
+            }
+            ["repertoire", .., color] => {
+                let color = match *color {
+                    "white" => Color::White,
+                    "black" => Color::Black,
+                    _ => return Err(usage()),
+                };
+                let path = tokens[1..tokens.len() - 1].join(" ");
+                if path.is_empty() {
+                    return Err(usage());
+                }
+                let repertoire = Repertoire::open(&path)?;
+                println!(
+                    "Repertoire with {} lines loaded, you play {}",
+                    repertoire.lines().len(),
+                    color
+                );
+                let schedule = Schedule::open(&format!("{}.srs", path));
+                self.drill = Some(RepertoireDrill::new(repertoire, color, schedule));
+                self.start_drill_line()?;
+                self.show_board();
+            }
+            _ => return Err(usage()),
+        }
+        Ok(())
+    }
+
+    //the board is set to the start of the repertoire and the program makes its first move
+    fn start_drill_line(&mut self) -> Result<(), ChessError> {
+        let drill = match self.drill.as_mut() {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        let color = drill.color();
+        self.board = drill.start();
+        self.start = self.board;
+        self.start_fen = self.board.fen();
+        self.history.clear();
+        self.moves.clear();
+        self.hint = None;
+        self.hints.clear();
+        self.turn = 0;
+        if self.board.active_player() != color {
+            self.drill_reply()?;
+        }
+        if self
+            .drill
+            .as_ref()
+            .is_some_and(|c| c.line_finished(&self.board))
+        {
+            self.drill = None;
+            return Err(ChessError::InvalidPgn(format!(
+                "the repertoire has no moves for {}",
+                color
+            )));
+        }
+        Ok(())
+    }
+
+    fn drill_reply(&mut self) -> Result<(), ChessError> {
+        let board = self.board;
+        if let Some(next_move) = self.drill.as_mut().and_then(|c| c.reply(&board)) {
+            println!("{}", pgn::to_san(&self.board, next_move));
+            self.play_move(next_move)?;
+        }
+        Ok(())
+    }
+
+    //checks the user's last move, a deviation is taken back
+    fn drill_answer(&mut self, before: &Board) -> Result<(), ChessError> {
+        let drill = match self.drill.as_mut() {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        let played = match self.moves.last() {
+            Some(c) if before.active_player() == drill.color() => *c,
+            _ => return Ok(()),
+        };
+        if let DrillAnswer::Deviation(expected) = drill.check(before, played) {
+            let expected: Vec<String> = expected.iter().map(|c| pgn::to_san(before, *c)).collect();
+            println!(
+                "{} is not in your repertoire, play {}",
+                pgn::to_san(before, played),
+                expected.join(" or ")
+            );
+            self.take_back();
+            return Ok(());
+        }
+        self.drill_reply()?;
+        let finished = self
+            .drill
+            .as_ref()
+            .is_some_and(|c| c.line_finished(&self.board));
+        if let Some(drill) = self.drill.as_mut().filter(|_| finished) {
+            if drill.finish_line()? {
+                println!("Line complete");
+            } else {
+                println!("Line complete with mistakes, it will be asked again soon");
+            }
+            self.show_board();
+            println!("Next line");
+            self.start_drill_line()?;
+        }
+        Ok(())
+    }
+
+    //the first hint names the figure to move, asking again gives the whole move
+    fn hint(&mut self) -> Result<String, ChessError> {
+        self.standard_only()?;
+        let ply = self.moves.len();
+        if let Some((hint_ply, next_move)) = self.hint {
+            if hint_ply == ply {
+                let san = pgn::to_san(&self.board, next_move);
+                if let Some(last) = self.hints.last_mut().filter(|c| c.0 == ply) {
+                    last.1 = format!("Hint: {}", san);
+                }
+                return Ok(format!("Play {}", san));
+            }
+        }
+        let config = EngineConfig {
+            depth: 3,
+            hash_mb: 1,
+        };
+        let next_move = engine::search(&self.board, &config, &mut TranspositionTable::new(1))
+            .best_move
+            .ok_or_else(|| ChessError::EngineError(String::from("no move to suggest")))?;
+        let figure = self.board.get(next_move.from).get_type();
+        let square = next_move.from;
+        self.hint = Some((ply, next_move));
+        self.hints
+            .push((ply, format!("Hint: {} on {}", figure, square)));
+        Ok(format!(
+            "Move your {} on {}, ask again for the whole move",
+            figure, square
+        ))
+    }
+
+    pub fn set_book(&mut self, book: Option<OpeningBook>) {
+        self.book = book;
+    }
+
+    pub fn engine_move(&mut self) -> Result<(), ChessError> {
+        let color = self.board.active_player();
+        if let Some(next_move) = self.book.as_ref().and_then(|c| c.choose(&self.board)) {
+            println!("Book move {}", pgn::to_san(&self.board, next_move));
+            return self.play_move(next_move);
+        }
+        let (start_fen, moves, board) = (self.start_fen.clone(), self.moves.clone(), self.board);
+        match self
+            .player_mut(color)
+            .choose_move(&start_fen, &moves, &board)?
+        {
+            Some(next_move) => self.play_move(next_move),
+            None => Err(ChessError::EngineError(format!(
+                "no move for {} in this position",
+                color
+            ))),
+        }
+    }
+
+    //applies a move in coordinate notation, castling is given as the king moving two files
+    //or taking its own tower
+    fn play_move(&mut self, mut next_move: Move) -> Result<(), ChessError> {
+        let color = self.board.active_player();
+        let figure = self.board.get(next_move.from);
+        let castling = self
+            .board
+            .castling_side(next_move)
+            .filter(|_| figure.get_color() == color);
+        if self.variant != Variant::Standard {
+            //promotions are asked for when the move is made
+            let legal =
+                self.variant.legal_moves(&self.board).into_iter().find(|m| {
+                    (m.from, m.to, m.drop) == (next_move.from, next_move.to, next_move.drop)
+                });
+            match (legal, next_move.drop) {
+                (Some(_), _) => {}
+                (None, Some(figure_type)) => return Err(ChessError::InvalidDrop(figure_type)),
+                (None, None) => return Err(ChessError::InvalidFigureMove(figure.get_type())),
+            }
+        } else if let Some(small) = castling {
+            let castle_type = if small { "o-o" } else { "o-o-o" };
+            self.board.castling(castle_type, true)?;
+            //the move is recorded the way the board writes castling
+            next_move = self.board.castling_move(color, small).unwrap_or(next_move);
+        } else if !self
+            .board
+            .legal_moves()
+            .iter()
+            .any(|m| (m.from, m.to) == (next_move.from, next_move.to))
+        {
+            //the figure rules tell why the move is illegal, otherwise it leaves the king in check
+            self.board
+                .validate_move(next_move.from, next_move.to, color)?;
+            let pseudo_legal = self
+                .board
+                .pseudo_legal_moves()
+                .iter()
+                .any(|m| (m.from, m.to) == (next_move.from, next_move.to));
+            return Err(if pseudo_legal {
+                ChessError::CantMoveFromToAsKingWillBeUnderAttack(next_move.from, next_move.to)
+            } else {
+                ChessError::InvalidFigureMove(figure.get_type())
+            });
+        }
+        let key = self.board.zobrist_key();
+        let checked = Some(color).filter(|c| self.board.is_in_check(*c));
+        let (response, undo) = self.variant.make_move(&mut self.board, next_move);
+        println!("{}", response);
+        self.history.push(Played { undo, key, checked });
+        //interactive promotions are only known after the move
+        let mut played = next_move;
+        let moved = self.board.get(next_move.to);
+        if figure.get_type() == FieldType::Pawn && moved.get_type() != FieldType::Pawn {
+            played.promotion = Some(moved.get_type());
+        }
+        self.moves.push(played);
+        self.turn += 1;
+        Ok(())
+    }
+
+    fn eval(&mut self, input: &str) -> Result<(), ChessError> {
+        for entry in input.split('\n') {
+            if entry.is_empty() {
+                continue;
+            }
+            if self.board.castling(entry, true)? {
+                let small = entry.trim().to_lowercase() == "o-o";
+                if let Some(castling) = self.board.castling_move(self.board.active_player(), small)
+                {
+                    self.play_move(castling)?;
+                }
+                continue;
+            };
+
+            //drops from the Crazyhouse pocket like "N@f3"
+            if entry.contains('@') {
+                let drop = Move::from_uci(entry)?;
+                if self.variant != Variant::Crazyhouse {
+                    return Err(ChessError::InvalidDrop(
+                        drop.drop.unwrap_or(FieldType::None),
+                    ));
+                }
+                self.play_move(drop)?;
+                continue;
+            }
+
+            let next_move = entry.trim().split_once(" ");
+            match next_move {
+                Some(c) => {
+                    if c.0.len() != 2 || c.1.len() != 2 {
+                        return Err(ChessError::InvalidMoveSyntax(entry.to_string()));
+                    }
+                    match (Square::parse(c.0), Square::parse(c.1)) {
+                        (Some(from), Some(to)) => self.play_move(Move::new(from, to))?,
+                        _ => return Err(ChessError::MoveOutsideOfBoard),
+                    }
+                }
+                None => return Err(ChessError::InvalidMoveSyntax(entry.to_string())),
+            }
+        }
+        Ok(())
+    }
+
+    //takes back the last move, false when there is none
+    fn take_back(&mut self) -> bool {
+        match self.history.pop() {
+            Some(played) => {
+                self.variant.unmake_move(&mut self.board, played.undo);
+                self.moves.pop();
                 self.turn -= 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if self.take_back() {
+            //take back the engine's reply as well, otherwise it would just play it again
+            if !self.player(self.board.active_player()).is_human() {
+                self.take_back();
             }
-            None => {}
+            self.hints.retain(|c| c.0 < self.moves.len());
+            println!(
+                "Last move was undone... now is {}'s turn",
+                self.board.active_player()
+            );
+            self.show_board();
         }
     }
     #[allow(dead_code)]
@@ -234,4 +1349,40 @@ impl Game {
         game.board.set_board_start();
         game.eval(&input)
     }
+    #[allow(dead_code)]
+    pub fn test_engine_helper(input: String, engine: UciEngine) -> Result<Vec<Move>, ChessError> {
+        let mut game = Game::new();
+        game.reset();
+        game.set_player(Color::Black, Player::Uci(engine));
+        game.eval(&input)?;
+        game.engine_move()?;
+        Ok(game.moves)
+    }
+    #[allow(dead_code)]
+    pub fn test_hint_helper(input: String) -> Result<(Vec<String>, PgnGame), ChessError> {
+        let mut game = Game::new();
+        game.reset();
+        let hints = vec![game.hint()?, game.hint()?];
+        game.eval(&input)?;
+        Ok((hints, game.record(None)))
+    }
+    #[allow(dead_code)]
+    pub fn test_undo_helper(input: String) -> Result<(Option<Outcome>, String), ChessError> {
+        let mut game = Game::new();
+        game.reset();
+        game.eval(&input)?;
+        let outcome = game.outcome();
+        while game.take_back() {}
+        Ok((outcome, game.board.fen()))
+    }
+}
+
+//the start position and the position after every move
+fn replay(variant: Variant, mut board: Board, moves: &[Move]) -> Vec<Board> {
+    let mut boards = vec![board];
+    for next_move in moves {
+        variant.make_move(&mut board, *next_move);
+        boards.push(board);
+    }
+    boards
 }
@@ -0,0 +1,97 @@
+use std::fmt;
+
+use crate::board::Board;
+use crate::field::{ChessError, Color};
+use crate::square::{File, Rank, Square};
+
+//the stronger player gives away material from the standard start position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Handicap {
+    PawnAndMove,
+    Knight,
+    Rook,
+    Queen,
+}
+
+impl Handicap {
+    pub const ALL: [Handicap; 4] = [
+        Handicap::PawnAndMove,
+        Handicap::Knight,
+        Handicap::Rook,
+        Handicap::Queen,
+    ];
+
+    //"pawn and move", "knight", "rook" or "queen", with or without "odds"
+    pub fn from_name(name: &str) -> Option<Handicap> {
+        let simple = |c: &str| {
+            c.to_lowercase()
+                .trim_end_matches("odds")
+                .replace([' ', '-'], "")
+        };
+        Handicap::ALL
+            .into_iter()
+            .find(|c| simple(&c.to_string()) == simple(name))
+    }
+
+    //"<name> [white|black]" with the color giving the odds
+    pub fn parse(input: &str) -> Result<(Handicap, Color), ChessError> {
+        let usage = || {
+            ChessError::InvalidCommand(String::from(
+                "handicap <pawn and move|knight|rook|queen> [white|black], handicap off",
+            ))
+        };
+        let input = input.trim().to_lowercase();
+        let (name, giver) = match input.rsplit_once(' ') {
+            Some((name, "white")) => (name, Some(Color::White)),
+            Some((name, "black")) => (name, Some(Color::Black)),
+            _ => (input.as_str(), None),
+        };
+        let handicap = Handicap::from_name(name).ok_or_else(usage)?;
+        Ok((handicap, giver.unwrap_or(handicap.default_giver())))
+    }
+
+    //pawn and move is given by black, the other odds by white
+    pub fn default_giver(self) -> Color {
+        match self {
+            Handicap::PawnAndMove => Color::Black,
+            _ => Color::White,
+        }
+    }
+
+    //field of the removed figure on the giver's first row, pawn and move takes the f pawn
+    fn removed_field(self, giver: Color) -> Square {
+        let (first, pawns) = if giver == Color::White {
+            (Rank::First, Rank::Second)
+        } else {
+            (Rank::Eighth, Rank::Seventh)
+        };
+        match self {
+            Handicap::PawnAndMove => Square::new(File::F, pawns),
+            Handicap::Knight => Square::new(File::B, first),
+            Handicap::Rook => Square::new(File::A, first),
+            Handicap::Queen => Square::new(File::D, first),
+        }
+    }
+
+    //the taken tower loses its castling, the receiver of pawn and move starts
+    pub fn start_board(self, giver: Color) -> Board {
+        let mut board = Board::new();
+        board.set_board_start();
+        board.remove_figure(self.removed_field(giver));
+        if self == Handicap::PawnAndMove {
+            board.set_active_player(giver.enemy_color());
+        }
+        board
+    }
+}
+
+impl fmt::Display for Handicap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Handicap::PawnAndMove => write!(f, "Pawn and move"),
+            Handicap::Knight => write!(f, "Knight odds"),
+            Handicap::Rook => write!(f, "Rook odds"),
+            Handicap::Queen => write!(f, "Queen odds"),
+        }
+    }
+}
@@ -1,12 +1,86 @@
+mod analysis;
+mod animation;
 mod board;
+mod book;
+mod diagram;
+mod editor;
+mod engine;
+mod evaluation;
 mod field;
 mod game;
+mod handicap;
+mod match_runner;
+mod pgn;
+mod player;
+mod puzzle;
+mod render;
+mod repertoire;
+mod square;
+mod tablebase;
 mod test;
+mod tournament;
+mod transposition;
+mod tui;
+mod uci;
+mod variant;
+mod zobrist;
 
 use crate::game::Game;
+use crate::handicap::Handicap;
+use crate::puzzle::PuzzleTrainer;
+use crate::tournament::Tournament;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(|c| c.as_str()) == Some("match") {
+        if let Err(e) = match_runner::match_command(&args[1..]) {
+            println!("{}", e);
+        }
+        return;
+    }
+    if args.first().map(|c| c.as_str()) == Some("puzzles") {
+        let path = args[1..].join(" ");
+        match PuzzleTrainer::open(&path) {
+            Ok(mut trainer) => {
+                if let Err(e) = trainer.puzzle_loop() {
+                    println!("{}", e)
+                }
+            }
+            Err(e) => println!("{}", e),
+        }
+        return;
+    }
+    if args.first().map(|c| c.as_str()) == Some("tournament") {
+        let name = if args.len() > 1 {
+            args[1..].join(" ")
+        } else {
+            String::from("Tournament")
+        };
+        if let Err(e) = Tournament::new(&name).tournament_loop() {
+            println!("{}", e);
+        }
+        return;
+    }
+
+    //"--tui" plays in the full-screen mode
+    let tui = args.first().map(|c| c.as_str()) == Some("--tui");
+    let args = if tui { &args[1..] } else { &args[..] };
     let mut game = Game::new();
+    //"--handicap <name> [white|black]" starts with odds
+    if args.first().map(|c| c.as_str()) == Some("--handicap") {
+        match Handicap::parse(&args[1..].join(" ")) {
+            Ok(handicap) => game.set_handicap(Some(handicap)),
+            Err(e) => println!("{}", e),
+        }
+    }
+
+    if tui {
+        match game.tui_loop() {
+            Ok(_) => {println!("Game closed!")}
+            Err(e) => {println!("{}", e)}
+        }
+        return;
+    }
 
     match game.game_loop() {
         Ok(_) => {println!("Game closed!")}
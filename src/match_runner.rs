@@ -0,0 +1,402 @@
+use std::fs;
+
+use crate::board::{Board, START_FEN};
+use crate::book::OpeningBook;
+use crate::field::{ChessError, Color, Move, Outcome};
+use crate::pgn::{self, PgnGame};
+use crate::player::Player;
+use crate::uci::SearchLimit;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Opening {
+    pub fen: String,
+    pub moves: Vec<Move>,
+}
+
+//sequential probability ratio test between two Elo hypotheses
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Sprt {
+        Sprt {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    //(lower, upper), the test stops once the log likelihood ratio leaves this range
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    //Some(true) when elo1 is accepted, Some(false) for elo0, None while the test goes on
+    pub fn decision(&self, stats: &MatchStats) -> Option<bool> {
+        let llr = stats.llr(self);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            Some(true)
+        } else if llr <= lower {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+//results from the view of the first player
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct MatchStats {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MatchStats {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    fn elo_from_score(score: f64) -> f64 {
+        -400.0 * (1.0 / score - 1.0).log10()
+    }
+
+    //Elo difference with the 95% confidence margin, None while one side has scored everything
+    pub fn elo(&self) -> Option<(f64, f64)> {
+        let score = self.score();
+        if self.games() == 0 || score <= 0.0 || score >= 1.0 {
+            return None;
+        }
+        let games = self.games() as f64;
+        let variance = (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / games;
+        let margin = 1.96 * (variance / games).sqrt();
+        let low = (score - margin).max(0.001);
+        let high = (score + margin).min(0.999);
+        Some((
+            MatchStats::elo_from_score(score),
+            (MatchStats::elo_from_score(high) - MatchStats::elo_from_score(low)) / 2.0,
+        ))
+    }
+
+    //log likelihood ratio of elo1 against elo0 with the normal approximation,
+    //the variance is kept positive so one-sided results still reach a bound
+    pub fn llr(&self, sprt: &Sprt) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        let games = self.games() as f64;
+        let score = self.score();
+        let variance =
+            ((self.wins as f64 + self.draws as f64 / 4.0) / games - score.powi(2)).max(1e-6);
+        let expected = |elo: f64| 1.0 / (1.0 + 10f64.powf(-elo / 400.0));
+        let (score0, score1) = (expected(sprt.elo0), expected(sprt.elo1));
+        (score1 - score0) * (2.0 * score - score0 - score1) / (2.0 * variance / games)
+    }
+}
+
+pub struct MatchConfig {
+    pub players: [String; 2],
+    pub games: u32,
+    pub openings: Vec<Opening>,
+    pub pgn_path: Option<String>,
+    pub sprt: Option<Sprt>,
+    pub max_plies: usize,
+    //None leaves UCI engines at 100 ms per move and the built-in engine at its depth
+    pub limit: Option<SearchLimit>,
+    pub book: Option<OpeningBook>,
+    //Syzygy directory for both engines
+    pub syzygy: Option<String>,
+}
+
+impl MatchConfig {
+    pub fn new(first: &str, second: &str) -> MatchConfig {
+        MatchConfig {
+            players: [first.to_string(), second.to_string()],
+            games: 10,
+            openings: Vec::new(),
+            pgn_path: None,
+            sprt: None,
+            max_plies: 400,
+            limit: None,
+            book: None,
+            syzygy: None,
+        }
+    }
+}
+
+//a FEN per line or the main lines of a PGN file
+pub fn load_openings(path: &str) -> Result<Vec<Opening>, ChessError> {
+    let text = fs::read_to_string(path).map_err(|e| ChessError::FileError(e.to_string()))?;
+    if path.ends_with(".pgn") || text.trim_start().starts_with('[') {
+        return Ok(pgn::parse_pgn(&text)?
+            .into_iter()
+            .map(|c| Opening {
+                fen: c.start_fen().to_string(),
+                moves: c.moves,
+            })
+            .collect());
+    }
+    let mut openings = Vec::new();
+    for line in text.lines().filter(|c| !c.trim().is_empty()) {
+        let mut board = Board::new();
+        board.set_board_fen(line.trim())?;
+        openings.push(Opening {
+            fen: board.fen(),
+            moves: Vec::new(),
+        });
+    }
+    Ok(openings)
+}
+
+//plays one game until the game end rules or the move limit stop it,
+//illegal moves and failing engines lose the game, book moves are played while there are any
+pub fn play_game(
+    white: &mut Player,
+    black: &mut Player,
+    opening: &Opening,
+    max_plies: usize,
+    book: Option<&OpeningBook>,
+) -> Result<(PgnGame, Outcome), ChessError> {
+    let mut board = Board::new();
+    board.set_board_fen(&opening.fen)?;
+    let mut history = vec![board.zobrist_key()];
+    let mut moves = Vec::new();
+    for next_move in &opening.moves {
+        if !board.legal_moves().contains(next_move) {
+            return Err(ChessError::InvalidPgn(format!(
+                "illegal opening move {}",
+                next_move
+            )));
+        }
+        board.make_move(*next_move);
+        history.push(board.zobrist_key());
+        moves.push(*next_move);
+    }
+    white.new_game()?;
+    black.new_game()?;
+
+    let outcome = loop {
+        if let Some(outcome) = board.outcome() {
+            break outcome;
+        }
+        //the current position is the last entry of the history
+        if board.count_repetitions(history.iter().copied()) >= 3 {
+            break Outcome::ThreefoldRepetition;
+        }
+        if moves.len() >= max_plies {
+            break Outcome::MoveLimit;
+        }
+        let color = board.active_player();
+        let player = if color == Color::White {
+            &mut *white
+        } else {
+            &mut *black
+        };
+        if let Some(next_move) = book.and_then(|c| c.choose(&board)) {
+            board.make_move(next_move);
+            history.push(board.zobrist_key());
+            moves.push(next_move);
+            continue;
+        }
+        let next_move = match player.choose_move(&opening.fen, &moves, &board) {
+            Ok(Some(m)) if board.legal_moves().contains(&m) => m,
+            _ => break Outcome::Forfeit(color.enemy_color()),
+        };
+        board.make_move(next_move);
+        history.push(board.zobrist_key());
+        moves.push(next_move);
+    };
+
+    let game = PgnGame::from_game(
+        "Engine match",
+        &white.name(),
+        &black.name(),
+        &opening.fen,
+        &moves,
+        Some(outcome),
+    );
+    Ok((game, outcome))
+}
+
+fn match_player(spec: &str, limit: Option<SearchLimit>) -> Result<Player, ChessError> {
+    let mut player = Player::from_spec(spec, limit)?;
+    if let (Player::Uci(engine), None) = (&mut player, limit) {
+        engine.set_limit(SearchLimit::MoveTime(100));
+    }
+    Ok(player)
+}
+
+pub fn run_match(config: &MatchConfig) -> Result<MatchStats, ChessError> {
+    let mut first = match_player(&config.players[0], config.limit)?;
+    let mut second = match_player(&config.players[1], config.limit)?;
+    if let Some(directory) = &config.syzygy {
+        first.set_tablebase(directory)?;
+        second.set_tablebase(directory)?;
+    }
+    let openings = if config.openings.is_empty() {
+        vec![Opening {
+            fen: START_FEN.to_string(),
+            moves: Vec::new(),
+        }]
+    } else {
+        config.openings.clone()
+    };
+    let mut pgn_output = String::new();
+    let mut stats = MatchStats::default();
+
+    for round in 0..config.games {
+        //every opening is played with both colors
+        let opening = &openings[(round as usize / 2) % openings.len()];
+        let first_is_white = round % 2 == 0;
+        let (mut game, outcome) = if first_is_white {
+            play_game(
+                &mut first,
+                &mut second,
+                opening,
+                config.max_plies,
+                config.book.as_ref(),
+            )?
+        } else {
+            play_game(
+                &mut second,
+                &mut first,
+                opening,
+                config.max_plies,
+                config.book.as_ref(),
+            )?
+        };
+        game.set_tag("Round", &(round + 1).to_string());
+        pgn_output.push_str(&game.to_pgn()?);
+        //written after every game so an aborted match keeps its games
+        if let Some(path) = &config.pgn_path {
+            fs::write(path, &pgn_output).map_err(|e| ChessError::FileError(e.to_string()))?;
+        }
+
+        let first_color = if first_is_white {
+            Color::White
+        } else {
+            Color::Black
+        };
+        match outcome.winner() {
+            Some(c) if c == first_color => stats.wins += 1,
+            Some(_) => stats.losses += 1,
+            None => stats.draws += 1,
+        }
+        println!(
+            "Game {} ({} vs {}): {} {}",
+            round + 1,
+            game.tag("White").unwrap_or("?"),
+            game.tag("Black").unwrap_or("?"),
+            outcome.result(),
+            outcome
+        );
+        print_stats(&first, &second, &stats);
+
+        if let Some(sprt) = &config.sprt {
+            let llr = stats.llr(sprt);
+            let (lower, upper) = sprt.bounds();
+            println!("LLR: {:.2} ({:.2}, {:.2})", llr, lower, upper);
+            match sprt.decision(&stats) {
+                Some(true) => {
+                    println!("SPRT: H1 accepted (elo >= {})", sprt.elo1);
+                    break;
+                }
+                Some(false) => {
+                    println!("SPRT: H0 accepted (elo <= {})", sprt.elo0);
+                    break;
+                }
+                None => {}
+            }
+        }
+    }
+
+    for player in [&first, &second] {
+        if let Some((table_stats, hashfull)) = player.table_stats() {
+            println!(
+                "{} hash: {}, filled {} permille",
+                player.name(),
+                table_stats,
+                hashfull
+            );
+        }
+    }
+    Ok(stats)
+}
+
+fn print_stats(first: &Player, second: &Player, stats: &MatchStats) {
+    print!(
+        "{} vs {}: +{} ={} -{} ({:.1}%)",
+        first.name(),
+        second.name(),
+        stats.wins,
+        stats.draws,
+        stats.losses,
+        stats.score() * 100.0
+    );
+    match stats.elo() {
+        Some((elo, margin)) => println!(", Elo difference: {:+.1} +/- {:.1}", elo, margin),
+        None => println!(),
+    }
+}
+
+//"match <player> <player> [--games n] [--openings file] [--pgn file] [--sprt elo0 elo1]
+//[--book file] [--syzygy dir] [--max-plies n] [--movetime ms | --depth n | --nodes n]"
+pub fn match_command(args: &[String]) -> Result<(), ChessError> {
+    let usage = || {
+        ChessError::InvalidCommand(String::from(
+            "match <player> <player> [--games n] [--openings file] [--pgn file] [--sprt elo0 elo1] [--book file] [--syzygy dir] [--max-plies n] [--movetime ms | --depth n | --nodes n]",
+        ))
+    };
+    if args.len() < 2 {
+        return Err(usage());
+    }
+    let mut config = MatchConfig::new(&args[0], &args[1]);
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        let mut value = || options.next().ok_or_else(usage);
+        match option.as_str() {
+            "--games" => config.games = value()?.parse().map_err(|_| usage())?,
+            "--openings" => config.openings = load_openings(value()?)?,
+            "--book" => config.book = Some(OpeningBook::open(value()?)?),
+            "--syzygy" => config.syzygy = Some(value()?.to_string()),
+            "--pgn" => config.pgn_path = Some(value()?.to_string()),
+            "--max-plies" => config.max_plies = value()?.parse().map_err(|_| usage())?,
+            "--movetime" => {
+                config.limit = Some(SearchLimit::MoveTime(
+                    value()?.parse().map_err(|_| usage())?,
+                ))
+            }
+            "--depth" => {
+                config.limit = Some(SearchLimit::Depth(value()?.parse().map_err(|_| usage())?))
+            }
+            "--nodes" => {
+                config.limit = Some(SearchLimit::Nodes(value()?.parse().map_err(|_| usage())?))
+            }
+            "--sprt" => {
+                let elo0 = value()?.parse().map_err(|_| usage())?;
+                let elo1 = value()?.parse().map_err(|_| usage())?;
+                config.sprt = Some(Sprt::new(elo0, elo1));
+            }
+            _ => return Err(usage()),
+        }
+    }
+    run_match(&config)?;
+    Ok(())
+}
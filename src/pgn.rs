@@ -0,0 +1,370 @@
+use crate::board::{Board, START_FEN};
+use crate::field::{ChessError, Color, FieldType, Move, Outcome};
+
+//standard algebraic notation, e.g. "Nbd2", "exd6", "e8=Q+" or "O-O"
+pub fn to_san(board: &Board, next_move: Move) -> String {
+    let figure = board.get(next_move.from);
+    let flags = board.annotate(next_move).flags;
+    let (from, to) = (next_move.from, next_move.to);
+    let mut san = String::new();
+    if let Some(figure_type) = next_move.drop {
+        san.push_str(&format!("{}@{}", figure_type.letter(), to));
+    } else if flags.castling {
+        san.push_str(if to.file() > from.file() {
+            "O-O"
+        } else {
+            "O-O-O"
+        });
+    } else {
+        if figure.get_type() == FieldType::Pawn {
+            if flags.capture {
+                san.push(from.file().letter());
+            }
+        } else {
+            san.push(figure.get_type().letter());
+            //other figures of the same type which could move to the same field
+            let others: Vec<Move> = board
+                .legal_moves()
+                .into_iter()
+                .filter(|m| {
+                    m.to == to
+                        && m.from != from
+                        && board.get(m.from).get_type() == figure.get_type()
+                })
+                .collect();
+            if !others.is_empty() {
+                if others.iter().all(|m| m.from.file() != from.file()) {
+                    san.push(from.file().letter());
+                } else if others.iter().all(|m| m.from.rank() != from.rank()) {
+                    san.push_str(&from.rank().to_string());
+                } else {
+                    san.push_str(&from.to_string());
+                }
+            }
+        }
+        if flags.capture {
+            san.push('x');
+        }
+        san.push_str(&to.to_string());
+        if let Some(promotion) = next_move.promotion {
+            san.push('=');
+            san.push(promotion.letter());
+        }
+    }
+
+    let mut next = *board;
+    next.make_move(next_move);
+    if next.is_in_check(next.active_player()) {
+        if next.legal_moves().is_empty() {
+            san.push('#');
+        } else {
+            san.push('+');
+        }
+    }
+    san
+}
+
+pub fn from_san(board: &Board, san: &str) -> Result<Move, ChessError> {
+    let cleaned = san
+        .trim()
+        .trim_end_matches(['+', '#', '!', '?'])
+        .replace('0', "O");
+    board
+        .legal_moves()
+        .into_iter()
+        .find(|m| to_san(board, *m).trim_end_matches(['+', '#']) == cleaned)
+        .ok_or_else(|| ChessError::InvalidMoveSyntax(san.to_string()))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<Move>,
+    //comments after the move with the index, indexes past the last move go to the end
+    pub move_comments: Vec<(usize, String)>,
+    //numeric annotation glyphs like $2 for a poor move after the move with the index
+    pub nags: Vec<(usize, u8)>,
+    //written as a comment after the last move
+    pub comment: Option<String>,
+}
+
+impl PgnGame {
+    pub fn new() -> PgnGame {
+        PgnGame {
+            tags: Vec::new(),
+            moves: Vec::new(),
+            move_comments: Vec::new(),
+            nags: Vec::new(),
+            comment: None,
+        }
+    }
+
+    //a finished or running game with the seven tag roster
+    pub fn from_game(
+        event: &str,
+        white: &str,
+        black: &str,
+        start_fen: &str,
+        moves: &[Move],
+        outcome: Option<Outcome>,
+    ) -> PgnGame {
+        let mut game = PgnGame::new();
+        game.set_tag("Event", event);
+        game.set_tag("Site", "?");
+        game.set_tag("Date", "????.??.??");
+        game.set_tag("Round", "?");
+        game.set_tag("White", white);
+        game.set_tag("Black", black);
+        game.set_tag("Result", outcome.map(|c| c.result()).unwrap_or("*"));
+        if start_fen != START_FEN {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", start_fen);
+        }
+        game.moves = moves.to_vec();
+        game.comment = outcome.map(|c| c.to_string());
+        game
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|c| c.0 == name).map(|c| c.1.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|c| c.0 == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn start_fen(&self) -> &str {
+        self.tag("FEN").unwrap_or(START_FEN)
+    }
+
+    pub fn start_board(&self) -> Result<Board, ChessError> {
+        let mut board = Board::new();
+        board.set_board_fen(self.start_fen())?;
+        //a Chess960 position can look like the standard one
+        if self
+            .tag("Variant")
+            .is_some_and(|c| c.eq_ignore_ascii_case("chess960"))
+        {
+            board.set_chess960(true);
+        }
+        Ok(board)
+    }
+
+    pub fn to_pgn(&self) -> Result<String, ChessError> {
+        let mut pgn = String::new();
+        for (name, value) in &self.tags {
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, value.replace('"', "'")));
+        }
+        pgn.push('\n');
+
+        let mut board = self.start_board()?;
+        let mut line = String::new();
+        let mut tokens = Vec::new();
+        let comments = |i: usize| {
+            self.move_comments
+                .iter()
+                .filter(move |c| c.0 == i)
+                .map(|c| format!("{{{}}}", c.1))
+        };
+        for (i, next_move) in self.moves.iter().enumerate() {
+            //black's move number is repeated after a comment
+            let commented = i > 0 && comments(i - 1).next().is_some();
+            if i == 0 || board.active_player() == Color::White || commented {
+                tokens.push(if board.active_player() == Color::White {
+                    format!("{}.", board.fullmove_number())
+                } else {
+                    format!("{}...", board.fullmove_number())
+                });
+            }
+            tokens.push(to_san(&board, *next_move));
+            for (_, nag) in self.nags.iter().filter(|c| c.0 == i) {
+                tokens.push(format!("${}", nag));
+            }
+            tokens.extend(comments(i));
+            board.make_move(*next_move);
+        }
+        for (_, comment) in self
+            .move_comments
+            .iter()
+            .filter(|c| c.0 >= self.moves.len())
+        {
+            tokens.push(format!("{{{}}}", comment));
+        }
+        if let Some(comment) = &self.comment {
+            tokens.push(format!("{{{}}}", comment));
+        }
+        tokens.push(self.tag("Result").unwrap_or("*").to_string());
+
+        //lines are kept below 80 characters
+        for token in tokens {
+            if !line.is_empty() && line.len() + token.len() >= 80 {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push_str("\n\n");
+        Ok(pgn)
+    }
+}
+
+//reads all games of a PGN file, comments, variations and annotations are skipped
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, ChessError> {
+    split_games(text)
+        .into_iter()
+        .map(|(game, movetext)| finish_game(game, &movetext))
+        .collect()
+}
+
+//the start position and the moves from there to the end of every line
+pub type GameLines = (Board, Vec<Vec<Move>>);
+
+//every line of a game from the start to the end of the main line or of a variation
+pub fn parse_pgn_lines(text: &str) -> Result<Vec<GameLines>, ChessError> {
+    let mut games = Vec::new();
+    for (game, movetext) in split_games(text) {
+        let start = game.start_board()?;
+        let tokens = movetext_tokens(&movetext);
+        let mut lines = Vec::new();
+        collect_lines(start, Vec::new(), &tokens, &mut 0, &mut lines)?;
+        games.push((start, lines));
+    }
+    Ok(games)
+}
+
+//the tags and the movetext of every game
+fn split_games(text: &str) -> Vec<(PgnGame, String)> {
+    let mut games = Vec::new();
+    let mut game = PgnGame::new();
+    let mut movetext = String::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('[') && line.ends_with(']') {
+            if !movetext.trim().is_empty() {
+                games.push((game, movetext.clone()));
+                game = PgnGame::new();
+                movetext.clear();
+            }
+            let tag = &line[1..line.len() - 1];
+            if let Some((name, value)) = tag.split_once(' ') {
+                game.set_tag(name, value.trim().trim_matches('"'));
+            }
+        } else if !line.starts_with('%') {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+    if !movetext.trim().is_empty() || !game.tags.is_empty() {
+        games.push((game, movetext));
+    }
+    games
+}
+
+fn finish_game(mut game: PgnGame, movetext: &str) -> Result<PgnGame, ChessError> {
+    let mut board = game.start_board()?;
+    let mut depth = 0;
+    for token in movetext_tokens(movetext) {
+        match token.as_str() {
+            "(" => depth += 1,
+            ")" => depth -= 1,
+            _ if depth > 0 => {}
+            _ => {
+                let next_move = from_san(&board, &token)
+                    .map_err(|_| ChessError::InvalidPgn(format!("illegal move {}", token)))?;
+                board.make_move(next_move);
+                game.moves.push(next_move);
+            }
+        }
+    }
+    Ok(game)
+}
+
+//a variation replaces the last move, the line ends with the closing bracket
+fn collect_lines(
+    mut board: Board,
+    mut line: Vec<Move>,
+    tokens: &[String],
+    next: &mut usize,
+    lines: &mut Vec<Vec<Move>>,
+) -> Result<(), ChessError> {
+    let mut before_last = board;
+    while let Some(token) = tokens.get(*next) {
+        *next += 1;
+        match token.as_str() {
+            "(" => {
+                let start = line[..line.len().saturating_sub(1)].to_vec();
+                collect_lines(before_last, start, tokens, next, lines)?;
+            }
+            ")" => break,
+            san => {
+                let next_move = from_san(&board, san)
+                    .map_err(|_| ChessError::InvalidPgn(format!("illegal move {}", san)))?;
+                before_last = board;
+                board.make_move(next_move);
+                line.push(next_move);
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    Ok(())
+}
+
+//moves and the brackets of variations
+fn movetext_tokens(movetext: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut in_comment = false;
+    let mut current = String::new();
+    let mut chars = movetext.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => in_comment = true,
+            '}' => in_comment = false,
+            _ if in_comment => {}
+            ';' => {
+                //comment until the end of the line
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' | ')' => {
+                push_token(&mut tokens, &current);
+                current.clear();
+                tokens.push(c.to_string());
+            }
+            _ if c.is_whitespace() => {
+                push_token(&mut tokens, &current);
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    push_token(&mut tokens, &current);
+    tokens
+}
+
+fn push_token(tokens: &mut Vec<String>, token: &str) {
+    if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token) {
+        return;
+    }
+    //move numbers can be glued to the move as in "1.e4"
+    let token = match token.rfind('.') {
+        Some(i) => &token[i + 1..],
+        None => token,
+    };
+    if token.is_empty() || token.starts_with('$') {
+        return;
+    }
+    tokens.push(token.to_string());
+}
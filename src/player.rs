@@ -0,0 +1,112 @@
+use crate::board::Board;
+use crate::engine::{BuiltinEngine, EngineConfig};
+use crate::field::{ChessError, Move};
+use crate::tablebase::Tablebase;
+use crate::transposition::TtStats;
+use crate::uci::{SearchLimit, UciEngine};
+
+pub enum Player {
+    Human,
+    Builtin(BuiltinEngine),
+    Uci(UciEngine),
+}
+
+impl Player {
+    //"human", "builtin", "builtin:<depth>" or "builtin:<depth>:<hash mb>" for the built-in engine,
+    //anything else is a UCI engine path, the built-in engine only takes a depth limit
+    pub fn from_spec(spec: &str, limit: Option<SearchLimit>) -> Result<Player, ChessError> {
+        if spec == "human" {
+            return Ok(Player::Human);
+        }
+        if let Some(config) = spec.strip_prefix("builtin") {
+            let mut engine_config = EngineConfig::new();
+            if let Some(options) = config.strip_prefix(':') {
+                let (depth, hash_mb) = match options.split_once(':') {
+                    Some((depth, hash_mb)) => (depth, Some(hash_mb)),
+                    None => (options, None),
+                };
+                engine_config.depth = depth
+                    .parse()
+                    .map_err(|_| ChessError::EngineError(format!("invalid depth {}", depth)))?;
+                if let Some(hash_mb) = hash_mb {
+                    engine_config.hash_mb = hash_mb.parse().map_err(|_| {
+                        ChessError::EngineError(format!("invalid hash size {}", hash_mb))
+                    })?;
+                }
+            } else if !config.is_empty() {
+                return Err(ChessError::EngineError(format!("unknown engine {}", spec)));
+            }
+            match limit {
+                Some(SearchLimit::Depth(depth)) if config.is_empty() => engine_config.depth = depth,
+                Some(SearchLimit::MoveTime(_)) | Some(SearchLimit::Nodes(_)) => {
+                    return Err(ChessError::EngineError(String::from(
+                        "the built-in engine only searches to a depth",
+                    )))
+                }
+                _ => {}
+            }
+            return Ok(Player::Builtin(BuiltinEngine::new(engine_config)));
+        }
+        let mut engine = UciEngine::new(spec)?;
+        if let Some(limit) = limit {
+            engine.set_limit(limit);
+        }
+        Ok(Player::Uci(engine))
+    }
+
+    pub fn is_human(&self) -> bool {
+        matches!(self, Player::Human)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Player::Human => String::from("Human"),
+            Player::Builtin(engine) => format!("Builtin depth {}", engine.config.depth),
+            Player::Uci(engine) => engine.name().to_string(),
+        }
+    }
+
+    pub fn new_game(&mut self) -> Result<(), ChessError> {
+        match self {
+            Player::Uci(engine) => engine.new_game()?,
+            Player::Builtin(engine) => engine.new_game(),
+            Player::Human => {}
+        }
+        Ok(())
+    }
+
+    //Syzygy tables for engines, UCI engines are told the directory
+    pub fn set_tablebase(&mut self, directory: &str) -> Result<(), ChessError> {
+        match self {
+            Player::Builtin(engine) => engine.set_tablebase(Some(Tablebase::open(directory)?)),
+            Player::Uci(engine) => engine.set_option("SyzygyPath", directory)?,
+            Player::Human => {}
+        }
+        Ok(())
+    }
+
+    //transposition table statistics of the built-in engine
+    pub fn table_stats(&self) -> Option<(TtStats, usize)> {
+        match self {
+            Player::Builtin(engine) => Some(engine.table_stats()),
+            _ => None,
+        }
+    }
+
+    //the game so far is given as start position and moves, humans don't choose here
+    pub fn choose_move(
+        &mut self,
+        start_fen: &str,
+        moves: &[Move],
+        board: &Board,
+    ) -> Result<Option<Move>, ChessError> {
+        match self {
+            Player::Human => Ok(None),
+            Player::Builtin(engine) => Ok(engine.choose_move(board)),
+            Player::Uci(engine) => {
+                engine.set_chess960(board.is_chess960())?;
+                Ok(engine.go(start_fen, moves)?.best_move)
+            }
+        }
+    }
+}
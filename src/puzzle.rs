@@ -0,0 +1,275 @@
+use std::fs;
+use std::io::{self, Write};
+
+use crate::board::Board;
+use crate::field::{ChessError, Move};
+use crate::pgn::{from_san, to_san};
+
+const START_RATING: f64 = 1500.0;
+//rating change for a result against expectation
+const RATING_K: f64 = 32.0;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Puzzle {
+    pub id: String,
+    //the position before the opponent's move which starts the puzzle
+    pub fen: String,
+    //the opponent's move, then the solution alternating with the replies
+    pub moves: Vec<Move>,
+    pub rating: i32,
+    pub themes: String,
+}
+
+//PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,... like the Lichess database
+pub fn parse_puzzles(text: &str) -> Result<Vec<Puzzle>, ChessError> {
+    let mut puzzles = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("PuzzleId") {
+            continue;
+        }
+        let invalid = || ChessError::InvalidFen(format!("puzzle in line {}", number + 1));
+        let columns: Vec<&str> = line.split(',').collect();
+        if columns.len() < 4 {
+            return Err(invalid());
+        }
+        let moves = columns[2]
+            .split_whitespace()
+            .map(Move::from_uci)
+            .collect::<Result<Vec<Move>, ChessError>>()
+            .map_err(|_| invalid())?;
+        if moves.len() < 2 {
+            return Err(invalid());
+        }
+        let mut board = Board::new();
+        board.set_board_fen(columns[1])?;
+        for next_move in &moves {
+            if !board.legal_moves().contains(next_move) {
+                return Err(invalid());
+            }
+            board.make_move(*next_move);
+        }
+        puzzles.push(Puzzle {
+            id: columns[0].to_string(),
+            fen: columns[1].to_string(),
+            moves,
+            rating: columns[3].parse().map_err(|_| invalid())?,
+            themes: columns.get(7).unwrap_or(&"").to_string(),
+        });
+    }
+    Ok(puzzles)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attempt {
+    //right move, the opponent answered with the reply
+    Correct(Move),
+    Solved,
+    Wrong,
+}
+
+//a puzzle being solved, the position is after the opponent's first move
+pub struct PuzzleSession {
+    puzzle: Puzzle,
+    board: Board,
+    //index of the next expected move
+    next: usize,
+}
+
+impl PuzzleSession {
+    pub fn new(puzzle: &Puzzle) -> Result<PuzzleSession, ChessError> {
+        let mut board = Board::new();
+        board.set_board_fen(&puzzle.fen)?;
+        board.make_move(puzzle.moves[0]);
+        Ok(PuzzleSession {
+            puzzle: puzzle.clone(),
+            board,
+            next: 1,
+        })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn expected(&self) -> Move {
+        self.puzzle.moves[self.next]
+    }
+
+    //any mate is as good as the expected move
+    pub fn attempt(&mut self, next_move: Move) -> Attempt {
+        if !self.board.legal_moves().contains(&next_move) {
+            return Attempt::Wrong;
+        }
+        let mut after = self.board;
+        after.make_move(next_move);
+        let mate = after.legal_moves().is_empty() && after.is_in_check(after.active_player());
+        if next_move != self.expected() && !mate {
+            return Attempt::Wrong;
+        }
+        self.board = after;
+        self.next += 1;
+        if mate || self.next >= self.puzzle.moves.len() {
+            return Attempt::Solved;
+        }
+        let reply = self.puzzle.moves[self.next];
+        self.board.make_move(reply);
+        self.next += 1;
+        if self.next >= self.puzzle.moves.len() {
+            return Attempt::Solved;
+        }
+        Attempt::Correct(reply)
+    }
+
+    //the rest of the solution in SAN
+    pub fn solution(&self) -> String {
+        let mut board = self.board;
+        let mut line = Vec::new();
+        for next_move in &self.puzzle.moves[self.next..] {
+            line.push(to_san(&board, *next_move));
+            board.make_move(*next_move);
+        }
+        line.join(" ")
+    }
+}
+
+pub struct PuzzleTrainer {
+    puzzles: Vec<Puzzle>,
+    played: Vec<bool>,
+    rating: f64,
+    streak: u32,
+    best_streak: u32,
+    solved: u32,
+    failed: u32,
+}
+
+impl PuzzleTrainer {
+    pub fn new(puzzles: Vec<Puzzle>) -> PuzzleTrainer {
+        PuzzleTrainer {
+            played: vec![false; puzzles.len()],
+            puzzles,
+            rating: START_RATING,
+            streak: 0,
+            best_streak: 0,
+            solved: 0,
+            failed: 0,
+        }
+    }
+
+    pub fn open(path: &str) -> Result<PuzzleTrainer, ChessError> {
+        let text = fs::read_to_string(path).map_err(|e| ChessError::FileError(e.to_string()))?;
+        Ok(PuzzleTrainer::new(parse_puzzles(&text)?))
+    }
+
+    pub fn rating(&self) -> i32 {
+        self.rating.round() as i32
+    }
+
+    pub fn streak(&self) -> u32 {
+        self.streak
+    }
+
+    //the unplayed puzzle closest to the own rating
+    pub fn next_puzzle(&mut self) -> Option<&Puzzle> {
+        let rating = self.rating;
+        let index = (0..self.puzzles.len())
+            .filter(|i| !self.played[*i])
+            .min_by_key(|i| (self.puzzles[*i].rating as f64 - rating).abs() as i32)?;
+        self.played[index] = true;
+        Some(&self.puzzles[index])
+    }
+
+    //Elo update against the puzzle rating
+    pub fn record(&mut self, puzzle_rating: i32, solved: bool) {
+        let expected = 1.0 / (1.0 + 10f64.powf((puzzle_rating as f64 - self.rating) / 400.0));
+        let score = if solved { 1.0 } else { 0.0 };
+        self.rating += RATING_K * (score - expected);
+        if solved {
+            self.solved += 1;
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.failed += 1;
+            self.streak = 0;
+        }
+    }
+
+    fn read_move(board: &Board, input: &str) -> Result<Move, ChessError> {
+        let compact: String = input.split_whitespace().collect();
+        Move::from_uci(&compact).or_else(|_| from_san(board, input.trim()))
+    }
+
+    //moves as "e2 e4", "e2e4" or "Nf3", "solution" gives up and "exit" ends the training
+    pub fn puzzle_loop(&mut self) -> Result<(), io::Error> {
+        println!(
+            "{} puzzles loaded, your rating is {}",
+            self.puzzles.len(),
+            self.rating()
+        );
+        'puzzles: while let Some(puzzle) = self.next_puzzle().cloned() {
+            let mut session = match PuzzleSession::new(&puzzle) {
+                Ok(c) => c,
+                Err(e) => {
+                    println!("{}", e);
+                    continue;
+                }
+            };
+            println!("Puzzle {} rated {}", puzzle.id, puzzle.rating);
+            println!("{}", session.board());
+            println!("{} to move", session.board().active_player());
+            let solved = loop {
+                print!("> ");
+                io::stdout().flush()?;
+                let mut input = String::new();
+                if io::stdin().read_line(&mut input)? == 0 || input.trim() == "exit" {
+                    break 'puzzles;
+                }
+                if input.trim() == "solution" {
+                    println!("The solution was {}", session.solution());
+                    break false;
+                }
+                let next_move = match PuzzleTrainer::read_move(session.board(), &input) {
+                    Ok(c) => c,
+                    Err(e) => {
+                        println!("{}", e);
+                        continue;
+                    }
+                };
+                if !session.board().legal_moves().contains(&next_move) {
+                    println!("{} is not a legal move", next_move);
+                    continue;
+                }
+                let san = to_san(session.board(), next_move);
+                let mut after = *session.board();
+                after.make_move(next_move);
+                match session.attempt(next_move) {
+                    Attempt::Correct(reply) => {
+                        println!("{} is right, the answer is {}", san, to_san(&after, reply));
+                        println!("{}", session.board());
+                    }
+                    Attempt::Solved => break true,
+                    Attempt::Wrong => {
+                        println!("{} is not the solution, it was {}", san, session.solution());
+                        break false;
+                    }
+                }
+            };
+            self.record(puzzle.rating, solved);
+            if solved {
+                println!("Solved!");
+            }
+            if !puzzle.themes.is_empty() {
+                println!("Themes: {}", puzzle.themes);
+            }
+            println!("Rating {}, streak {}", self.rating(), self.streak());
+        }
+        println!(
+            "{} solved, {} failed, best streak {}, rating {}",
+            self.solved,
+            self.failed,
+            self.best_streak,
+            self.rating()
+        );
+        Ok(())
+    }
+}
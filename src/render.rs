@@ -0,0 +1,173 @@
+use crate::board::Board;
+use crate::field::{ChessError, Color, Field, FieldType};
+use crate::square::{File, Rank, Square};
+
+pub const LIGHT: &str = "\x1b[48;5;180m";
+pub const DARK: &str = "\x1b[48;5;137m";
+pub const RESET: &str = "\x1b[0m";
+const WHITE_FIGURE: &str = "\x1b[1;38;5;231m";
+const BLACK_FIGURE: &str = "\x1b[38;5;16m";
+
+//which side is at the bottom
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    White,
+    Black,
+    SideToMove,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PieceSet {
+    //outlined white and filled black glyphs
+    Unicode,
+    //filled glyphs for both colors, told apart by the figure color
+    Solid,
+    //FEN letters, white upper case
+    Letters,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub colors: bool,
+    pub orientation: Orientation,
+    pub pieces: PieceSet,
+}
+
+impl RenderOptions {
+    pub fn new() -> RenderOptions {
+        RenderOptions {
+            colors: true,
+            orientation: Orientation::White,
+            pieces: PieceSet::Unicode,
+        }
+    }
+
+    //true when black is at the bottom
+    pub fn flipped(&self, board: &Board) -> bool {
+        match self.orientation {
+            Orientation::White => false,
+            Orientation::Black => true,
+            Orientation::SideToMove => board.active_player() == Color::Black,
+        }
+    }
+
+    //pairs like "colors off", "flip auto" or "pieces letters" change the options
+    pub fn apply(&mut self, input: &str) -> Result<(), ChessError> {
+        let usage = || {
+            ChessError::InvalidCommand(String::from(
+                "board [colors on|off] [flip white|black|auto] [pieces unicode|solid|letters], board plain",
+            ))
+        };
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        if !tokens.len().is_multiple_of(2) {
+            return Err(usage());
+        }
+        let mut options = *self;
+        for pair in tokens.chunks(2) {
+            match (pair[0], pair[1]) {
+                ("colors", "on") => options.colors = true,
+                ("colors", "off") => options.colors = false,
+                ("flip", "white") => options.orientation = Orientation::White,
+                ("flip", "black") => options.orientation = Orientation::Black,
+                ("flip", "auto") => options.orientation = Orientation::SideToMove,
+                ("pieces", "unicode") => options.pieces = PieceSet::Unicode,
+                ("pieces", "solid") => options.pieces = PieceSet::Solid,
+                ("pieces", "letters") => options.pieces = PieceSet::Letters,
+                _ => return Err(usage()),
+            }
+        }
+        *self = options;
+        Ok(())
+    }
+}
+
+//the black pawn is written without the variation selector of Field's Display,
+//which some terminals draw one column wider
+pub fn glyph(field: Field, pieces: PieceSet) -> char {
+    let figure_type = field.get_type();
+    if figure_type == FieldType::None {
+        return ' ';
+    }
+    let filled = match pieces {
+        PieceSet::Letters => {
+            return match field.get_color() {
+                Color::White => figure_type.letter(),
+                _ => figure_type.letter().to_ascii_lowercase(),
+            }
+        }
+        PieceSet::Solid => true,
+        PieceSet::Unicode => field.get_color() == Color::Black,
+    };
+    match (figure_type, filled) {
+        (FieldType::King, false) => '♔',
+        (FieldType::Queen, false) => '♕',
+        (FieldType::Tower, false) => '♖',
+        (FieldType::Bishop, false) => '♗',
+        (FieldType::Knight, false) => '♘',
+        (FieldType::Pawn, false) => '♙',
+        (FieldType::King, true) => '♚',
+        (FieldType::Queen, true) => '♛',
+        (FieldType::Tower, true) => '♜',
+        (FieldType::Bishop, true) => '♝',
+        (FieldType::Knight, true) => '♞',
+        _ => '♟',
+    }
+}
+
+//the board with coordinates on all sides, without colors empty fields are dots
+pub fn render(board: &Board, options: &RenderOptions) -> String {
+    let flipped = options.flipped(board);
+    let ranks: Vec<Rank> = if flipped {
+        Rank::ALL.into_iter().collect()
+    } else {
+        Rank::ALL.into_iter().rev().collect()
+    };
+    let files: Vec<File> = if flipped {
+        File::ALL.into_iter().rev().collect()
+    } else {
+        File::ALL.into_iter().collect()
+    };
+    let file_line: String = files.iter().map(|y| format!(" {} ", y)).collect();
+    let (top, bottom) = if flipped {
+        (Color::White, Color::Black)
+    } else {
+        (Color::Black, Color::White)
+    };
+
+    let mut text = String::new();
+    if board.is_crazyhouse() {
+        text.push_str(&format!("   {}\n", board.pocket_line(top)));
+    }
+    text.push_str(&format!("  {}\n", file_line));
+    for x in &ranks {
+        text.push_str(&format!("{} ", x));
+        for y in &files {
+            let field = board.get(Square::new(*y, *x));
+            let mut mark = glyph(field, options.pieces);
+            if !options.colors {
+                if mark == ' ' {
+                    mark = '.';
+                }
+                text.push_str(&format!(" {} ", mark));
+                continue;
+            }
+            let background = if (x.number() + y.number()) % 2 == 0 {
+                DARK
+            } else {
+                LIGHT
+            };
+            let foreground = if field.get_color() == Color::White {
+                WHITE_FIGURE
+            } else {
+                BLACK_FIGURE
+            };
+            text.push_str(&format!("{}{} {} {}", background, foreground, mark, RESET));
+        }
+        text.push_str(&format!(" {}\n", x));
+    }
+    text.push_str(&format!("  {}\n", file_line));
+    if board.is_crazyhouse() {
+        text.push_str(&format!("   {}\n", board.pocket_line(bottom)));
+    }
+    text
+}
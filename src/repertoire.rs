@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::board::Board;
+use crate::book::random_u64;
+use crate::field::{ChessError, Color, Move};
+use crate::pgn::parse_pgn_lines;
+
+//days until a line is due again by its box, failed lines go back to the first box
+const INTERVALS: [u64; 6] = [0, 1, 2, 4, 8, 16];
+
+pub struct Repertoire {
+    start: Board,
+    //every line from the start to the end of a variation
+    lines: Vec<Vec<Move>>,
+    //repertoire moves of every position, transpositions share their moves
+    moves: HashMap<u64, Vec<Move>>,
+}
+
+impl Repertoire {
+    //all games of the PGN with their variations are merged into one tree
+    pub fn parse(text: &str) -> Result<Repertoire, ChessError> {
+        let games = parse_pgn_lines(text)?;
+        let start = games
+            .first()
+            .map(|c| c.0)
+            .ok_or_else(|| ChessError::InvalidPgn(String::from("empty repertoire")))?;
+        let mut repertoire = Repertoire {
+            start,
+            lines: Vec::new(),
+            moves: HashMap::new(),
+        };
+        for (game_start, lines) in games {
+            if game_start.fen() != start.fen() {
+                return Err(ChessError::InvalidPgn(String::from(
+                    "all lines of a repertoire need the same start position",
+                )));
+            }
+            for line in lines {
+                let mut board = start;
+                for next_move in &line {
+                    let moves = repertoire.moves.entry(board.zobrist_key()).or_default();
+                    if !moves.contains(next_move) {
+                        moves.push(*next_move);
+                    }
+                    board.make_move(*next_move);
+                }
+                if !repertoire.lines.contains(&line) {
+                    repertoire.lines.push(line);
+                }
+            }
+        }
+        Ok(repertoire)
+    }
+
+    pub fn open(path: &str) -> Result<Repertoire, ChessError> {
+        let text = fs::read_to_string(path).map_err(|e| ChessError::FileError(e.to_string()))?;
+        Repertoire::parse(&text)
+    }
+
+    pub fn start(&self) -> Board {
+        self.start
+    }
+
+    pub fn lines(&self) -> &[Vec<Move>] {
+        &self.lines
+    }
+
+    pub fn moves(&self, board: &Board) -> &[Move] {
+        self.moves
+            .get(&board.zobrist_key())
+            .map(|c| c.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+fn line_key(line: &[Move]) -> String {
+    line.iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|c| c.as_secs() / 86400)
+        .unwrap_or(0)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Review {
+    pub level: usize,
+    //day since 1970 when the line is asked again
+    pub due: u64,
+}
+
+//Leitner boxes of the lines, kept in a file next to the repertoire
+pub struct Schedule {
+    path: String,
+    reviews: HashMap<String, Review>,
+}
+
+impl Schedule {
+    //lines of "<box> <due day> <moves>"
+    pub fn open(path: &str) -> Schedule {
+        let mut schedule = Schedule {
+            path: path.to_string(),
+            reviews: HashMap::new(),
+        };
+        for line in fs::read_to_string(path).unwrap_or_default().lines() {
+            let mut tokens = line.splitn(3, ' ');
+            if let (Some(level), Some(due), Some(moves)) =
+                (tokens.next(), tokens.next(), tokens.next())
+            {
+                if let (Ok(level), Ok(due)) = (level.parse(), due.parse()) {
+                    schedule
+                        .reviews
+                        .insert(moves.to_string(), Review { level, due });
+                }
+            }
+        }
+        schedule
+    }
+
+    //new lines are due right away
+    pub fn review(&self, line: &[Move]) -> Review {
+        self.reviews
+            .get(&line_key(line))
+            .copied()
+            .unwrap_or(Review { level: 0, due: 0 })
+    }
+
+    pub fn record(&mut self, line: &[Move], correct: bool, day: u64) -> Result<(), ChessError> {
+        let level = if correct {
+            (self.review(line).level + 1).min(INTERVALS.len() - 1)
+        } else {
+            0
+        };
+        self.reviews.insert(
+            line_key(line),
+            Review {
+                level,
+                due: day + INTERVALS[level],
+            },
+        );
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), ChessError> {
+        let mut text = String::new();
+        for (moves, review) in &self.reviews {
+            text.push_str(&format!("{} {} {}\n", review.level, review.due, moves));
+        }
+        fs::write(&self.path, text).map_err(|e| ChessError::FileError(e.to_string()))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DrillAnswer {
+    InRepertoire,
+    //the repertoire moves of the position
+    Deviation(Vec<Move>),
+}
+
+//the program plays the other color, the user has to find the repertoire moves
+pub struct RepertoireDrill {
+    repertoire: Repertoire,
+    color: Color,
+    schedule: Schedule,
+    played: Vec<Move>,
+    mistakes: usize,
+    day: u64,
+}
+
+impl RepertoireDrill {
+    pub fn new(repertoire: Repertoire, color: Color, schedule: Schedule) -> RepertoireDrill {
+        RepertoireDrill {
+            repertoire,
+            color,
+            schedule,
+            played: Vec::new(),
+            mistakes: 0,
+            day: today(),
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn start(&mut self) -> Board {
+        self.played.clear();
+        self.mistakes = 0;
+        self.repertoire.start()
+    }
+
+    pub fn check(&mut self, board: &Board, next_move: Move) -> DrillAnswer {
+        let moves = self.repertoire.moves(board);
+        if moves.contains(&next_move) {
+            self.played.push(next_move);
+            DrillAnswer::InRepertoire
+        } else {
+            self.mistakes += 1;
+            DrillAnswer::Deviation(moves.to_vec())
+        }
+    }
+
+    //the user's turn ends the line when the repertoire has no move for it
+    pub fn line_finished(&self, board: &Board) -> bool {
+        self.repertoire.moves(board).is_empty()
+    }
+
+    //a random branch, branches with due and weak lines first
+    pub fn reply(&mut self, board: &Board) -> Option<Move> {
+        let moves = self.repertoire.moves(board);
+        if moves.is_empty() {
+            return None;
+        }
+        let ranked: Vec<(Move, (bool, usize))> = moves
+            .iter()
+            .map(|m| {
+                let mut prefix = self.played.clone();
+                prefix.push(*m);
+                let urgency = self
+                    .repertoire
+                    .lines()
+                    .iter()
+                    .filter(|c| c.starts_with(&prefix))
+                    .map(|c| {
+                        let review = self.schedule.review(c);
+                        (review.due <= self.day, usize::MAX - review.level)
+                    })
+                    .max()
+                    .unwrap_or((false, 0));
+                (*m, urgency)
+            })
+            .collect();
+        let most_urgent = ranked.iter().map(|c| c.1).max()?;
+        let candidates: Vec<Move> = ranked
+            .into_iter()
+            .filter(|c| c.1 == most_urgent)
+            .map(|c| c.0)
+            .collect();
+        let next_move = candidates[(random_u64() % candidates.len() as u64) as usize];
+        self.played.push(next_move);
+        Some(next_move)
+    }
+
+    //schedules the played line, true without mistakes
+    pub fn finish_line(&mut self) -> Result<bool, ChessError> {
+        let correct = self.mistakes == 0;
+        self.schedule.record(&self.played, correct, self.day)?;
+        Ok(correct)
+    }
+
+    //number of lines, of lines due today and of lines in the first box
+    pub fn status(&self) -> (usize, usize, usize) {
+        let lines = self.repertoire.lines();
+        let reviews: Vec<Review> = lines.iter().map(|c| self.schedule.review(c)).collect();
+        (
+            lines.len(),
+            reviews.iter().filter(|c| c.due <= self.day).count(),
+            reviews.iter().filter(|c| c.level == 0).count(),
+        )
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt;
+
+//columns of the board, the a file is on white's left
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum File {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+
+//rows of the board, the first rank is on white's side
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Eighth,
+}
+
+impl File {
+    pub const ALL: [File; 8] = [
+        File::A,
+        File::B,
+        File::C,
+        File::D,
+        File::E,
+        File::F,
+        File::G,
+        File::H,
+    ];
+
+    //1 for the a file, None outside of the board
+    pub fn new(number: i32) -> Option<File> {
+        usize::try_from(number - 1)
+            .ok()
+            .and_then(|i| File::ALL.get(i).copied())
+    }
+
+    pub fn number(self) -> i32 {
+        self as i32 + 1
+    }
+
+    pub fn letter(self) -> char {
+        (b'a' + self as u8) as char
+    }
+
+    pub fn from_letter(c: char) -> Option<File> {
+        File::ALL.into_iter().find(|f| f.letter() == c)
+    }
+}
+
+impl Rank {
+    pub const ALL: [Rank; 8] = [
+        Rank::First,
+        Rank::Second,
+        Rank::Third,
+        Rank::Fourth,
+        Rank::Fifth,
+        Rank::Sixth,
+        Rank::Seventh,
+        Rank::Eighth,
+    ];
+
+    //1 for the first rank, None outside of the board
+    pub fn new(number: i32) -> Option<Rank> {
+        usize::try_from(number - 1)
+            .ok()
+            .and_then(|i| Rank::ALL.get(i).copied())
+    }
+
+    pub fn number(self) -> i32 {
+        self as i32 + 1
+    }
+
+    pub fn from_digit(c: char) -> Option<Rank> {
+        c.to_digit(10).and_then(|n| Rank::new(n as i32))
+    }
+}
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.letter())
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+//a field of the board, written as "e4"
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square {
+    rank: Rank,
+    file: File,
+}
+
+impl Square {
+    pub const fn new(file: File, rank: Rank) -> Square {
+        Square { rank, file }
+    }
+
+    //rank and file counted from 1, None outside of the board
+    pub fn from_coords(rank: i32, file: i32) -> Option<Square> {
+        Some(Square::new(File::new(file)?, Rank::new(rank)?))
+    }
+
+    pub fn file(self) -> File {
+        self.file
+    }
+
+    pub fn rank(self) -> Rank {
+        self.rank
+    }
+
+    //fields are counted rank by rank, a1 first
+    pub fn index(self) -> usize {
+        self.rank as usize * 8 + self.file as usize
+    }
+
+    //the bit of the field in a set of fields held as u64
+    pub fn bit(self) -> u64 {
+        1 << self.index()
+    }
+
+    pub fn from_index(index: usize) -> Option<Square> {
+        Some(Square::new(
+            *File::ALL.get(index % 8)?,
+            *Rank::ALL.get(index / 8)?,
+        ))
+    }
+
+    //all fields in the order of their index
+    pub fn all() -> impl DoubleEndedIterator<Item = Square> {
+        (0..64).filter_map(Square::from_index)
+    }
+
+    //the field the given number of ranks up and files right, None when it leaves the board
+    pub fn offset(self, ranks: i32, files: i32) -> Option<Square> {
+        Square::from_coords(self.rank.number() + ranks, self.file.number() + files)
+    }
+
+    //"e4", None for anything else
+    pub fn parse(text: &str) -> Option<Square> {
+        let mut chars = text.chars();
+        let file = File::from_letter(chars.next()?)?;
+        let rank = Rank::from_digit(chars.next()?)?;
+        if chars.next().is_some() {
+            return None;
+        }
+        Some(Square::new(file, rank))
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.file, self.rank)
+    }
+}
@@ -0,0 +1,1035 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::board::Board;
+use crate::field::{ChessError, Color, FieldType, Move};
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+//table names list the pieces in this order, e.g. "KRPvKP"
+const PIECE_ORDER: [FieldType; 6] = [
+    FieldType::King,
+    FieldType::Queen,
+    FieldType::Tower,
+    FieldType::Bishop,
+    FieldType::Knight,
+    FieldType::Pawn,
+];
+
+//flags of the table sections
+const STM: u8 = 1;
+const MAPPED: u8 = 2;
+const WIN_PLIES: u8 = 4;
+const LOSS_PLIES: u8 = 8;
+const WIDE: u8 = 16;
+const SINGLE_VALUE: u8 = 128;
+
+//win/draw/loss from the view of the side to move, cursed wins and blessed losses
+//are drawn by the fifty move rule
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Wdl {
+        match value {
+            -2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            1 => Wdl::CursedWin,
+            2 => Wdl::Win,
+            _ => Wdl::Draw,
+        }
+    }
+}
+
+impl fmt::Display for Wdl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Wdl::Loss => write!(f, "Loss"),
+            Wdl::BlessedLoss => write!(f, "Blessed loss"),
+            Wdl::Draw => write!(f, "Draw"),
+            Wdl::CursedWin => write!(f, "Cursed win"),
+            Wdl::Win => write!(f, "Win"),
+        }
+    }
+}
+
+//squares are Square::index values, a1 = 0 over b1 = 1 to h8 = 63 like in the table files
+fn rank_of(square: usize) -> usize {
+    square >> 3
+}
+
+fn file_of(square: usize) -> usize {
+    square & 7
+}
+
+//distance above the a1-h8 diagonal, negative below it
+fn off_diagonal(square: usize) -> i32 {
+    rank_of(square) as i32 - file_of(square) as i32
+}
+
+//piece codes of the table files: pawn 1 to king 6, black pieces have bit 3 set
+fn piece_code(figure_type: FieldType, color: Color) -> u8 {
+    let code = match figure_type {
+        FieldType::Pawn => 1,
+        FieldType::Knight => 2,
+        FieldType::Bishop => 3,
+        FieldType::Tower => 4,
+        FieldType::Queen => 5,
+        FieldType::King => 6,
+        FieldType::None => 0,
+    };
+    if color == Color::Black {
+        code | 8
+    } else {
+        code
+    }
+}
+
+//index tables shared by all tables
+struct Encoding {
+    binomial: [[u64; 64]; 7],
+    map_b1h1h7: [u64; 64],
+    map_a1d1d4: [u64; 64],
+    map_kk: [[u64; 64]; 10],
+    map_pawns: [u64; 64],
+    lead_pawn_idx: [[u64; 64]; 6],
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+impl Encoding {
+    fn get() -> &'static Encoding {
+        static ENCODING: OnceLock<Encoding> = OnceLock::new();
+        ENCODING.get_or_init(Encoding::new)
+    }
+
+    fn new() -> Encoding {
+        let mut encoding = Encoding {
+            binomial: [[0; 64]; 7],
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            map_pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        //squares below the a1-h8 diagonal
+        let mut code = 0;
+        for s in 0..64 {
+            if off_diagonal(s) < 0 {
+                encoding.map_b1h1h7[s] = code;
+                code += 1;
+            }
+        }
+
+        //the a1-d1-d4 triangle with the diagonal squares last
+        let mut diagonal = Vec::new();
+        code = 0;
+        for s in 0..28 {
+            if off_diagonal(s) < 0 && file_of(s) <= 3 {
+                encoding.map_a1d1d4[s] = code;
+                code += 1;
+            } else if off_diagonal(s) == 0 && file_of(s) <= 3 {
+                diagonal.push(s);
+            }
+        }
+        for s in diagonal {
+            encoding.map_a1d1d4[s] = code;
+            code += 1;
+        }
+
+        //the 462 legal placements of two kings with the first in the triangle,
+        //placements with both kings on the diagonal come last
+        let mut both_on_diagonal = Vec::new();
+        code = 0;
+        for idx in 0..10 {
+            for s1 in 0..28 {
+                if encoding.map_a1d1d4[s1] != idx as u64 || (idx == 0 && s1 != 1) {
+                    continue;
+                }
+                for s2 in 0..64 {
+                    let adjacent = (rank_of(s1) as i32 - rank_of(s2) as i32).abs() <= 1
+                        && (file_of(s1) as i32 - file_of(s2) as i32).abs() <= 1;
+                    if adjacent || (off_diagonal(s1) == 0 && off_diagonal(s2) > 0) {
+                        continue;
+                    }
+                    if off_diagonal(s1) == 0 && off_diagonal(s2) == 0 {
+                        both_on_diagonal.push((idx, s2));
+                    } else {
+                        encoding.map_kk[idx][s2] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, s2) in both_on_diagonal {
+            encoding.map_kk[idx][s2] = code;
+            code += 1;
+        }
+
+        encoding.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..7.min(n + 1) {
+                encoding.binomial[k][n] = if k > 0 {
+                    encoding.binomial[k - 1][n - 1]
+                } else {
+                    0
+                } + if k < n {
+                    encoding.binomial[k][n - 1]
+                } else {
+                    0
+                };
+            }
+        }
+
+        //pawn squares a2-h7, the leading pawn is the one with the highest value
+        let mut available = 47;
+        for lead_pawns in 1..6 {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..7 {
+                    let s = rank * 8 + file;
+                    if lead_pawns == 1 {
+                        encoding.map_pawns[s] = available;
+                        encoding.map_pawns[s ^ 7] = available - 1;
+                        available = available.saturating_sub(2);
+                    }
+                    encoding.lead_pawn_idx[lead_pawns][s] = idx;
+                    idx += encoding.binomial[lead_pawns - 1][encoding.map_pawns[s] as usize];
+                }
+                encoding.lead_pawns_size[lead_pawns][file] = idx;
+            }
+        }
+        encoding
+    }
+}
+
+//one compressed section of a table, for one side to move and one file of the leading pawn
+#[derive(Clone, Debug, Default)]
+struct PairsData {
+    flags: u8,
+    pieces: Vec<u8>,
+    group_len: Vec<usize>,
+    //the last entry is the number of positions of the section
+    group_idx: Vec<u64>,
+    block_size: u64,
+    span: u64,
+    sparse_index_size: u64,
+    blocks: u64,
+    block_length_size: u64,
+    min_sym_len: u8,
+    lowest_sym: usize,
+    base: Vec<u64>,
+    symlen: Vec<u32>,
+    btree: usize,
+    sparse_index: usize,
+    block_length: usize,
+    data: usize,
+    //positions of the four DTZ value maps
+    map_idx: [usize; 4],
+}
+
+struct Table {
+    bytes: Vec<u8>,
+    dtz: bool,
+    symmetric: bool,
+    has_pawns: bool,
+    //a piece other than a king is alone of its kind, three pieces lead instead of the kings
+    unique_pieces: bool,
+    //the second group holds the pawns of the other side
+    both_pawns: bool,
+    //indexed by file of the leading pawn, then by side to move
+    sections: Vec<Vec<PairsData>>,
+}
+
+impl Table {
+    fn read_u8(&self, pos: usize) -> Option<u8> {
+        self.bytes.get(pos).copied()
+    }
+
+    fn read_u16(&self, pos: usize) -> Option<u16> {
+        Some(u16::from_le_bytes([
+            self.read_u8(pos)?,
+            self.read_u8(pos + 1)?,
+        ]))
+    }
+
+    fn read_u32(&self, pos: usize) -> Option<u32> {
+        let bytes = self.bytes.get(pos..pos + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_u32_be(&self, pos: usize) -> Option<u32> {
+        let bytes = self.bytes.get(pos..pos + 4)?;
+        Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    //name like "KRvK" tells the material, white is the first part
+    fn new(bytes: Vec<u8>, name: &str, dtz: bool) -> Option<Table> {
+        let (white, black) = name.split_once('v')?;
+        let magic = if dtz { DTZ_MAGIC } else { WDL_MAGIC };
+        if bytes.get(0..4)? != magic {
+            return None;
+        }
+        let count = |side: &str, c: char| side.chars().filter(|s| *s == c).count();
+        let has_pawns = name.contains('P');
+        if has_pawns != (bytes.get(4)? & 2 != 0) {
+            return None;
+        }
+        let mut table = Table {
+            bytes,
+            dtz,
+            symmetric: white == black,
+            has_pawns,
+            unique_pieces: false,
+            both_pawns: false,
+            sections: Vec::new(),
+        };
+
+        //the side with fewer pawns leads
+        let white_pawns = count(white, 'P');
+        let black_pawns = count(black, 'P');
+        let white_leads = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+        let pawn_count = if white_leads {
+            [white_pawns, black_pawns]
+        } else {
+            [black_pawns, white_pawns]
+        };
+        let unique_pieces = [white, black]
+            .iter()
+            .any(|side| "QRBNP".chars().any(|c| count(side, c) == 1));
+        let piece_count = white.len() + black.len();
+        let sides = if !dtz && !table.symmetric { 2 } else { 1 };
+        let files = if has_pawns { 4 } else { 1 };
+        let both_pawns = has_pawns && pawn_count[1] > 0;
+        table.unique_pieces = unique_pieces;
+        table.both_pawns = both_pawns;
+
+        let mut pos = 5;
+        for file in 0..files {
+            let order_byte = table.read_u8(pos)?;
+            let pawn_order_byte = if both_pawns {
+                table.read_u8(pos + 1)?
+            } else {
+                0xff
+            };
+            let order = [
+                [order_byte & 0xf, pawn_order_byte & 0xf],
+                [order_byte >> 4, pawn_order_byte >> 4],
+            ];
+            pos += 1 + usize::from(both_pawns);
+            let mut side_sections = Vec::new();
+            for (side, side_order) in order.iter().enumerate().take(sides) {
+                let mut section = PairsData::default();
+                for k in 0..piece_count {
+                    let byte = table.read_u8(pos + k)?;
+                    section
+                        .pieces
+                        .push(if side == 1 { byte >> 4 } else { byte & 0xf });
+                }
+                section.set_groups(*side_order, file, has_pawns, unique_pieces, pawn_count);
+                side_sections.push(section);
+            }
+            pos += piece_count;
+            table.sections.push(side_sections);
+        }
+        pos += pos & 1;
+
+        for file in 0..files {
+            for side in 0..sides {
+                let mut section = std::mem::take(&mut table.sections[file][side]);
+                pos = section.set_sizes(&table, pos)?;
+                table.sections[file][side] = section;
+            }
+        }
+
+        if dtz {
+            for file in 0..files {
+                let flags = table.sections[file][0].flags;
+                if flags & MAPPED == 0 {
+                    continue;
+                }
+                for i in 0..4 {
+                    if flags & WIDE != 0 {
+                        pos += pos & 1;
+                        table.sections[file][0].map_idx[i] = pos + 2;
+                        pos += 2 * table.read_u16(pos)? as usize + 2;
+                    } else {
+                        table.sections[file][0].map_idx[i] = pos + 1;
+                        pos += table.read_u8(pos)? as usize + 1;
+                    }
+                }
+            }
+            pos += pos & 1;
+        }
+
+        for section in table.sections.iter_mut().flatten() {
+            section.sparse_index = pos;
+            pos += section.sparse_index_size as usize * 6;
+        }
+        for section in table.sections.iter_mut().flatten() {
+            section.block_length = pos;
+            pos += section.block_length_size as usize * 2;
+        }
+        for section in table.sections.iter_mut().flatten() {
+            pos = (pos + 0x3f) & !0x3f;
+            section.data = pos;
+            pos += (section.blocks * section.block_size) as usize;
+        }
+        if pos > table.bytes.len() {
+            return None;
+        }
+        Some(table)
+    }
+
+    //value stored for the position, None when a DTZ table only has the other side to move
+    fn probe(&self, board: &Board, flip: bool, wdl: i32) -> Option<Option<i32>> {
+        let encoding = Encoding::get();
+        let black_to_move = board.active_player() == Color::Black;
+        //symmetric tables only store white to move, other tables have white as the first part
+        let flipped = flip || (self.symmetric && black_to_move);
+        let flip_color = if flipped { 8 } else { 0 };
+        let flip_squares = if flipped { 56 } else { 0 };
+        let stm = usize::from(flipped ^ black_to_move);
+
+        let mut board_pieces = Vec::new();
+        for (square, field) in board.fields() {
+            if field.get_type() != FieldType::None {
+                board_pieces.push((
+                    square.index(),
+                    piece_code(field.get_type(), field.get_color()),
+                ));
+            }
+        }
+
+        let mut squares = Vec::new();
+        let mut pieces = Vec::new();
+        let mut lead_pawns = 0;
+        let mut file = 0;
+        if self.has_pawns {
+            let lead = self.sections[0][0].pieces[0] ^ flip_color;
+            for (s, _) in board_pieces.iter().filter(|c| c.1 == lead) {
+                squares.push(s ^ flip_squares);
+                pieces.push(lead ^ flip_color);
+            }
+            lead_pawns = squares.len();
+            let leading = (0..lead_pawns).max_by_key(|i| encoding.map_pawns[squares[*i]])?;
+            squares.swap(0, leading);
+            file = file_of(squares[0]).min(7 - file_of(squares[0]));
+        }
+
+        let section = &self.sections[file][stm % self.sections[file].len()];
+        //symmetric tables without pawns are the same for both sides
+        let one_sided = self.has_pawns || !self.symmetric;
+        if self.dtz && one_sided && usize::from(section.flags & STM) != stm {
+            return Some(None);
+        }
+
+        for (s, code) in &board_pieces {
+            if self.has_pawns && *code == self.sections[0][0].pieces[0] ^ flip_color {
+                continue;
+            }
+            squares.push(s ^ flip_squares);
+            pieces.push(code ^ flip_color);
+        }
+        if squares.len() != section.pieces.len() {
+            return None;
+        }
+
+        //the same order as in the table
+        for i in lead_pawns..squares.len().saturating_sub(1) {
+            if let Some(j) = (i + 1..squares.len()).find(|j| pieces[*j] == section.pieces[i]) {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        //the leading piece goes to the a-d files
+        if file_of(squares[0]) > 3 {
+            squares.iter_mut().for_each(|s| *s ^= 7);
+        }
+
+        let mut idx;
+        if self.has_pawns {
+            idx = encoding.lead_pawn_idx[lead_pawns][squares[0]];
+            squares[1..lead_pawns].sort_by_key(|s| encoding.map_pawns[*s]);
+            for (i, s) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                idx += encoding.binomial[i][encoding.map_pawns[*s] as usize];
+            }
+        } else {
+            //and below the fifth rank
+            if rank_of(squares[0]) > 3 {
+                squares.iter_mut().for_each(|s| *s ^= 56);
+            }
+            //the first piece of the leading group off the diagonal goes below it
+            for i in 0..section.group_len[0] {
+                if off_diagonal(squares[i]) == 0 {
+                    continue;
+                }
+                if off_diagonal(squares[i]) > 0 {
+                    for s in squares[i..].iter_mut() {
+                        *s = ((*s >> 3) | (*s << 3)) & 63;
+                    }
+                }
+                break;
+            }
+            if self.unique_pieces {
+                let adjust1 = u64::from(squares[1] > squares[0]);
+                let adjust2 =
+                    u64::from(squares[2] > squares[0]) + u64::from(squares[2] > squares[1]);
+                let (s0, s1, s2) = (squares[0], squares[1], squares[2]);
+                idx = if off_diagonal(s0) != 0 {
+                    (encoding.map_a1d1d4[s0] * 63 + (s1 as u64 - adjust1)) * 62 + s2 as u64
+                        - adjust2
+                } else if off_diagonal(s1) != 0 {
+                    (6 * 63 + rank_of(s0) as u64 * 28 + encoding.map_b1h1h7[s1]) * 62 + s2 as u64
+                        - adjust2
+                } else if off_diagonal(s2) != 0 {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + rank_of(s0) as u64 * 7 * 28
+                        + (rank_of(s1) as u64 - adjust1) * 28
+                        + encoding.map_b1h1h7[s2]
+                } else {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + 4 * 7 * 28
+                        + rank_of(s0) as u64 * 6 * 7
+                        + (rank_of(s1) as u64 - adjust1) * 6
+                        + (rank_of(s2) as u64 - adjust2)
+                };
+            } else {
+                idx = encoding.map_kk[encoding.map_a1d1d4[squares[0]] as usize][squares[1]];
+            }
+        }
+
+        //the remaining groups in ascending order of their squares
+        idx *= section.group_idx[0];
+        let mut start = section.group_len[0];
+        let mut remaining_pawns = self.both_pawns;
+        for next in 1..section.group_len.len() {
+            let len = section.group_len[next];
+            squares[start..start + len].sort();
+            let mut n = 0;
+            for i in 0..len {
+                let s = squares[start + i];
+                let adjust = squares[..start].iter().filter(|c| s > **c).count();
+                let pawn_shift = if remaining_pawns { 8 } else { 0 };
+                n += encoding.binomial[i + 1][s - adjust - pawn_shift];
+            }
+            remaining_pawns = false;
+            idx += n * section.group_idx[next];
+            start += len;
+        }
+
+        let value = self.decompress(section, idx)?;
+        if !self.dtz {
+            return Some(Some(value - 2));
+        }
+        Some(Some(self.map_dtz(&self.sections[file][0], value, wdl)?))
+    }
+
+    //DTZ values are stored by frequency, the maps give back the distances
+    fn map_dtz(&self, section: &PairsData, mut value: i32, wdl: i32) -> Option<i32> {
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+        let flags = section.flags;
+        if flags & MAPPED != 0 {
+            let map = section.map_idx[WDL_MAP[(wdl + 2) as usize]];
+            value = if flags & WIDE != 0 {
+                self.read_u16(map + 2 * value as usize)? as i32
+            } else {
+                self.read_u8(map + value as usize)? as i32
+            };
+        }
+        //moves are converted to plies
+        if (wdl == 2 && flags & WIN_PLIES == 0)
+            || (wdl == -2 && flags & LOSS_PLIES == 0)
+            || wdl == 1
+            || wdl == -1
+        {
+            value *= 2;
+        }
+        Some(value + 1)
+    }
+
+    //canonical Huffman codes of symbols which expand into pairs of symbols
+    fn decompress(&self, section: &PairsData, idx: u64) -> Option<i32> {
+        if section.flags & SINGLE_VALUE != 0 {
+            return Some(section.min_sym_len as i32);
+        }
+        let k = (idx / section.span) as usize;
+        let mut block = self.read_u32(section.sparse_index + 6 * k)? as usize;
+        let mut offset = self.read_u16(section.sparse_index + 6 * k + 4)? as i64;
+        offset += (idx % section.span) as i64 - (section.span / 2) as i64;
+        let block_length = |block: usize| {
+            self.read_u16(section.block_length + 2 * block)
+                .map(|c| c as i64)
+        };
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        let mut pos = section.data + block * section.block_size as usize;
+        let mut buffer = (self.read_u32_be(pos)? as u64) << 32 | self.read_u32_be(pos + 4)? as u64;
+        pos += 8;
+        let mut buffer_size = 64;
+        let mut sym;
+        loop {
+            let mut len = 0;
+            while buffer < *section.base.get(len)? {
+                len += 1;
+            }
+            let shift = 64 - len - section.min_sym_len as usize;
+            sym = ((buffer - section.base[len]) >> shift) as usize;
+            sym += self.read_u16(section.lowest_sym + 2 * len)? as usize;
+            let symlen = *section.symlen.get(sym)? as i64;
+            if offset < symlen + 1 {
+                break;
+            }
+            offset -= symlen + 1;
+            let len = len + section.min_sym_len as usize;
+            buffer <<= len;
+            buffer_size -= len;
+            if buffer_size <= 32 {
+                buffer_size += 32;
+                buffer |= (self.read_u32_be(pos)? as u64) << (64 - buffer_size);
+                pos += 4;
+            }
+        }
+
+        while section.symlen[sym] != 0 {
+            let (left, right) = self.pair(section, sym)?;
+            if offset < section.symlen[left] as i64 + 1 {
+                sym = left;
+            } else {
+                offset -= section.symlen[left] as i64 + 1;
+                sym = right;
+            }
+        }
+        Some(self.pair(section, sym)?.0 as i32)
+    }
+
+    //the two symbols a symbol expands to, 12 bits each
+    fn pair(&self, section: &PairsData, sym: usize) -> Option<(usize, usize)> {
+        let pos = section.btree + 3 * sym;
+        let (b0, b1, b2) = (
+            self.read_u8(pos)? as usize,
+            self.read_u8(pos + 1)? as usize,
+            self.read_u8(pos + 2)? as usize,
+        );
+        Some((((b1 & 0xf) << 8) | b0, (b2 << 4) | (b1 >> 4)))
+    }
+}
+
+impl PairsData {
+    fn set_groups(
+        &mut self,
+        order: [u8; 2],
+        file: usize,
+        has_pawns: bool,
+        unique_pieces: bool,
+        pawn_count: [usize; 2],
+    ) {
+        let encoding = Encoding::get();
+        let mut first_len: i32 = if has_pawns {
+            0
+        } else if unique_pieces {
+            3
+        } else {
+            2
+        };
+        self.group_len = vec![1];
+        //the leading group holds the first pieces, then equal pieces are grouped
+        for i in 1..self.pieces.len() {
+            first_len -= 1;
+            if first_len > 0 || self.pieces[i] == self.pieces[i - 1] {
+                if let Some(last) = self.group_len.last_mut() {
+                    *last += 1;
+                }
+            } else {
+                self.group_len.push(1);
+            }
+        }
+        let groups = self.group_len.len();
+
+        //the groups are combined in the order given by the table
+        let both_pawns = has_pawns && pawn_count[1] > 0;
+        let mut next = if both_pawns { 2 } else { 1 };
+        let mut free_squares =
+            64 - self.group_len[0] - if both_pawns { self.group_len[1] } else { 0 };
+        let mut idx: u64 = 1;
+        self.group_idx = vec![0; groups + 1];
+        let mut k = 0;
+        while next < groups || k == order[0] as usize || k == order[1] as usize {
+            if k == order[0] as usize {
+                self.group_idx[0] = idx;
+                idx *= if has_pawns {
+                    encoding.lead_pawns_size[self.group_len[0]][file]
+                } else if unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] as usize {
+                self.group_idx[1] = idx;
+                idx *= encoding.binomial[self.group_len[1]][48 - self.group_len[0]];
+            } else {
+                self.group_idx[next] = idx;
+                idx *= encoding.binomial[self.group_len[next]][free_squares];
+                free_squares -= self.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        self.group_idx[groups] = idx;
+    }
+
+    fn set_sizes(&mut self, table: &Table, mut pos: usize) -> Option<usize> {
+        self.flags = table.read_u8(pos)?;
+        pos += 1;
+        if self.flags & SINGLE_VALUE != 0 {
+            //the value of all positions
+            self.min_sym_len = table.read_u8(pos)?;
+            return Some(pos + 1);
+        }
+
+        let size = *self.group_idx.last()?;
+        self.block_size = 1 << table.read_u8(pos)?;
+        self.span = 1 << table.read_u8(pos + 1)?;
+        self.sparse_index_size = size.div_ceil(self.span);
+        let padding = table.read_u8(pos + 2)? as u64;
+        self.blocks = table.read_u32(pos + 3)? as u64;
+        self.block_length_size = self.blocks + padding;
+        let max_sym_len = table.read_u8(pos + 7)?;
+        self.min_sym_len = table.read_u8(pos + 8)?;
+        pos += 9;
+        self.lowest_sym = pos;
+        let lengths = max_sym_len.checked_sub(self.min_sym_len)? as usize + 1;
+        self.base = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            let lowest = table.read_u16(pos + 2 * i)? as u64;
+            let next_lowest = table.read_u16(pos + 2 * (i + 1))? as u64;
+            self.base[i] = (self.base[i + 1] + lowest).wrapping_sub(next_lowest) / 2;
+        }
+        for (i, base) in self.base.iter_mut().enumerate() {
+            let shift = 64 - i - self.min_sym_len as usize;
+            *base = if shift < 64 { *base << shift } else { 0 };
+        }
+        pos += lengths * 2;
+
+        let symbols = table.read_u16(pos)? as usize;
+        pos += 2;
+        self.btree = pos;
+        self.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for sym in 0..symbols {
+            if !visited[sym] {
+                self.set_symlen(table, sym, &mut visited)?;
+            }
+        }
+        Some(pos + symbols * 3 + (symbols & 1))
+    }
+
+    //number of values a symbol expands to, minus one
+    fn set_symlen(&mut self, table: &Table, sym: usize, visited: &mut [bool]) -> Option<()> {
+        visited[sym] = true;
+        let (left, right) = table.pair(self, sym)?;
+        if right == 0xfff {
+            self.symlen[sym] = 0;
+            return Some(());
+        }
+        for child in [left, right] {
+            if !*visited.get(child)? {
+                self.set_symlen(table, child, visited)?;
+            }
+        }
+        self.symlen[sym] = self.symlen[left] + self.symlen[right] + 1;
+        Some(())
+    }
+}
+
+pub struct Tablebase {
+    directory: String,
+    //names of the available tables like "KRvK"
+    wdl_names: HashSet<String>,
+    dtz_names: HashSet<String>,
+    wdl: HashMap<String, Table>,
+    dtz: HashMap<String, Table>,
+    max_pieces: usize,
+}
+
+impl Tablebase {
+    pub fn open(directory: &str) -> Result<Tablebase, ChessError> {
+        let mut tablebase = Tablebase {
+            directory: directory.to_string(),
+            wdl_names: HashSet::new(),
+            dtz_names: HashSet::new(),
+            wdl: HashMap::new(),
+            dtz: HashMap::new(),
+            max_pieces: 0,
+        };
+        let entries = fs::read_dir(directory).map_err(|e| ChessError::FileError(e.to_string()))?;
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let (name, extension) = match file_name.rsplit_once('.') {
+                Some(c) => c,
+                None => continue,
+            };
+            if !name.contains('v') || !name.chars().all(|c| "KQRBNPv".contains(c)) {
+                continue;
+            }
+            match extension {
+                "rtbw" => {
+                    tablebase.max_pieces = tablebase.max_pieces.max(name.len() - 1);
+                    tablebase.wdl_names.insert(name.to_string());
+                }
+                "rtbz" => {
+                    tablebase.dtz_names.insert(name.to_string());
+                }
+                _ => {}
+            }
+        }
+        Ok(tablebase)
+    }
+
+    pub fn directory(&self) -> &str {
+        &self.directory
+    }
+
+    pub fn table_count(&self) -> usize {
+        self.wdl_names.len()
+    }
+
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    //tables have no castling, the fifty move rule is left to the DTZ values
+    pub fn can_probe(&self, board: &Board) -> bool {
+        !board.has_castling_rights() && Tablebase::piece_count(board) <= self.max_pieces
+    }
+
+    fn piece_count(board: &Board) -> usize {
+        board
+            .fields()
+            .filter(|(_, field)| field.get_type() != FieldType::None)
+            .count()
+    }
+
+    fn material(board: &Board, color: Color) -> String {
+        let mut material = String::new();
+        for figure_type in PIECE_ORDER {
+            for (_, field) in board.fields() {
+                if field.get_type() == figure_type && field.get_color() == color {
+                    material.push(figure_type.letter());
+                }
+            }
+        }
+        material
+    }
+
+    //the table of the position, true when the colors are swapped in the table
+    fn table(&mut self, board: &Board, dtz: bool) -> Result<(&Table, bool), ChessError> {
+        let white = Tablebase::material(board, Color::White);
+        let black = Tablebase::material(board, Color::Black);
+        let names = if dtz {
+            &self.dtz_names
+        } else {
+            &self.wdl_names
+        };
+        let direct = format!("{}v{}", white, black);
+        let swapped = format!("{}v{}", black, white);
+        let (name, flip) = if names.contains(&direct) {
+            (direct, false)
+        } else if names.contains(&swapped) {
+            (swapped, true)
+        } else {
+            return Err(ChessError::FileError(format!(
+                "no tablebase for {}",
+                direct
+            )));
+        };
+        let tables = if dtz { &mut self.dtz } else { &mut self.wdl };
+        if !tables.contains_key(&name) {
+            let extension = if dtz { "rtbz" } else { "rtbw" };
+            let path = Path::new(&self.directory).join(format!("{}.{}", name, extension));
+            let bytes = fs::read(&path).map_err(|e| ChessError::FileError(e.to_string()))?;
+            let table = Table::new(bytes, &name, dtz)
+                .ok_or_else(|| ChessError::FileError(format!("corrupted table {}", name)))?;
+            tables.insert(name.clone(), table);
+        }
+        Ok((&tables[&name], flip))
+    }
+
+    fn probe_wdl_table(&mut self, board: &Board) -> Result<i32, ChessError> {
+        if Tablebase::piece_count(board) == 2 {
+            return Ok(0);
+        }
+        let (table, flip) = self.table(board, false)?;
+        table
+            .probe(board, flip, 0)
+            .flatten()
+            .ok_or_else(|| ChessError::FileError(String::from("corrupted WDL table")))
+    }
+
+    fn is_zeroing(board: &Board, next_move: &Move) -> bool {
+        board.get(next_move.from).get_type() == FieldType::Pawn
+            || board.get(next_move.to).get_type() != FieldType::None
+    }
+
+    //the tables don't know en passant and only store a "don't care" value when a capture wins,
+    //so captures (and pawn moves for DTZ) are searched first, true when such a move is the best
+    fn search(&mut self, board: &Board, zeroing_moves: bool) -> Result<(i32, bool), ChessError> {
+        let moves = board.legal_moves();
+        if moves.is_empty() {
+            let mated = board.is_in_check(board.active_player());
+            return Ok((if mated { -2 } else { 0 }, false));
+        }
+        let mut best = -2;
+        let mut searched = 0;
+        for next_move in &moves {
+            let pawn_move = board.get(next_move.from).get_type() == FieldType::Pawn;
+            if !next_move.flags.capture && (!zeroing_moves || !pawn_move) {
+                continue;
+            }
+            searched += 1;
+            let mut next = *board;
+            next.make_move(*next_move);
+            let value = -self.search(&next, false)?.0;
+            if value > best {
+                best = value;
+                if value >= 2 {
+                    return Ok((value, true));
+                }
+            }
+        }
+        let all_searched = searched > 0 && searched == moves.len();
+        let value = if all_searched {
+            best
+        } else {
+            self.probe_wdl_table(board)?
+        };
+        if best >= value {
+            return Ok((best, best > 0 || all_searched));
+        }
+        Ok((value, false))
+    }
+
+    pub fn probe_wdl(&mut self, board: &Board) -> Result<Wdl, ChessError> {
+        if board.has_castling_rights() {
+            return Err(ChessError::FileError(String::from(
+                "tablebases have no positions with castling rights",
+            )));
+        }
+        Ok(Wdl::from_value(self.search(board, false)?.0))
+    }
+
+    fn dtz_before_zeroing(wdl: i32) -> i32 {
+        match wdl {
+            2 => 1,
+            1 => 101,
+            -1 => -101,
+            -2 => -1,
+            _ => 0,
+        }
+    }
+
+    //plies to the next capture or pawn move with best play, positive when the side to move wins,
+    //values beyond 100 only win or lose when the fifty move rule is ignored
+    pub fn probe_dtz(&mut self, board: &Board) -> Result<i32, ChessError> {
+        if board.has_castling_rights() {
+            return Err(ChessError::FileError(String::from(
+                "tablebases have no positions with castling rights",
+            )));
+        }
+        let (wdl, zeroing) = self.search(board, true)?;
+        if wdl == 0 {
+            return Ok(0);
+        }
+        if zeroing {
+            return Ok(Tablebase::dtz_before_zeroing(wdl));
+        }
+        let stored = {
+            let (table, flip) = self.table(board, true)?;
+            table
+                .probe(board, flip, wdl)
+                .ok_or_else(|| ChessError::FileError(String::from("corrupted DTZ table")))?
+        };
+        if let Some(dtz) = stored {
+            let cursed = if wdl.abs() == 1 { 100 } else { 0 };
+            return Ok((dtz + cursed) * wdl.signum());
+        }
+
+        //the table stores the other side to move, so the moves are looked up one by one
+        let mut min_dtz = i32::MAX;
+        for next_move in board.legal_moves() {
+            let zeroing = Tablebase::is_zeroing(board, &next_move);
+            let mut next = *board;
+            next.make_move(next_move);
+            let mut dtz = if zeroing {
+                -Tablebase::dtz_before_zeroing(self.search(&next, false)?.0)
+            } else {
+                -self.probe_dtz(&next)?
+            };
+            if dtz == 1 && next.is_in_check(next.active_player()) && next.legal_moves().is_empty() {
+                min_dtz = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < min_dtz && dtz.signum() == wdl.signum() {
+                min_dtz = dtz;
+            }
+        }
+        Ok(if min_dtz == i32::MAX { -1 } else { min_dtz })
+    }
+
+    //all moves with their DTZ from the view of the side to move, the best first:
+    //fast wins within the fifty move rule, then draws, then the slowest losses
+    pub fn root_moves(&mut self, board: &Board) -> Result<Vec<(Move, i32)>, ChessError> {
+        let mut moves = Vec::new();
+        for next_move in board.legal_moves() {
+            let mut next = *board;
+            next.make_move(next_move);
+            let mut dtz = if next.halfmove_clock() == 0 {
+                let wdl = -self.search(&next, false)?.0;
+                Tablebase::dtz_before_zeroing(wdl)
+            } else {
+                let dtz = -self.probe_dtz(&next)?;
+                dtz + dtz.signum()
+            };
+            if dtz == 2 && next.is_in_check(next.active_player()) && next.legal_moves().is_empty() {
+                dtz = 1;
+            }
+            moves.push((next_move, dtz));
+        }
+        let clock = board.halfmove_clock();
+        moves.sort_by_key(|(_, dtz)| {
+            let category = match *dtz {
+                d if d > 0 && d + clock <= 100 => 4,
+                d if d > 0 => 3,
+                0 => 2,
+                d if -d + clock > 100 => 1,
+                _ => 0,
+            };
+            //shorter wins and longer losses
+            (Reverse(category), if *dtz > 0 { *dtz } else { -dtz.abs() })
+        });
+        Ok(moves)
+    }
+}
@@ -1,6 +1,56 @@
 #[cfg(test)]
 mod tests {
-    use crate::{field::ChessError, field::FieldType, Game};
+    use crate::analysis::{GameAnalysis, Judgment};
+    use crate::animation::{self, lzw, AnimationOptions};
+    use crate::board::{Board, START_FEN};
+    use crate::book::OpeningBook;
+    use crate::diagram::{png, raster, svg, DiagramOptions};
+    use crate::editor::{EditResult, PositionEditor};
+    use crate::engine::{self, EngineConfig, MATE_SCORE};
+    use crate::evaluation::{Evaluation, Material};
+    use crate::field::{Color, Outcome};
+    use crate::handicap::Handicap;
+    use crate::match_runner::{play_game, MatchStats, Opening, Sprt};
+    use crate::pgn::{from_san, parse_pgn, parse_pgn_lines, to_san};
+    use crate::player::Player;
+    use crate::puzzle::{parse_puzzles, Attempt, PuzzleSession, PuzzleTrainer};
+    use crate::render::{render, Orientation, RenderOptions};
+    use crate::repertoire::{DrillAnswer, Repertoire, RepertoireDrill, Schedule};
+    use crate::square::Square;
+    use crate::tablebase::{Tablebase, Wdl};
+    use crate::tournament::{Tournament, TournamentFormat};
+    use crate::transposition::{Bound, TranspositionTable};
+    use crate::tui::{parse_keys, Key, View};
+    use crate::uci::{Score, SearchLimit, UciEngine, UciInfo};
+    use crate::variant::Variant;
+    use crate::{field::ChessError, field::FieldType, field::Move, Game};
+
+    fn sq(name: &str) -> Square {
+        Square::parse(name).unwrap()
+    }
+
+    //starts a minimal UCI engine script which always answers with the given move,
+    //without a move it never answers go, the script is gone once the engine runs
+    #[cfg(unix)]
+    fn stub_engine(name: &str, best_move: Option<&str>) -> UciEngine {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!("chess_stub_{}_{}", name, std::process::id()));
+        let answer = best_move.map_or(String::new(), |c| {
+            format!(
+                "echo \"info depth 1 score cp 20 nodes 5 pv {0}\"; echo \"bestmove {0}\"",
+                c
+            )
+        });
+        let script = format!(
+            "#!/bin/sh\nwhile read line; do\n  case \"$line\" in\n    uci) echo \"id name Stub\"; echo uciok ;;\n    isready) echo readyok ;;\n    go*) {} ;;\n    quit) exit 0 ;;\n  esac\ndone\n",
+            answer
+        );
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let engine = UciEngine::new(&path.to_string_lossy());
+        std::fs::remove_file(&path).unwrap();
+        engine.unwrap()
+    }
 
     #[test]
     fn test_move_out_of_board() {
@@ -22,7 +72,8 @@ mod tests {
         assert_eq!(
             Game::test_helper("e2 e4\ne7 e5\ne1 e2\nd8 f6\ne2 f3".to_string()),
             Err(ChessError::CantMoveFromToAsKingWillBeUnderAttack(
-                2, 5, 3, 6
+                sq("e2"),
+                sq("f3")
             ))
         );
     }
@@ -31,7 +82,8 @@ mod tests {
         assert_eq!(
             Game::test_helper("e2 e4\ne7 e5\ne1 e2\nd8 f6\ne2 e3\nf6 f3\ne3 d4".to_string()),
             Err(ChessError::CantMoveFromToAsKingWillBeUnderAttack(
-                3, 5, 4, 4
+                sq("e3"),
+                sq("d4")
             ))
         );
     }
@@ -40,7 +92,8 @@ mod tests {
         assert_eq!(
             Game::test_helper("e2 e4\ne7 e5\nb1 c3\nd8 g5\nb2 b3\ng5 g3\nf2 f3".to_string()),
             Err(ChessError::CantMoveFromToAsKingWillBeUnderAttack(
-                2, 6, 3, 6
+                sq("f2"),
+                sq("f3")
             ))
         );
     }
@@ -48,27 +101,37 @@ mod tests {
     fn test_king_move_blocked_field() {
         assert_eq!(
             Game::test_helper("e2 e4\ne7 e5\ne1 e2\nb8 c6\ne2 e3\ng8 f6\ne3 e4".to_string()),
-            Err(ChessError::FieldAlreadyOwned(4, 5))
+            Err(ChessError::FieldAlreadyOwned(sq("e4")))
         );
     }
     #[test]
     fn test_king_move_small_castle_blocked() {
         assert_eq!(
             Game::test_helper("o-o".to_string()),
-            Err(ChessError::CastlingBlockedByFigure(1, 6))
+            Err(ChessError::CastlingBlockedByFigure(sq("f1")))
         );
     }
     #[test]
     fn test_king_move_large_castle_blocked() {
         assert_eq!(
             Game::test_helper("o-o-o".to_string()),
-            Err(ChessError::CastlingBlockedByFigure(1, 2))
+            Err(ChessError::CastlingBlockedByFigure(sq("b1")))
+        );
+    }
+    #[test]
+    fn test_king_move_small_castle_check_on_king() {
+        assert_eq!(
+            Game::test_helper("e2 e4\ne7 e5\nf1 c4\nd8 h4\ng1 f3\nh4 e4\no-o".to_string()),
+            Err(ChessError::CastlingNotPossibleWhenKingUnderAttack(sq("e1")))
+        );
+    }
+    #[test]
+    fn test_king_move_small_castle() {
+        assert_eq!(
+            Game::test_helper("e2 e4\ne7 e5\ng1 f3\nb8 c6\nf1 c4\ng8 f6\no-o".to_string()),
+            Ok(())
         );
     }
-    // #[test]
-    // fn test_king_move_small_castle_check_on_king() { //TODO: failing with CastlingNoPossibleAlreadyMoved, don't want to fix it now (no impact on the game, just wrong error msg)
-    //     assert_eq!(Game::test_helper("e2 e4\ne7 e5\nf1 c4\nd8 h4\ng1 f3\nh4 e4\no-o".to_string()), Err(ChessError::CastlingNotPossibleWhenKingUnderAttack(1, 5)));
-    // }
     #[test]
     fn test_tower_move_invalid() {
         assert_eq!(
@@ -80,14 +143,14 @@ mod tests {
     fn test_tower_move_blocked_by_owned() {
         assert_eq!(
             Game::test_helper("a1 a8".to_string()),
-            Err(ChessError::FieldAlreadyOwned(2, 1))
+            Err(ChessError::FieldAlreadyOwned(sq("a2")))
         );
     }
     #[test]
     fn test_tower_move_blocked_by_enemy() {
         assert_eq!(
             Game::test_helper("a2 a4\nb7 b5\na4 b5\ne7 e5\na1 a8".to_string()),
-            Err(ChessError::MoveBlockedByEnemyFigure(7, 1))
+            Err(ChessError::MoveBlockedByEnemyFigure(sq("a7")))
         );
     }
     #[test]
@@ -108,14 +171,14 @@ mod tests {
     fn test_bishop_move_blocked_by_owned() {
         assert_eq!(
             Game::test_helper("c1 a3".to_string()),
-            Err(ChessError::FieldAlreadyOwned(2, 2))
+            Err(ChessError::FieldAlreadyOwned(sq("b2")))
         );
     }
     #[test]
     fn test_bishop_move_blocked_by_enemy() {
         assert_eq!(
             Game::test_helper("b2 b3\nb7 b5\nc1 a3\nb5 b4\na3 d6".to_string()),
-            Err(ChessError::MoveBlockedByEnemyFigure(4, 2))
+            Err(ChessError::MoveBlockedByEnemyFigure(sq("b4")))
         );
     }
     #[test]
@@ -129,14 +192,14 @@ mod tests {
     fn test_queen_move_like_tower_blocked_by_owned() {
         assert_eq!(
             Game::test_helper("d1 d8".to_string()),
-            Err(ChessError::FieldAlreadyOwned(2, 4))
+            Err(ChessError::FieldAlreadyOwned(sq("d2")))
         );
     }
     #[test]
     fn test_queen_move_like_bishop_blocked_by_owned() {
         assert_eq!(
             Game::test_helper("d1 a4".to_string()),
-            Err(ChessError::FieldAlreadyOwned(2, 3))
+            Err(ChessError::FieldAlreadyOwned(sq("c2")))
         );
     }
 
@@ -144,14 +207,14 @@ mod tests {
     fn test_queen_move_like_tower_blocked_by_enemy() {
         assert_eq!(
             Game::test_helper("d2 d4\nc7 c5\nd4 c5\nd7 d5\nd1 d8".to_string()),
-            Err(ChessError::MoveBlockedByEnemyFigure(5, 4))
+            Err(ChessError::MoveBlockedByEnemyFigure(sq("d5")))
         );
     }
     #[test]
     fn test_queen_move_like_bishop_blocked_by_enemy() {
         assert_eq!(
             Game::test_helper("e2 e4\ng7 g5\nb1 c3\ng5 g4\nd1 h5".to_string()),
-            Err(ChessError::MoveBlockedByEnemyFigure(4, 7))
+            Err(ChessError::MoveBlockedByEnemyFigure(sq("g4")))
         );
     }
     #[test]
@@ -182,4 +245,1450 @@ mod tests {
             Ok(())
         );
     }
+    #[test]
+    fn test_move_uci_notation() {
+        let m = Move::from_uci("e7e8q").unwrap();
+        assert_eq!((m.from, m.to), (sq("e7"), sq("e8")));
+        assert_eq!(m.promotion, Some(FieldType::Queen));
+        assert_eq!(m.to_string(), "e7e8q");
+        assert_eq!(
+            Move::from_uci("e9e4"),
+            Err(ChessError::InvalidMoveSyntax("e9e4".to_string()))
+        );
+    }
+    #[test]
+    fn test_uci_info_parse() {
+        let info = UciInfo::parse("info depth 12 seldepth 18 multipv 1 score mate -3 nodes 4096 nps 1000 pv e2e4 e7e5 g1f3");
+        assert_eq!(info.depth, 12);
+        assert_eq!(info.score, Some(Score::Mate(-3)));
+        assert_eq!(info.nodes, 4096);
+        assert_eq!(info.pv.len(), 3);
+        assert_eq!(info.pv[2], Move::from_uci("g1f3").unwrap());
+    }
+    #[cfg(unix)]
+    #[test]
+    fn test_uci_engine_plays_black() {
+        let engine = stub_engine("reply", Some("e7e5"));
+        assert_eq!(engine.name(), "Stub");
+        assert_eq!(
+            Game::test_engine_helper("e2 e4".to_string(), engine),
+            Ok(vec![
+                Move::from_uci("e2e4").unwrap(),
+                Move::from_uci("e7e5").unwrap()
+            ])
+        );
+    }
+    #[cfg(unix)]
+    #[test]
+    fn test_uci_engine_illegal_move() {
+        let engine = stub_engine("illegal", Some("e8e6"));
+        assert_eq!(
+            Game::test_engine_helper("e2 e4".to_string(), engine),
+            Err(ChessError::InvalidFigureMove(FieldType::King))
+        );
+    }
+    #[cfg(unix)]
+    #[test]
+    fn test_uci_engine_timeout() {
+        //an engine which never answers is given up instead of hanging the game
+        let mut engine = stub_engine("silent", None);
+        engine.set_timeout(std::time::Duration::from_millis(200));
+        engine.set_limit(SearchLimit::MoveTime(50));
+        assert_eq!(
+            engine.go(START_FEN, &[]),
+            Err(ChessError::EngineError(String::from(
+                "Stub didn't answer in time"
+            )))
+        );
+        assert!(matches!(
+            UciEngine::new("/nonexistent/engine"),
+            Err(ChessError::EngineError(_))
+        ));
+        //files which can't be read are no engine errors
+        let error: ChessError = std::io::Error::from(std::io::ErrorKind::NotFound).into();
+        assert!(matches!(error, ChessError::FileError(_)));
+    }
+
+    fn perft(board: &Board, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        board
+            .legal_moves()
+            .into_iter()
+            .map(|m| {
+                let mut next = *board;
+                next.make_move(m);
+                perft(&next, depth - 1)
+            })
+            .sum()
+    }
+    #[test]
+    fn test_perft_start_position() {
+        let mut board = Board::new();
+        board.set_board_start();
+        assert_eq!(perft(&board, 3), 8902);
+    }
+    #[test]
+    fn test_perft_castling_en_passant_promotion() {
+        let mut board = Board::new();
+        board
+            .set_board_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+        assert_eq!(perft(&board, 2), 2039);
+        board
+            .set_board_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8")
+            .unwrap();
+        assert_eq!(perft(&board, 2), 1486);
+    }
+    #[test]
+    fn test_perft_pins_and_checks() {
+        let mut board = Board::new();
+        board
+            .set_board_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")
+            .unwrap();
+        assert_eq!(perft(&board, 3), 2812);
+        board
+            .set_board_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")
+            .unwrap();
+        assert_eq!(perft(&board, 3), 9467);
+
+        //taking en passant would leave both pawns off the rank of the king and the tower
+        board
+            .set_board_fen("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1")
+            .unwrap();
+        assert!(!board.legal_moves().iter().any(|m| m.flags.en_passant));
+
+        //in double check only the king moves, not back along the line of the tower
+        board
+            .set_board_fen("4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1")
+            .unwrap();
+        assert_eq!(board.checkers(), vec![sq("f3"), sq("a1")]);
+        let targets: Vec<String> = board.legal_moves().iter().map(|m| m.to_string()).collect();
+        assert_eq!(targets, ["e1e2", "e1f2"]);
+
+        //a pinned knight can't move, a pinned bishop only moves along the line
+        board
+            .set_board_fen("4k3/4r3/8/q3N3/8/2B5/8/4K3 w - - 0 1")
+            .unwrap();
+        assert!(board.checkers().is_empty());
+        assert_eq!(board.pins().len(), 2);
+        assert!(!board.legal_moves().iter().any(|m| m.from == sq("e5")));
+        board
+            .set_board_fen("4k3/8/8/8/8/2b5/3B4/4K3 w - - 0 1")
+            .unwrap();
+        let bishop: Vec<String> = board
+            .legal_moves()
+            .iter()
+            .filter(|m| m.from == sq("d2"))
+            .map(|m| m.to_string())
+            .collect();
+        assert_eq!(bishop, ["d2c3"]);
+    }
+    #[test]
+    fn test_pawn_move_backwards() {
+        assert_eq!(
+            Game::test_helper("e2 e4\ne7 e5\ne4 e3".to_string()),
+            Err(ChessError::InvalidFigureMove(FieldType::Pawn))
+        );
+    }
+    #[test]
+    fn test_fen_roundtrip() {
+        let mut board = Board::new();
+        board.set_board_fen(START_FEN).unwrap();
+        let mut start = Board::new();
+        start.set_board_start();
+        assert_eq!(board, start);
+        board.make_move(Move::from_uci("e2e4").unwrap());
+        assert_eq!(
+            board.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        assert!(matches!(
+            board.set_board_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN w KQkq - 0 1"),
+            Err(ChessError::InvalidFen(_))
+        ));
+    }
+    #[test]
+    fn test_san_notation() {
+        let mut board = Board::new();
+        board
+            .set_board_fen("r3k2r/8/8/8/8/8/3P4/R3K1NR w KQkq - 0 1")
+            .unwrap();
+        assert_eq!(to_san(&board, Move::from_uci("e1c1").unwrap()), "O-O-O");
+        assert_eq!(to_san(&board, Move::from_uci("a1a8").unwrap()), "Rxa8+");
+        assert_eq!(to_san(&board, Move::from_uci("g1f3").unwrap()), "Nf3");
+        assert_eq!(from_san(&board, "0-0-0"), Move::from_uci("e1c1"));
+        board
+            .set_board_fen("1k6/8/8/8/8/8/3K4/R6R w - - 0 1")
+            .unwrap();
+        assert_eq!(to_san(&board, Move::from_uci("a1d1").unwrap()), "Rad1");
+        assert_eq!(from_san(&board, "Rhd1"), Move::from_uci("h1d1"));
+    }
+    #[test]
+    fn test_pgn_roundtrip() {
+        let text = "[Event \"Test\"]\n[Result \"1-0\"]\n\n1. e4 {best by test} e5 2.Nf3 (2. f4 exf4) Nc6 3. Bb5 $1 a6 1-0\n";
+        let games = parse_pgn(text).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].tag("Event"), Some("Test"));
+        assert_eq!(games[0].moves.len(), 6);
+        assert_eq!(
+            games[0].to_pgn().unwrap(),
+            "[Event \"Test\"]\n[Result \"1-0\"]\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 1-0\n\n"
+        );
+    }
+    #[test]
+    fn test_outcome() {
+        let mut board = Board::new();
+        board
+            .set_board_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")
+            .unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::Stalemate));
+        board
+            .set_board_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1")
+            .unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::Checkmate(Color::White)));
+        board
+            .set_board_fen("7k/8/6K1/8/8/2B5/8/8 b - - 0 1")
+            .unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::InsufficientMaterial));
+        board
+            .set_board_fen("7k/8/6K1/8/8/2R5/8/8 b - - 100 80")
+            .unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::FiftyMoveRule));
+    }
+    #[test]
+    fn test_match_game_builtin_mates() {
+        let opening = Opening {
+            fen: "7k/8/6K1/8/8/8/8/R7 w - - 0 1".to_string(),
+            moves: Vec::new(),
+        };
+        let (game, outcome) = play_game(
+            &mut Player::from_spec("builtin", None).unwrap(),
+            &mut Player::from_spec("builtin", None).unwrap(),
+            &opening,
+            10,
+            None,
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Checkmate(Color::White));
+        assert_eq!(game.moves, vec![Move::from_uci("a1a8").unwrap()]);
+        assert_eq!(game.tag("Result"), Some("1-0"));
+    }
+    #[test]
+    fn test_builtin_player_limit() {
+        let player = Player::from_spec("builtin", Some(SearchLimit::Depth(6))).unwrap();
+        assert_eq!(player.name(), "Builtin depth 6");
+        //an explicit depth in the spec wins
+        let player = Player::from_spec("builtin:2", Some(SearchLimit::Depth(6))).unwrap();
+        assert_eq!(player.name(), "Builtin depth 2");
+        for limit in [SearchLimit::Nodes(1000), SearchLimit::MoveTime(100)] {
+            assert!(matches!(
+                Player::from_spec("builtin", Some(limit)),
+                Err(ChessError::EngineError(_))
+            ));
+        }
+    }
+    #[test]
+    fn test_match_stats() {
+        let stats = MatchStats {
+            wins: 30,
+            draws: 40,
+            losses: 30,
+        };
+        assert_eq!(stats.score(), 0.5);
+        let (elo, margin) = stats.elo().unwrap();
+        assert!(elo.abs() < 1e-9);
+        assert!(margin > 40.0 && margin < 60.0);
+        let stats = MatchStats {
+            wins: 120,
+            draws: 60,
+            losses: 20,
+        };
+        let sprt = Sprt::new(0.0, 10.0);
+        assert!(stats.llr(&sprt) > sprt.bounds().1);
+        //a match without a single loss stops as well
+        let mut stats = MatchStats {
+            wins: 20,
+            draws: 5,
+            losses: 0,
+        };
+        assert_eq!(sprt.decision(&stats), Some(true));
+        stats.wins = 5;
+        stats.draws = 20;
+        assert_eq!(sprt.decision(&stats), None);
+        assert_eq!(sprt.decision(&MatchStats::default()), None);
+        //all wins or all losses cross a bound
+        let stats = MatchStats {
+            wins: 10,
+            draws: 0,
+            losses: 0,
+        };
+        assert!(stats.llr(&sprt) > sprt.bounds().1);
+        assert_eq!(sprt.decision(&stats), Some(true));
+        let stats = MatchStats {
+            wins: 0,
+            draws: 0,
+            losses: 10,
+        };
+        assert!(stats.llr(&sprt) < sprt.bounds().0);
+        assert_eq!(sprt.decision(&stats), Some(false));
+    }
+    #[test]
+    fn test_round_robin_pairings() {
+        let mut tournament = Tournament::new("Test");
+        for name in ["A", "B", "C", "D", "E"] {
+            tournament.add_player(name, "human").unwrap();
+        }
+        let mut encounters = std::collections::HashSet::new();
+        for _ in 0..tournament.total_rounds() {
+            let round = tournament.pair_next_round().unwrap().to_vec();
+            for (board, pairing) in round.iter().enumerate() {
+                match pairing.black {
+                    Some(black) => {
+                        let pair = (pairing.white.min(black), pairing.white.max(black));
+                        assert!(encounters.insert(pair));
+                        tournament.record_result(board + 1, 0.5).unwrap();
+                    }
+                    None => assert_eq!(pairing.result, Some(0.0)),
+                }
+            }
+        }
+        assert_eq!(encounters.len(), 10);
+        assert!(tournament.pair_next_round().is_err());
+        for player in 0..5 {
+            assert_eq!(tournament.points(player), 2.0);
+        }
+    }
+    #[test]
+    fn test_swiss_pairings() {
+        let mut tournament = Tournament::new("Test");
+        for name in ["A", "B", "C", "D", "E"] {
+            tournament.add_player(name, "human").unwrap();
+        }
+        tournament.set_format(TournamentFormat::Swiss(4)).unwrap();
+        let mut encounters = std::collections::HashSet::new();
+        let mut byes = Vec::new();
+        for _ in 0..4 {
+            let round = tournament.pair_next_round().unwrap().to_vec();
+            assert_eq!(round.len(), 3);
+            for (board, pairing) in round.iter().enumerate() {
+                match pairing.black {
+                    Some(black) => {
+                        let pair = (pairing.white.min(black), pairing.white.max(black));
+                        assert!(encounters.insert(pair));
+                        //the lower registered player always wins
+                        let white_points = if pairing.white < black { 1.0 } else { 0.0 };
+                        tournament.record_result(board + 1, white_points).unwrap();
+                    }
+                    None => {
+                        assert_eq!(pairing.result, Some(1.0));
+                        assert!(!byes.contains(&pairing.white));
+                        byes.push(pairing.white);
+                    }
+                }
+            }
+        }
+        assert_eq!(encounters.len(), 8);
+        assert!(tournament.pair_next_round().is_err());
+        assert!(tournament.set_format(TournamentFormat::RoundRobin).is_err());
+    }
+    #[test]
+    fn test_tiebreaks() {
+        let mut tournament = Tournament::new("Test");
+        for name in ["A", "B", "C", "D"] {
+            tournament.add_player(name, "human").unwrap();
+        }
+        //A beats everyone, B and C draw, B beats D and C loses to D
+        let results = [
+            (0, 1, 1.0),
+            (0, 2, 1.0),
+            (0, 3, 1.0),
+            (1, 2, 0.5),
+            (1, 3, 1.0),
+            (2, 3, 0.0),
+        ];
+        for _ in 0..3 {
+            let round = tournament.pair_next_round().unwrap().to_vec();
+            for (board, pairing) in round.iter().enumerate() {
+                let black = pairing.black.unwrap();
+                let (low, high) = (pairing.white.min(black), pairing.white.max(black));
+                let result = results
+                    .iter()
+                    .find(|c| (c.0, c.1) == (low, high))
+                    .unwrap()
+                    .2;
+                let white_points = if pairing.white == low {
+                    result
+                } else {
+                    1.0 - result
+                };
+                tournament.record_result(board + 1, white_points).unwrap();
+            }
+        }
+        let standings = tournament.standings();
+        let order: Vec<usize> = standings.iter().map(|c| c.player).collect();
+        assert_eq!(order, vec![0, 1, 3, 2]);
+        //B: opponents A 3, C 0.5, D 1
+        assert_eq!(standings[1].points, 1.5);
+        assert_eq!(standings[1].buchholz, 4.5);
+        assert_eq!(standings[1].sonneborn_berger, 0.5 * 0.5 + 1.0);
+        //D and C have one point each, D is ahead by Buchholz
+        assert_eq!(standings[2].points, 1.0);
+        assert_eq!(standings[2].buchholz, 3.0 + 1.5 + 0.5);
+        assert!(tournament.crosstable().contains("X"));
+    }
+    #[test]
+    fn test_polyglot_keys() {
+        //reference keys of the Polyglot book format
+        let positions = [
+            (START_FEN, 0x463b96181691fc9c),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                0x823c9b50fd114196,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                0x0756b94461c50fb0,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+                0x662fafb965db29d4,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                0x22a48b5a8e47ff78,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3",
+                0x652a607ca3f242c1,
+            ),
+            (
+                "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4",
+                0x00fdd303c946bdd9,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+                0x3c8123ea7b067637,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4",
+                0x5c3f9b829b279560,
+            ),
+        ];
+        for (fen, key) in positions {
+            let mut board = Board::new();
+            board.set_board_fen(fen).unwrap();
+            assert_eq!(board.zobrist_key(), key, "{}", fen);
+        }
+        //the same key after playing the moves
+        let mut board = Board::new();
+        board.set_board_fen(START_FEN).unwrap();
+        let start = board;
+        let mut undos = Vec::new();
+        let lines = [
+            &["e2e4", "d7d5", "e4e5", "f7f5", "e1e2", "e8f7"][..],
+            &["a2a4", "b7b5", "h2h4", "b5b4", "c2c4", "b4c3", "a1a3"],
+        ];
+        for line in lines {
+            board = start;
+            for uci in line {
+                undos.push(board.make_move(Move::from_uci(uci).unwrap()));
+                let mut from_fen = Board::new();
+                from_fen.set_board_fen(&board.fen()).unwrap();
+                assert_eq!(board.zobrist_key(), from_fen.zobrist_key(), "{}", uci);
+            }
+            //taking the moves back restores the key
+            while let Some(undo) = undos.pop() {
+                board.unmake_move(undo);
+            }
+            assert_eq!(board.zobrist_key(), 0x463b96181691fc9c);
+        }
+        //the setters keep the key as well
+        board.set_figure(sq("e4"), FieldType::Pawn, Color::White);
+        board.remove_figure(sq("e2"));
+        board.set_en_passant(sq("e3"));
+        board.set_active_player(Color::Black);
+        assert_eq!(board.zobrist_key(), 0x823c9b50fd114196);
+    }
+    #[test]
+    fn test_opening_book() {
+        //(from row, from file, to row, to file) counted from 0
+        let raw = |from: (u16, u16), to: (u16, u16)| (from.0 * 8 + from.1) << 6 | (to.0 * 8 + to.1);
+        let mut castling = Board::new();
+        castling
+            .set_board_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")
+            .unwrap();
+        let mut start = Board::new();
+        start.set_board_fen(START_FEN).unwrap();
+        let entries = [
+            (start.zobrist_key(), raw((1, 4), (3, 4)), 3u16),
+            (start.zobrist_key(), raw((1, 3), (3, 3)), 1),
+            //an illegal move is skipped
+            (start.zobrist_key(), raw((1, 3), (4, 3)), 10),
+            (castling.zobrist_key(), raw((0, 4), (0, 7)), 1),
+        ];
+        let mut bytes = Vec::new();
+        for (key, raw_move, weight) in entries.iter().rev() {
+            bytes.extend_from_slice(&key.to_be_bytes());
+            bytes.extend_from_slice(&raw_move.to_be_bytes());
+            bytes.extend_from_slice(&weight.to_be_bytes());
+            bytes.extend_from_slice(&[0; 4]);
+        }
+        let book = OpeningBook::from_bytes(&bytes).unwrap();
+        assert_eq!(book.len(), 4);
+        assert_eq!(
+            book.moves(&start),
+            vec![
+                (Move::from_uci("e2e4").unwrap(), 3),
+                (Move::from_uci("d2d4").unwrap(), 1)
+            ]
+        );
+        let chosen = book.choose(&start);
+        assert!(book.moves(&start).iter().any(|c| Some(c.0) == chosen));
+        assert_eq!(
+            book.choose(&castling),
+            Some(Move::from_uci("e1g1").unwrap())
+        );
+        assert!(OpeningBook::from_bytes(&bytes[1..]).is_err());
+    }
+    #[test]
+    fn test_transposition_table() {
+        let mut table = TranspositionTable::new(1);
+        let e2e4 = Move::from_uci("e2e4").unwrap();
+        assert_eq!(table.probe(42), None);
+        table.store(42, 3, 25, Bound::Exact, Some(e2e4));
+        let entry = table.probe(42).unwrap();
+        assert_eq!(
+            (entry.depth, entry.score, entry.bound),
+            (3, 25, Bound::Exact)
+        );
+        assert_eq!(entry.best_move, Some(e2e4));
+        //a shallower bound of the same search doesn't replace the deeper result
+        table.store(42, 1, 10, Bound::Lower, None);
+        assert_eq!(table.probe(42).unwrap().depth, 3);
+        //a deeper one does and keeps the best move
+        table.store(42, 5, 30, Bound::Upper, None);
+        let entry = table.probe(42).unwrap();
+        assert_eq!((entry.depth, entry.best_move), (5, Some(e2e4)));
+        let stats = table.stats();
+        assert_eq!((stats.probes, stats.hits, stats.stores), (4, 3, 2));
+        assert_eq!(stats.hit_rate(), 0.75);
+        table.clear();
+        assert_eq!(table.probe(42), None);
+    }
+    #[test]
+    fn test_search() {
+        let mut board = Board::new();
+        board
+            .set_board_fen("7k/8/6K1/8/8/8/8/R7 w - - 0 1")
+            .unwrap();
+        let config = EngineConfig::new();
+        let mut table = TranspositionTable::new(1);
+        let report = engine::search(&board, &config, &mut table);
+        assert_eq!(report.best_move, Some(Move::from_uci("a1a8").unwrap()));
+        assert_eq!(report.score, MATE_SCORE - 1);
+        //the second search finds the position in the table
+        let again = engine::search(&board, &config, &mut table);
+        assert_eq!(again.best_move, report.best_move);
+        assert!(again.nodes < report.nodes);
+        assert!(table.stats().hits > 0);
+    }
+
+    //KQvK tables where every position has the same value
+    fn write_tablebase(name: &str) -> String {
+        let directory =
+            std::env::temp_dir().join(format!("chess_tb_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        //flags, order, the pieces of both sides in the low and high nibbles, alignment
+        let mut wdl = vec![0x71, 0xe8, 0x23, 0x5d, 0x01, 0x00, 0x66, 0x55, 0xee, 0x00];
+        //win with white to move, loss with black to move
+        wdl.extend_from_slice(&[0x80, 4, 0x80, 0]);
+        wdl.resize(64, 0);
+        std::fs::write(directory.join("KQvK.rtbw"), wdl).unwrap();
+        //five moves to zero with white to move
+        let mut dtz = vec![0xd7, 0x66, 0x0c, 0xa5, 0x00, 0x00, 0x66, 0x55, 0xee, 0x00];
+        dtz.extend_from_slice(&[0x80, 5]);
+        dtz.resize(64, 0);
+        std::fs::write(directory.join("KQvK.rtbz"), dtz).unwrap();
+        directory.to_string_lossy().to_string()
+    }
+
+    //a KQvK WDL table compressed with pairs and Huffman codes, every position with white
+    //to move is a win except index 30386 (Ka1 Qb2 kh1), black to move always loses
+    fn write_compressed_tablebase() -> String {
+        let directory =
+            std::env::temp_dir().join(format!("chess_tb_compressed_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut wdl = vec![0x71, 0xe8, 0x23, 0x5d, 0x01, 0x00, 0x66, 0x55, 0xee, 0x00];
+        //flags, 64 byte blocks, 4096 positions per sparse index entry, no padding, 31 blocks,
+        //code lengths 2 and 1 with the lowest symbols 2 and 0, then 4 symbols
+        wdl.extend_from_slice(&[0x00, 6, 12, 0, 31, 0, 0, 0, 2, 1, 2, 0, 0, 0, 4, 0]);
+        //symbols 0 and 1 are a win and a draw, 2 expands to 3 3 and 3 to 0 0,
+        //so the one bit code of symbol 2 stands for four wins
+        wdl.extend_from_slice(&[4, 0xf0, 0xff, 2, 0xf0, 0xff, 3, 0x30, 0, 0, 0, 0]);
+        //black to move
+        wdl.extend_from_slice(&[0x80, 0]);
+        //every entry points to the middle of its 4096 positions, 1024 positions per block
+        for k in 0..8u32 {
+            wdl.extend_from_slice(&(4 * k + 2).to_le_bytes());
+            wdl.extend_from_slice(&[0, 0]);
+        }
+        for _ in 0..31 {
+            wdl.extend_from_slice(&1023u16.to_le_bytes());
+        }
+        wdl.resize(192, 0);
+        let mut data = vec![0xff; 31 * 64 + 8];
+        //block 29 starts at index 29696, 172 codes of four wins, two single wins, the draw
+        data[29 * 64 + 21] = 0xf0;
+        data[29 * 64 + 22] = 0x7f;
+        wdl.extend_from_slice(&data);
+        std::fs::write(directory.join("KQvK.rtbw"), wdl).unwrap();
+        directory.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_tablebase_probe() {
+        let directory = write_tablebase("probe");
+        let mut tablebase = Tablebase::open(&directory).unwrap();
+        assert_eq!(tablebase.table_count(), 1);
+        assert_eq!(tablebase.max_pieces(), 3);
+        let mut board = Board::new();
+
+        board
+            .set_board_fen("8/8/8/8/8/8/1Q6/K6k w - - 0 1")
+            .unwrap();
+        assert!(tablebase.can_probe(&board));
+        assert_eq!(tablebase.probe_wdl(&board).unwrap(), Wdl::Win);
+        assert_eq!(tablebase.probe_dtz(&board).unwrap(), 11);
+        //hanging the queen or stalemating draws, every other move keeps the win
+        let moves = tablebase.root_moves(&board).unwrap();
+        assert_eq!(moves[0].1, 13);
+        let hanging = Move::from_uci("b2g2").unwrap();
+        let stalemate = Move::from_uci("b2f2").unwrap();
+        for drawing in [hanging, stalemate] {
+            assert_eq!(moves.iter().find(|c| c.0 == drawing).unwrap().1, 0);
+        }
+
+        board
+            .set_board_fen("8/8/8/8/8/8/1Q6/K6k b - - 0 1")
+            .unwrap();
+        assert_eq!(tablebase.probe_wdl(&board).unwrap(), Wdl::Loss);
+        //the DTZ table only stores white to move
+        assert_eq!(tablebase.probe_dtz(&board).unwrap(), -12);
+
+        //the colors are swapped to find the table
+        board
+            .set_board_fen("8/8/8/8/8/8/1q6/k6K b - - 0 1")
+            .unwrap();
+        assert_eq!(tablebase.probe_wdl(&board).unwrap(), Wdl::Win);
+
+        //two kings are a draw without a table
+        board.set_board_fen("8/8/8/8/8/8/8/K6k w - - 0 1").unwrap();
+        assert_eq!(tablebase.probe_wdl(&board).unwrap(), Wdl::Draw);
+        assert_eq!(tablebase.probe_dtz(&board).unwrap(), 0);
+
+        board
+            .set_board_fen("8/8/8/8/8/8/1R6/K6k w - - 0 1")
+            .unwrap();
+        assert!(tablebase.probe_wdl(&board).is_err());
+        board
+            .set_board_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1")
+            .unwrap();
+        assert!(!tablebase.can_probe(&board));
+        assert!(tablebase.probe_wdl(&board).is_err());
+        board.set_board_fen(START_FEN).unwrap();
+        assert!(!tablebase.can_probe(&board));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_tablebase_decompress() {
+        let directory = write_compressed_tablebase();
+        let mut tablebase = Tablebase::open(&directory).unwrap();
+        let mut board = Board::new();
+        let mut wdl = |fen: &str| {
+            board.set_board_fen(fen).unwrap();
+            tablebase.probe_wdl(&board).unwrap()
+        };
+        assert_eq!(wdl("8/8/8/8/8/8/1Q6/K6k w - - 0 1"), Wdl::Draw);
+        //the indexes before and after it
+        assert_eq!(wdl("8/8/8/8/8/8/1Q6/K5k1 w - - 0 1"), Wdl::Win);
+        assert_eq!(wdl("8/8/8/8/8/8/1Q6/K4k2 w - - 0 1"), Wdl::Win);
+        assert_eq!(wdl("8/8/8/8/8/8/1Q5k/K7 w - - 0 1"), Wdl::Win);
+        //the mirrored positions share the index
+        assert_eq!(wdl("8/8/8/8/8/8/6Q1/k6K w - - 0 1"), Wdl::Draw);
+        assert_eq!(wdl("K6k/1Q6/8/8/8/8/8/8 w - - 0 1"), Wdl::Draw);
+        //a different block and sparse index entry
+        assert_eq!(wdl("8/8/8/8/3k4/8/8/KQ6 w - - 0 1"), Wdl::Win);
+        assert_eq!(wdl("8/8/8/8/8/8/1Q6/K6k b - - 0 1"), Wdl::Loss);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_engine_with_tablebase() {
+        let directory = write_tablebase("engine");
+        let mut player = Player::from_spec("builtin:1", None).unwrap();
+        player.set_tablebase(&directory).unwrap();
+        let mut board = Board::new();
+        board
+            .set_board_fen("8/8/8/8/8/8/1Q6/K6k w - - 0 1")
+            .unwrap();
+        let best_move = player.choose_move(&board.fen(), &[], &board).unwrap();
+        assert_ne!(best_move, Move::from_uci("b2g2").ok());
+        std::fs::remove_dir_all(directory).unwrap();
+        assert!(Player::from_spec("builtin", None)
+            .unwrap()
+            .set_tablebase("/nonexistent/syzygy")
+            .is_err());
+    }
+
+    #[test]
+    fn test_evaluation_terms() {
+        let mut board = Board::new();
+        board.set_board_fen(START_FEN).unwrap();
+        let evaluation = Evaluation::new(&board);
+        assert_eq!(evaluation.white, evaluation.black);
+        assert_eq!(evaluation.white.material, 4000);
+        assert_eq!(evaluation.score(Color::White), 0);
+
+        //doubled and isolated pawns on the a-file, all of them passed
+        board
+            .set_board_fen("4k3/8/8/3P4/8/P7/P7/4K3 w - - 0 1")
+            .unwrap();
+        let evaluation = Evaluation::new(&board);
+        assert_eq!(evaluation.white.pawn_structure, 5);
+        assert_eq!(evaluation.black.pawn_structure, 0);
+        assert_eq!(evaluation.white.king_safety, -30);
+        assert_eq!(
+            evaluation.score(Color::White),
+            -evaluation.score(Color::Black)
+        );
+        assert_eq!(engine::evaluate(&board), evaluation.score(Color::White));
+
+        //the mirrored position has the same terms for the other color
+        board
+            .set_board_fen("4k3/p7/p7/8/3p4/8/8/4K3 b - - 0 1")
+            .unwrap();
+        let mirrored = Evaluation::new(&board);
+        assert_eq!(mirrored.black, evaluation.white);
+        assert_eq!(mirrored.white, evaluation.black);
+    }
+
+    #[test]
+    fn test_hint() {
+        let (hints, record) = Game::test_hint_helper(String::from("e2 e4")).unwrap();
+        assert!(hints[0].starts_with("Move your "));
+        assert!(hints[1].starts_with("Play "));
+        assert_eq!(record.move_comments.len(), 1);
+        assert_eq!(record.move_comments[0].0, 0);
+        let san = hints[1].trim_start_matches("Play ");
+        assert_eq!(record.move_comments[0].1, format!("Hint: {}", san));
+        let pgn = record.to_pgn().unwrap();
+        assert!(pgn.contains(&format!("1. e4 {{Hint: {}}} *", san)));
+    }
+
+    #[test]
+    fn test_game_undo() {
+        //the position after the capture comes back twice, the first time isn't enough
+        let moves = "e2 e4\nd7 d5\ne4 d5\ng8 f6\ng1 f3\nf6 g8\nf3 g1\ng8 f6\ng1 f3\nf6 g8\nf3 g1";
+        let (outcome, fen) = Game::test_undo_helper(moves.to_string()).unwrap();
+        assert_eq!(outcome, Some(Outcome::ThreefoldRepetition));
+        assert_eq!(fen, START_FEN);
+        let (outcome, _) =
+            Game::test_undo_helper(moves.strip_suffix("\nf3 g1").unwrap().to_string()).unwrap();
+        assert_eq!(outcome, None);
+    }
+
+    #[test]
+    fn test_game_analysis() {
+        let mut game = parse_pgn("1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0")
+            .unwrap()
+            .remove(0);
+        let config = EngineConfig {
+            depth: 2,
+            hash_mb: 1,
+        };
+        let analysis = GameAnalysis::new(&game, &config).unwrap();
+        assert_eq!(analysis.moves.len(), 7);
+        //Nf6 allows the mate
+        let blunder = analysis.moves[5];
+        assert_eq!(blunder.color, Color::Black);
+        assert_eq!(blunder.judgment, Some(Judgment::Blunder));
+        assert_ne!(blunder.best_move, Some(blunder.played));
+        assert_eq!(analysis.moves[6].judgment, None);
+
+        let white = analysis.summary(Color::White);
+        let black = analysis.summary(Color::Black);
+        assert_eq!((white.moves, black.moves), (4, 3));
+        assert_eq!(black.blunders, 1);
+        assert!(white.accuracy > black.accuracy);
+        assert!(analysis.report().contains("3... Nf6 #1: Blunder"));
+
+        analysis.annotate(&mut game);
+        let pgn = game.to_pgn().unwrap();
+        assert!(pgn.contains("Nf6 $4 {#1 Blunder"));
+        assert!(pgn.contains("[BlackAccuracy"));
+        //the annotations don't change the moves
+        assert_eq!(parse_pgn(&pgn).unwrap()[0].moves, game.moves);
+    }
+
+    #[test]
+    fn test_puzzles() {
+        let csv = "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags
+00001,6k1/5ppp/8/8/8/8/5PPP/RR4K1 b - - 0 1,g8h8 b1b8,1200,80,90,100,mate mateIn1 backRankMate,,
+00002,3r2k1/p4ppp/8/8/8/8/4RPPP/4R1K1 b - - 0 1,a7a6 e2e8 d8e8 e1e8,1600,80,90,100,mate mateIn2,,";
+        let puzzles = parse_puzzles(csv).unwrap();
+        assert_eq!(puzzles.len(), 2);
+        assert_eq!(puzzles[1].rating, 1600);
+        assert_eq!(puzzles[0].themes, "mate mateIn1 backRankMate");
+        assert!(parse_puzzles("1,8/8/8/8/8/8/8/K6k w - - 0 1,a1a8 h1h2,1500").is_err());
+
+        //the other rook mates as well
+        let mut session = PuzzleSession::new(&puzzles[0]).unwrap();
+        assert_eq!(session.expected(), Move::from_uci("b1b8").unwrap());
+        assert_eq!(
+            session.attempt(Move::from_uci("a1a8").unwrap()),
+            Attempt::Solved
+        );
+
+        let mut session = PuzzleSession::new(&puzzles[1]).unwrap();
+        assert_eq!(
+            session.attempt(Move::from_uci("e2e7").unwrap()),
+            Attempt::Wrong
+        );
+        assert_eq!(session.solution(), "Re8+ Rxe8 Rxe8#");
+        assert_eq!(
+            session.attempt(Move::from_uci("e2e8").unwrap()),
+            Attempt::Correct(Move::from_uci("d8e8").unwrap())
+        );
+        assert_eq!(
+            session.attempt(Move::from_uci("e1e8").unwrap()),
+            Attempt::Solved
+        );
+
+        let mut trainer = PuzzleTrainer::new(puzzles);
+        assert_eq!(trainer.next_puzzle().unwrap().id, "00002");
+        trainer.record(1500, true);
+        assert_eq!((trainer.rating(), trainer.streak()), (1516, 1));
+        assert_eq!(trainer.next_puzzle().unwrap().id, "00001");
+        trainer.record(1200, false);
+        assert_eq!(trainer.streak(), 0);
+        assert!(trainer.rating() < 1516);
+        assert!(trainer.next_puzzle().is_none());
+    }
+
+    #[test]
+    fn test_repertoire() {
+        let pgn = "1. e4 e5 (1... c5 2. Nf3 {Open Sicilian}) 2. Nf3 Nc6 *";
+        assert_eq!(parse_pgn(pgn).unwrap()[0].moves.len(), 4);
+        let lines = &parse_pgn_lines(pgn).unwrap()[0].1;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 3);
+
+        let uci = |c: &str| Move::from_uci(c).unwrap();
+        let repertoire = Repertoire::parse(pgn).unwrap();
+        assert_eq!(repertoire.moves(&repertoire.start()), &[uci("e2e4")]);
+        let path = std::env::temp_dir().join(format!("chess_srs_{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut drill = RepertoireDrill::new(repertoire, Color::White, Schedule::open(path));
+
+        //plays through one of the lines without mistakes
+        let mut board = drill.start();
+        assert_eq!(drill.check(&board, uci("e2e4")), DrillAnswer::InRepertoire);
+        board.make_move(uci("e2e4"));
+        let first = drill.reply(&board).unwrap();
+        board.make_move(first);
+        assert_eq!(drill.check(&board, uci("g1f3")), DrillAnswer::InRepertoire);
+        board.make_move(uci("g1f3"));
+        if let Some(reply) = drill.reply(&board) {
+            board.make_move(reply);
+        }
+        assert!(drill.line_finished(&board));
+        assert!(drill.finish_line().unwrap());
+
+        //the other line is due now, a deviation sends it back to the first box
+        let mut board = drill.start();
+        assert_eq!(
+            drill.check(&board, uci("d2d4")),
+            DrillAnswer::Deviation(vec![uci("e2e4")])
+        );
+        drill.check(&board, uci("e2e4"));
+        board.make_move(uci("e2e4"));
+        assert_ne!(drill.reply(&board).unwrap(), first);
+        assert!(!drill.finish_line().unwrap());
+        assert_eq!(drill.status(), (2, 1, 1));
+
+        //the schedule is read back from its file
+        let repertoire = Repertoire::parse(pgn).unwrap();
+        let drill = RepertoireDrill::new(repertoire, Color::White, Schedule::open(path));
+        assert_eq!(drill.status(), (2, 1, 1));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_chess960() {
+        let mut board = Board::new();
+        board.set_board_960(518).unwrap();
+        assert_eq!(board.fen(), START_FEN);
+        board.set_board_960(0).unwrap();
+        assert_eq!(
+            board.fen(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+        );
+        assert!(board.set_board_960(960).is_err());
+
+        //castling is written as the king taking its own tower
+        board
+            .set_board_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+            .unwrap();
+        assert!(board.is_chess960());
+        assert_eq!(perft(&board, 3), 12189);
+        board
+            .set_board_fen("1r4k1/8/8/8/8/8/8/R5KR w HA - 0 1")
+            .unwrap();
+        assert_eq!(board.fen(), "1r4k1/8/8/8/8/8/8/R5KR w KQ - 0 1");
+        let large = Move::from_uci("g1a1").unwrap();
+        assert!(board.legal_moves().contains(&large));
+        assert_eq!(to_san(&board, large), "O-O-O");
+        let castling = Move::from_uci("g1h1").unwrap();
+        assert_eq!(to_san(&board, castling), "O-O");
+        board.make_move(castling);
+        assert_eq!(board.fen(), "1r4k1/8/8/8/8/8/8/R4RK1 b - - 1 1");
+
+        //a tower inside the outer one is named by its file
+        board
+            .set_board_fen("2r1kr2/8/8/8/8/8/8/1R2K1R1 w GBfc - 0 1")
+            .unwrap();
+        assert_eq!(board.fen(), "2r1kr2/8/8/8/8/8/8/1R2K1R1 w KQkq - 0 1");
+        board
+            .set_board_fen("rr2k3/8/8/8/8/8/8/4K3 b b - 0 1")
+            .unwrap();
+        assert_eq!(board.fen(), "rr2k3/8/8/8/8/8/8/4K3 b b - 0 1");
+        assert!(board
+            .legal_moves()
+            .contains(&Move::from_uci("e8b8").unwrap()));
+    }
+
+    #[test]
+    fn test_variants() {
+        assert_eq!(
+            Variant::from_name("king of the hill"),
+            Some(Variant::KingOfTheHill)
+        );
+        assert_eq!(
+            Variant::from_name("racingkings"),
+            Some(Variant::RacingKings)
+        );
+        let board = |fen: &str| {
+            let mut board = Board::new();
+            board.set_board_fen_unchecked(fen).unwrap();
+            board
+        };
+        let hill = board("4k3/8/8/8/3K4/8/8/8 b - - 0 1");
+        assert_eq!(
+            Variant::KingOfTheHill.outcome(&hill, []),
+            Some(Outcome::KingOfTheHill(Color::White))
+        );
+
+        let check = board("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1");
+        assert_eq!(Variant::ThreeCheck.outcome(&check, [Color::Black]), None);
+        assert_eq!(
+            Variant::ThreeCheck.outcome(&check, [Color::White, Color::White, Color::Black]),
+            None
+        );
+        assert_eq!(
+            Variant::ThreeCheck.outcome(&check, [Color::Black, Color::Black]),
+            Some(Outcome::ThreeChecks(Color::White))
+        );
+
+        //taking is compulsory
+        let mut antichess = Variant::Antichess.start_board().unwrap();
+        for uci in ["e2e3", "b7b5"] {
+            Variant::Antichess.make_move(&mut antichess, Move::from_uci(uci).unwrap());
+        }
+        assert_eq!(
+            Variant::Antichess.legal_moves(&antichess),
+            vec![Move::from_uci("f1b5").unwrap()]
+        );
+        assert_eq!(
+            Variant::Antichess.outcome(&board("8/8/8/8/8/p7/P7/8 w - - 0 1"), []),
+            Some(Outcome::NoMovesLeft(Color::White))
+        );
+
+        //the knight takes next to the king and everything around explodes
+        let mut atomic = board("4k3/3p4/8/4N3/8/8/8/4K3 w - - 0 1");
+        let take = Move::from_uci("e5d7").unwrap();
+        assert!(Variant::Atomic.legal_moves(&atomic).contains(&take));
+        let before = atomic;
+        let (_, undo) = Variant::Atomic.make_move(&mut atomic, take);
+        assert_eq!(atomic.fen(), "8/8/8/8/8/8/8/4K3 b - - 0 1");
+        let exploded = atomic;
+        //taking it back brings the exploded figures back
+        Variant::Atomic.unmake_move(&mut atomic, undo);
+        assert_eq!(atomic, before);
+        let atomic = exploded;
+        assert_eq!(
+            Variant::Atomic.outcome(&atomic, []),
+            Some(Outcome::KingExploded(Color::White))
+        );
+
+        let horde = Variant::Horde.start_board().unwrap();
+        assert_eq!(Variant::Horde.legal_moves(&horde).len(), 8);
+        assert!(Variant::Horde
+            .legal_moves(&board("4k3/8/8/8/8/8/8/P7 w - - 0 1"))
+            .contains(&Move::from_uci("a1a3").unwrap()));
+        assert_eq!(
+            Variant::Horde.outcome(&board("4k3/8/8/8/8/8/8/8 w - - 0 1"), []),
+            Some(Outcome::AllFiguresTaken(Color::Black))
+        );
+
+        //no checks, black gets one more move after white reached the last row
+        let racing = Variant::RacingKings.start_board().unwrap();
+        assert_eq!(Variant::RacingKings.legal_moves(&racing).len(), 21);
+        assert_eq!(
+            Variant::RacingKings.outcome(&board("7K/8/k7/8/8/8/8/8 b - - 0 1"), []),
+            Some(Outcome::RaceWon(Color::White))
+        );
+        assert_eq!(
+            Variant::RacingKings.outcome(&board("7K/k7/8/8/8/8/8/8 b - - 0 1"), []),
+            None
+        );
+    }
+
+    #[test]
+    fn test_crazyhouse() {
+        let drop = Move::from_uci("N@f3").unwrap();
+        assert_eq!(drop.to_string(), "N@f3");
+        assert!(Move::from_uci("K@f3").is_err());
+
+        //taken figures can be dropped back, pawns not on the first and last row
+        let mut board = Variant::Crazyhouse.start_board().unwrap();
+        for uci in ["e2e4", "d7d5", "e4d5", "g8f6"] {
+            board.make_move(Move::from_uci(uci).unwrap());
+        }
+        assert_eq!(board.pocket(Color::White, FieldType::Pawn), 1);
+        let pawn_drop = Move::from_uci("P@e6").unwrap();
+        assert!(board.legal_moves().contains(&pawn_drop));
+        assert_eq!(to_san(&board, pawn_drop), "P@e6");
+        board.make_move(pawn_drop);
+        assert_eq!(
+            board.fen(),
+            "rnbqkb1r/ppp1pppp/4Pn2/3P4/8/8/PPPP1PPP/RNBQKBNR[] b KQkq - 0 3"
+        );
+
+        let mut board = Board::new();
+        board
+            .set_board_fen("4k3/8/8/8/8/8/8/4K3[Pn] w - - 0 1")
+            .unwrap();
+        assert_eq!(board.fen(), "4k3/8/8/8/8/8/8/4K3[Pn] w - - 0 1");
+        assert_eq!(board.legal_moves().len(), 5 + 48);
+
+        //a promoted queen goes back to the pocket as a pawn
+        board
+            .set_board_fen("3k4/8/8/8/8/8/8/3q~K3[] w - - 0 1")
+            .unwrap();
+        board.make_move(Move::from_uci("e1d1").unwrap());
+        assert_eq!(board.pocket(Color::White, FieldType::Pawn), 1);
+        assert_eq!(board.pocket(Color::White, FieldType::Queen), 0);
+        assert_eq!(board.fen(), "3k4/8/8/8/8/8/8/3K4[P] b - - 0 1");
+    }
+
+    #[test]
+    fn test_animation() {
+        let mut board = Board::new();
+        board.set_board_start();
+        let mut boards = vec![board];
+        let moves: Vec<Move> = ["e2e4", "e7e5", "g1f3"]
+            .iter()
+            .map(|c| Move::from_uci(c).unwrap())
+            .collect();
+        for next_move in &moves {
+            board.make_move(*next_move);
+            boards.push(board);
+        }
+        let frames = animation::frames(&boards, &moves);
+        let captions: Vec<Option<String>> = frames.iter().map(|c| c.caption.clone()).collect();
+        assert_eq!(
+            captions,
+            vec![
+                None,
+                Some(String::from("1. e4")),
+                Some(String::from("1... e5")),
+                Some(String::from("2. Nf3"))
+            ]
+        );
+        assert_eq!(frames[3].last_move, Some(moves[2]));
+
+        //clear, 0, the new code for 0 0, 0 and the end in 9 bits
+        assert_eq!(lzw(&[0, 0, 0, 0]), vec![0, 1, 8, 4, 16, 16]);
+
+        let options = AnimationOptions::parse(&["delay", "250", "size", "80", "nocoords"]).unwrap();
+        assert!(AnimationOptions::parse(&["delay", "fast"]).is_err());
+        let gif = animation::gif(&frames, &options);
+        assert_eq!(&gif[..6], b"GIF89a");
+        //80 pixels wide with the caption band below
+        assert_eq!(&gif[6..10], &[80, 0, 88, 0]);
+        assert_eq!(gif.last(), Some(&0x3b));
+        //graphic control blocks with 25 hundredths of a second
+        let delays = gif
+            .windows(6)
+            .filter(|c| c[..4] == [0x21, 0xf9, 0x04, 0x04] && c[4..] == [25, 0])
+            .count();
+        assert_eq!(delays, 4);
+
+        let svg = animation::svg(&frames, &options);
+        assert_eq!(svg.matches("<g visibility=\"hidden\">").count(), 4);
+        assert!(svg.contains("keyTimes=\"0;0.2500;0.5000\" dur=\"1s\""));
+        assert!(svg.contains(">1... e5</text>"));
+    }
+
+    #[test]
+    fn test_diagram() {
+        let mut board = Board::new();
+        board.set_board_start();
+        let options = DiagramOptions::parse(&[
+            "size",
+            "160",
+            "nocoords",
+            "highlight",
+            "e4",
+            "arrow",
+            "g1f3",
+        ])
+        .unwrap();
+        assert!(DiagramOptions::parse(&["highlight", "e9"]).is_err());
+        assert!(DiagramOptions::parse(&["size"]).is_err());
+
+        let text = svg(&board, &options);
+        assert!(text.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"160\""));
+        assert!(text.contains("fill=\"#ffeb3b\" fill-opacity=\"0.5\""));
+        assert!(text.contains("fill=\"#15781b\""));
+        assert!(!text.contains("<text"));
+
+        //fields are 20 pixels, a1 is dark in the lower left corner, e4 is highlighted
+        let image = raster(&board, &options);
+        assert_eq!((image.width, image.height), (160, 160));
+        assert_eq!(image.pixels[159 * 160], [181, 136, 99]);
+        assert_eq!(image.pixels[159 * 160 + 159], [240, 217, 181]);
+        assert_eq!(image.pixels[80 * 160 + 81], [248, 226, 120]);
+
+        let flipped = DiagramOptions::parse(&["size", "160", "nocoords", "flip"]).unwrap();
+        //the tower in the upper left corner
+        assert_eq!(image.pixels[12 * 160 + 10], [40, 40, 40]);
+        assert_eq!(
+            raster(&board, &flipped).pixels[12 * 160 + 10],
+            [255, 255, 255]
+        );
+
+        let data = png(&board, &options);
+        assert_eq!(&data[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&data[12..16], b"IHDR");
+        assert_eq!(&data[16..24], &[0, 0, 0, 160, 0, 0, 0, 160]);
+        assert!(data.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+    }
+
+    #[test]
+    fn test_material() {
+        let mut start = Board::new();
+        start.set_board_start();
+        let mut board = start;
+        for uci in ["e2e4", "d7d5", "e4d5", "d8d5"] {
+            board.make_move(Move::from_uci(uci).unwrap());
+        }
+        let material = Material::new(&start, &board);
+        assert_eq!(material.taken_by_white, vec![FieldType::Pawn]);
+        assert_eq!(material.taken_by_black, vec![FieldType::Pawn]);
+        assert_eq!(material.balance, 0);
+        assert_eq!(material.line(Color::White), "♟");
+
+        //the second queen came from the missing pawn, the tower was taken
+        board
+            .set_board_fen("r1bqkbnr/pppppppp/8/8/8/8/PPPPPPP1/RNBQKBNQ w Qkq - 0 1")
+            .unwrap();
+        let material = Material::new(&start, &board);
+        assert_eq!(material.taken_by_white, vec![FieldType::Knight]);
+        assert_eq!(material.taken_by_black, vec![FieldType::Tower]);
+        assert_eq!(material.line(Color::White), "♞ +6");
+        assert_eq!(material.line(Color::Black), "♖");
+
+        //after en passant the taken pawn is gone as well
+        board
+            .set_board_fen("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3")
+            .unwrap();
+        board.make_move(Move::from_uci("e5d6").unwrap());
+        assert_eq!(
+            Material::new(&start, &board).taken_by_white,
+            vec![FieldType::Pawn]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let mut board = Board::new();
+        board.set_board_start();
+        board.make_move(Move::from_uci("e2e4").unwrap());
+        let mut options = RenderOptions::new();
+        options.apply("colors off pieces letters").unwrap();
+        assert_eq!(
+            render(&board, &options),
+            "   a  b  c  d  e  f  g  h \n\
+             8  r  n  b  q  k  b  n  r  8\n\
+             7  p  p  p  p  p  p  p  p  7\n\
+             6  .  .  .  .  .  .  .  .  6\n\
+             5  .  .  .  .  .  .  .  .  5\n\
+             4  .  .  .  .  P  .  .  .  4\n\
+             3  .  .  .  .  .  .  .  .  3\n\
+             2  P  P  P  P  .  P  P  P  2\n\
+             1  R  N  B  Q  K  B  N  R  1\n   \
+             a  b  c  d  e  f  g  h \n"
+        );
+
+        //black to move is at the bottom, files run from h to a
+        options.apply("flip auto pieces unicode").unwrap();
+        assert_eq!(options.orientation, Orientation::SideToMove);
+        let text = render(&board, &options);
+        assert!(text.starts_with("   h  g  f  e  d  c  b  a \n1  ♖  ♘  ♗  ♔  ♕"));
+        assert!(text.contains("7  ♟  ♟  ♟"));
+        assert!(!text.contains('\u{fe0e}'));
+
+        options.apply("colors on").unwrap();
+        assert!(render(&board, &options).contains("\x1b[48;5;"));
+        assert!(options.apply("flip sideways").is_err());
+        assert!(options.apply("colors").is_err());
+    }
+
+    #[test]
+    fn test_tui() {
+        assert_eq!(
+            parse_keys(b"\x1b[A\x1b[De4\r\x7f\x1b\x1b[6~"),
+            vec![
+                Key::Up,
+                Key::Left,
+                Key::Char('e'),
+                Key::Char('4'),
+                Key::Enter,
+                Key::Backspace,
+                Key::Escape,
+                Key::PageDown
+            ]
+        );
+
+        let mut board = Board::new();
+        board.set_board_start();
+        let mut view = View {
+            board: &board,
+            flipped: false,
+            cursor: sq("e2"),
+            selected: Some(sq("e2")),
+            targets: vec![sq("e3"), sq("e4")],
+            last_move: None,
+            moves: ["e4", "e5", "Nf3", "Nc6", "Bb5"]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+            scroll: 0,
+            clocks: [
+                std::time::Duration::from_secs(65),
+                std::time::Duration::from_secs(3),
+            ],
+            material: [String::from("♟ +1"), String::new()],
+            status: String::from("White to move"),
+            command: String::from("Nf"),
+        };
+        let frame = view.render();
+        assert!(frame.contains("> White 01:05  ♟ +1"));
+        assert!(frame.contains("  Black 00:03"));
+        assert!(frame.contains("3. Bb5"));
+        assert!(frame.contains(" · "));
+        assert!(frame.ends_with(" > Nf"));
+        let first_rank = frame.lines().nth(7).unwrap();
+        assert!(first_rank.starts_with(" 1 "));
+
+        //scrolled back the last move is hidden, flipped the first rank is on top
+        view.scroll = 1;
+        view.flipped = true;
+        let frame = view.render();
+        assert!(!frame.contains("3. Bb5"));
+        assert!(frame.contains("2. Nf3 Nc6"));
+        assert!(frame.lines().next().unwrap().starts_with(" 1 "));
+    }
+
+    #[test]
+    fn test_handicap() {
+        assert_eq!(
+            Handicap::parse("knight odds"),
+            Ok((Handicap::Knight, Color::White))
+        );
+        assert_eq!(
+            Handicap::parse("Pawn and move"),
+            Ok((Handicap::PawnAndMove, Color::Black))
+        );
+        assert!(Handicap::parse("bishop").is_err());
+
+        //the missing tower can't castle anymore
+        let (handicap, giver) = Handicap::parse("rook black").unwrap();
+        assert_eq!(
+            handicap.start_board(giver).fen(),
+            "1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQk - 0 1"
+        );
+        assert_eq!(
+            Handicap::PawnAndMove.start_board(Color::Black).fen(),
+            "rnbqkbnr/ppppp1pp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+        assert_eq!(
+            Handicap::PawnAndMove.start_board(Color::White).fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPP1PP/RNBQKBNR b KQkq - 0 1"
+        );
+        assert_eq!(
+            Handicap::Queen.start_board(Color::White).fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_position_editor() {
+        let mut board = Board::new();
+        board.set_board_start();
+        let mut editor = PositionEditor::new(&board);
+        for input in [
+            "clear all",
+            "put Kw e1",
+            "put kb e8",
+            "put Rw h1",
+            "put Pb d4",
+            "castle K on",
+            "side black",
+        ] {
+            assert!(matches!(editor.apply(input), Ok(EditResult::Editing)));
+        }
+        assert!(editor.apply("put Xw e4").is_err());
+        assert!(editor.apply("clear i9").is_err());
+
+        //the checks run when the editing is done
+        for (input, undo) in [
+            ("put Pw a8", "clear a8"),
+            ("put qb e2", "clear e2"),
+            ("castle Q on", "castle Q off"),
+            ("ep e6", "ep off"),
+        ] {
+            editor.apply(input).unwrap();
+            assert!(matches!(
+                editor.apply("done"),
+                Err(ChessError::InvalidPosition(_))
+            ));
+            editor.apply(undo).unwrap();
+        }
+        //black may take the pawn which just moved en passant
+        editor.apply("put Pw e4").unwrap();
+        editor.apply("ep e3").unwrap();
+        assert!(matches!(editor.apply("done"), Ok(EditResult::Done)));
+        let board = editor.board();
+        assert_eq!(board.fen(), "4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 1");
+        assert!(board
+            .validate_move(sq("d4"), sq("e3"), Color::Black)
+            .is_ok());
+    }
+    #[test]
+    fn test_square_and_move_types() {
+        let e4 = sq("e4");
+        assert_eq!(e4.to_string(), "e4");
+        assert_eq!((e4.file().letter(), e4.rank().number()), ('e', 4));
+        assert_eq!(Square::parse("i1"), None);
+        assert_eq!(Square::parse("e44"), None);
+        assert_eq!(sq("h8").index(), 63);
+        assert_eq!(sq("h8").offset(1, 0), None);
+        assert_eq!(e4.offset(1, -1), Some(sq("d5")));
+        assert_eq!(Square::all().count(), 64);
+
+        //flags are filled in by the board which knows the position
+        let mut board = Board::new();
+        board
+            .set_board_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1")
+            .unwrap();
+        let en_passant = board.annotate(Move::from_uci("e5d6").unwrap());
+        assert!(en_passant.flags.en_passant && en_passant.flags.capture);
+        let castling = board.annotate(Move::from_uci("e1g1").unwrap());
+        assert!(castling.flags.castling && !castling.flags.capture);
+        assert_eq!(en_passant, Move::new(sq("e5"), sq("d6")));
+        assert!(board
+            .legal_moves()
+            .iter()
+            .any(|m| m.to == sq("c1") && m.flags.castling));
+    }
+    #[test]
+    fn test_make_unmake_move() {
+        //castling, en passant, promotions and crazyhouse pockets are all taken back
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1",
+            "r3k2r/pPp5/8/8/8/8/5q~2/R3K2R[QNp] w KQkq - 0 1",
+        ] {
+            let mut board = Board::new();
+            board.set_board_fen(fen).unwrap();
+            let before = board;
+            for first in before.legal_moves() {
+                let undo = board.make_move(first);
+                let after = board;
+                for second in after.legal_moves() {
+                    let undo = board.make_move(second);
+                    board.unmake_move(undo);
+                    assert_eq!(board, after, "{} {} in {}", first, second, fen);
+                }
+                board.unmake_move(undo);
+                assert_eq!(board, before, "{} in {}", first, fen);
+            }
+        }
+
+        //trying a king move leaves the castling rights alone
+        let mut board = Board::new();
+        board
+            .set_board_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")
+            .unwrap();
+        let before = board;
+        let undo = board.make_move(Move::from_uci("e1f1").unwrap());
+        board.unmake_move(undo);
+        assert_eq!(board, before);
+
+        board.set_board_start();
+        let undo = board.make_move(Move::from_uci("e2e4").unwrap());
+        assert_eq!(board.describe_move(&undo), "White moved Pawn from e2 to e4");
+    }
+    #[test]
+    fn test_attackers() {
+        let mut board = Board::new();
+        board
+            .set_board_fen("4k3/8/8/3pP3/8/2n5/8/R3K2R w KQ d6 0 1")
+            .unwrap();
+        //the pawn which just moved can be taken en passant
+        assert_eq!(board.attackers(sq("d5"), Color::White), vec![sq("e5")]);
+        assert_eq!(board.attackers(sq("d6"), Color::White), vec![sq("e5")]);
+        assert_eq!(board.attackers(sq("a8"), Color::White), vec![sq("a1")]);
+        assert_eq!(board.attackers(sq("e2"), Color::Black), vec![sq("c3")]);
+        assert!(!board.is_square_attacked(sq("e1"), Color::Black));
+        //the knight's fields, own figures included
+        let knight = ["a2", "a4", "b1", "b5", "d1", "d5", "e2", "e4"];
+        assert_eq!(
+            board.attacks(sq("c3")),
+            knight.iter().fold(0, |map, c| map | sq(c).bit())
+        );
+        for color in [Color::White, Color::Black] {
+            let map = board.attack_map(color);
+            for square in Square::all() {
+                assert_eq!(
+                    map & square.bit() != 0,
+                    board.is_square_attacked(square, color),
+                    "{} by {}",
+                    square,
+                    color
+                );
+            }
+        }
+        //the knight covers d1, the king may not pass it
+        assert_eq!(
+            board.castling("o-o-o", true),
+            Err(ChessError::CastlingNotPossibleWhenKingUnderAttack(sq("d1")))
+        );
+        assert_eq!(board.castling("o-o", true), Ok(true));
+    }
 }
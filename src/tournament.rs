@@ -0,0 +1,639 @@
+use std::collections::HashSet;
+use std::fs;
+
+use crate::board::START_FEN;
+use crate::book::OpeningBook;
+use crate::field::{ChessError, Color};
+use crate::game::Game;
+use crate::match_runner::{self, Opening};
+use crate::pgn::PgnGame;
+use crate::player::Player;
+use crate::tablebase::Tablebase;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TournamentFormat {
+    RoundRobin,
+    //number of rounds
+    Swiss(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entrant {
+    pub name: String,
+    //"human", "builtin[:depth]" or the path of a UCI engine
+    pub spec: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pairing {
+    pub white: usize,
+    //None is a bye for the white player
+    pub black: Option<usize>,
+    //points of the white player once the game is finished
+    pub result: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Standing {
+    pub player: usize,
+    pub points: f64,
+    pub buchholz: f64,
+    pub sonneborn_berger: f64,
+}
+
+pub struct Tournament {
+    name: String,
+    format: TournamentFormat,
+    players: Vec<Entrant>,
+    rounds: Vec<Vec<Pairing>>,
+    games: Vec<PgnGame>,
+    book: Option<OpeningBook>,
+    //Syzygy directory for the engines
+    syzygy: Option<String>,
+}
+
+impl Tournament {
+    pub fn new(name: &str) -> Tournament {
+        Tournament {
+            name: name.to_string(),
+            format: TournamentFormat::RoundRobin,
+            players: Vec::new(),
+            rounds: Vec::new(),
+            games: Vec::new(),
+            book: None,
+            syzygy: None,
+        }
+    }
+
+    pub fn set_format(&mut self, format: TournamentFormat) -> Result<(), ChessError> {
+        if !self.rounds.is_empty() {
+            return Err(ChessError::TournamentError(String::from(
+                "the format can't change after the first round",
+            )));
+        }
+        self.format = format;
+        Ok(())
+    }
+
+    pub fn add_player(&mut self, name: &str, spec: &str) -> Result<(), ChessError> {
+        if !self.rounds.is_empty() {
+            return Err(ChessError::TournamentError(String::from(
+                "players can't join after the first round",
+            )));
+        }
+        self.players.push(Entrant {
+            name: name.to_string(),
+            spec: spec.to_string(),
+        });
+        Ok(())
+    }
+
+    pub fn current_round(&self) -> Option<&[Pairing]> {
+        self.rounds.last().map(|c| c.as_slice())
+    }
+
+    pub fn total_rounds(&self) -> usize {
+        match self.format {
+            TournamentFormat::RoundRobin => self.players.len() - 1 + self.players.len() % 2,
+            TournamentFormat::Swiss(rounds) => rounds,
+        }
+    }
+
+    //all finished games of a player as (opponent, color, own points), None opponent is a bye
+    fn results(&self, player: usize) -> Vec<(Option<usize>, Color, f64)> {
+        let mut results = Vec::new();
+        for pairing in self.rounds.iter().flatten() {
+            let result = match pairing.result {
+                Some(c) => c,
+                None => continue,
+            };
+            if pairing.white == player {
+                results.push((pairing.black, Color::White, result));
+            } else if pairing.black == Some(player) {
+                results.push((Some(pairing.white), Color::Black, 1.0 - result));
+            }
+        }
+        results
+    }
+
+    pub fn points(&self, player: usize) -> f64 {
+        self.results(player).iter().fold(0.0, |sum, c| sum + c.2)
+    }
+
+    pub fn pair_next_round(&mut self) -> Result<&[Pairing], ChessError> {
+        if self.players.len() < 2 {
+            return Err(ChessError::TournamentError(String::from(
+                "at least two players are needed",
+            )));
+        }
+        if let Some(round) = self.rounds.last() {
+            if round.iter().any(|c| c.result.is_none()) {
+                return Err(ChessError::TournamentError(String::from(
+                    "the current round isn't finished",
+                )));
+            }
+        }
+        if self.rounds.len() >= self.total_rounds() {
+            return Err(ChessError::TournamentError(String::from(
+                "all rounds have been played",
+            )));
+        }
+        let round = match self.format {
+            TournamentFormat::RoundRobin => self.round_robin_round(self.rounds.len()),
+            TournamentFormat::Swiss(_) => self.swiss_round(),
+        };
+        self.rounds.push(round);
+        Ok(self.rounds.last().map(|c| c.as_slice()).unwrap_or(&[]))
+    }
+
+    //circle method: the last player stays in place while the others rotate
+    fn round_robin_round(&self, round: usize) -> Vec<Pairing> {
+        let mut ids: Vec<Option<usize>> = (0..self.players.len()).map(Some).collect();
+        if ids.len() % 2 == 1 {
+            ids.push(None);
+        }
+        let fixed = ids.pop().unwrap_or(None);
+        let rotation = round % ids.len();
+        ids.rotate_right(rotation);
+        ids.push(fixed);
+
+        let count = ids.len();
+        let mut pairings = Vec::new();
+        for i in 0..count / 2 {
+            //the fixed player changes colors every round
+            let (white, black) = if i == 0 && round % 2 == 1 {
+                (ids[count - 1], ids[0])
+            } else {
+                (ids[i], ids[count - 1 - i])
+            };
+            match (white, black) {
+                (Some(white), Some(black)) => pairings.push(Pairing {
+                    white,
+                    black: Some(black),
+                    result: None,
+                }),
+                //sitting out a round robin round gives no points
+                (Some(c), None) | (None, Some(c)) => pairings.push(Pairing {
+                    white: c,
+                    black: None,
+                    result: Some(0.0),
+                }),
+                (None, None) => {}
+            }
+        }
+        pairings
+    }
+
+    fn swiss_round(&self) -> Vec<Pairing> {
+        let mut ranking: Vec<usize> = (0..self.players.len()).collect();
+        let points: Vec<f64> = ranking.iter().map(|c| self.points(*c)).collect();
+        ranking.sort_by(|a, b| points[*b].total_cmp(&points[*a]).then(a.cmp(b)));
+
+        let mut pairings = Vec::new();
+        //the lowest ranked player without a bye sits out and gets a point
+        if ranking.len() % 2 == 1 {
+            let bye = ranking
+                .iter()
+                .rposition(|c| self.results(*c).iter().all(|r| r.0.is_some()))
+                .unwrap_or(ranking.len() - 1);
+            pairings.push(Pairing {
+                white: ranking.remove(bye),
+                black: None,
+                result: Some(1.0),
+            });
+        }
+
+        let mut played = HashSet::new();
+        for pairing in self.rounds.iter().flatten() {
+            if let Some(black) = pairing.black {
+                played.insert((pairing.white.min(black), pairing.white.max(black)));
+            }
+        }
+        //rematches are only allowed when there is no other way
+        let pairs = Tournament::swiss_pairs(&ranking, &points, &played)
+            .unwrap_or_else(|| ranking.chunks(2).map(|c| (c[0], c[1])).collect());
+        for (first, second) in pairs {
+            let (white, black) = self.colors(first, second);
+            pairings.push(Pairing {
+                white,
+                black: Some(black),
+                result: None,
+            });
+        }
+        //byes are listed last
+        pairings.sort_by_key(|c| c.black.is_none());
+        pairings
+    }
+
+    //Dutch system: the top half of a score group meets the bottom half, backtracking on rematches
+    fn swiss_pairs(
+        ranking: &[usize],
+        points: &[f64],
+        played: &HashSet<(usize, usize)>,
+    ) -> Option<Vec<(usize, usize)>> {
+        if ranking.is_empty() {
+            return Some(Vec::new());
+        }
+        let first = ranking[0];
+        let group = ranking
+            .iter()
+            .filter(|c| points[**c] == points[first])
+            .count();
+        let ideal = (group / 2).max(1);
+        let mut candidates: Vec<usize> = (1..ranking.len()).collect();
+        candidates.sort_by(|a, b| {
+            let distance = |i: usize| {
+                (
+                    (points[first] - points[ranking[i]]).abs(),
+                    (i as i64 - ideal as i64).abs(),
+                )
+            };
+            let (a, b) = (distance(*a), distance(*b));
+            a.0.total_cmp(&b.0).then(a.1.cmp(&b.1))
+        });
+        for i in candidates {
+            let opponent = ranking[i];
+            if played.contains(&(first.min(opponent), first.max(opponent))) {
+                continue;
+            }
+            let rest: Vec<usize> = ranking[1..]
+                .iter()
+                .copied()
+                .filter(|c| *c != opponent)
+                .collect();
+            if let Some(mut pairs) = Tournament::swiss_pairs(&rest, points, played) {
+                pairs.insert(0, (first, opponent));
+                return Some(pairs);
+            }
+        }
+        None
+    }
+
+    //the player who had white less often gets white, then the one who had black last
+    fn colors(&self, first: usize, second: usize) -> (usize, usize) {
+        let balance = |player: usize| -> i32 {
+            self.results(player)
+                .iter()
+                .filter(|c| c.0.is_some())
+                .map(|c| if c.1 == Color::White { 1 } else { -1 })
+                .sum()
+        };
+        let last_color = |player: usize| {
+            self.results(player)
+                .iter()
+                .rev()
+                .find(|c| c.0.is_some())
+                .map(|c| c.1)
+        };
+        let (first_balance, second_balance) = (balance(first), balance(second));
+        if first_balance != second_balance {
+            return if first_balance < second_balance {
+                (first, second)
+            } else {
+                (second, first)
+            };
+        }
+        match (last_color(first), last_color(second)) {
+            (Some(Color::White), Some(Color::Black)) => (second, first),
+            (Some(Color::Black), Some(Color::White)) => (first, second),
+            _ if self.rounds.len().is_multiple_of(2) => (first, second),
+            _ => (second, first),
+        }
+    }
+
+    //board counts from 1 in the current round
+    pub fn record_result(&mut self, board: usize, white_points: f64) -> Result<(), ChessError> {
+        let pairing = self
+            .rounds
+            .last_mut()
+            .and_then(|c| c.get_mut(board.wrapping_sub(1)))
+            .ok_or_else(|| ChessError::TournamentError(format!("no board {}", board)))?;
+        if pairing.black.is_none() {
+            return Err(ChessError::TournamentError(String::from(
+                "a bye has no result to enter",
+            )));
+        }
+        pairing.result = Some(white_points);
+        Ok(())
+    }
+
+    //engines play on their own, games with humans are played in the game loop
+    pub fn play(&mut self, board: usize) -> Result<(), ChessError> {
+        let pairing = *self
+            .current_round()
+            .and_then(|c| c.get(board.wrapping_sub(1)))
+            .ok_or_else(|| ChessError::TournamentError(format!("no board {}", board)))?;
+        let black = match (pairing.black, pairing.result) {
+            (Some(black), None) => black,
+            _ => {
+                return Err(ChessError::TournamentError(format!(
+                    "board {} is already finished",
+                    board
+                )))
+            }
+        };
+        let mut white_player = Player::from_spec(&self.players[pairing.white].spec, None)?;
+        let mut black_player = Player::from_spec(&self.players[black].spec, None)?;
+        if let Some(directory) = &self.syzygy {
+            white_player.set_tablebase(directory)?;
+            black_player.set_tablebase(directory)?;
+        }
+        println!(
+            "Round {} board {}: {} - {}",
+            self.rounds.len(),
+            board,
+            self.players[pairing.white].name,
+            self.players[black].name
+        );
+
+        let (mut game, outcome) = if white_player.is_human() || black_player.is_human() {
+            let mut game = Game::new();
+            game.set_book(self.book.clone());
+            game.set_player(Color::White, white_player);
+            game.set_player(Color::Black, black_player);
+            match game
+                .play_single()
+                .map_err(|e| ChessError::TerminalError(e.to_string()))?
+            {
+                Some(outcome) => (game.record(Some(outcome)), outcome),
+                None => {
+                    println!("Game left unfinished, the result can be entered by hand");
+                    return Ok(());
+                }
+            }
+        } else {
+            let opening = Opening {
+                fen: START_FEN.to_string(),
+                moves: Vec::new(),
+            };
+            match_runner::play_game(
+                &mut white_player,
+                &mut black_player,
+                &opening,
+                400,
+                self.book.as_ref(),
+            )?
+        };
+        println!("{} {}", outcome.result(), outcome);
+
+        game.set_tag("Event", &self.name);
+        game.set_tag("Round", &self.rounds.len().to_string());
+        game.set_tag("White", &self.players[pairing.white].name);
+        game.set_tag("Black", &self.players[black].name);
+        self.games.push(game);
+        let white_points = match outcome.winner() {
+            Some(Color::White) => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+        self.record_result(board, white_points)
+    }
+
+    //sorted by points, Buchholz and Sonneborn-Berger
+    pub fn standings(&self) -> Vec<Standing> {
+        let points: Vec<f64> = (0..self.players.len()).map(|c| self.points(c)).collect();
+        let mut standings: Vec<Standing> = (0..self.players.len())
+            .map(|player| {
+                let games: Vec<(usize, f64)> = self
+                    .results(player)
+                    .into_iter()
+                    .filter_map(|c| c.0.map(|opponent| (opponent, c.2)))
+                    .collect();
+                Standing {
+                    player,
+                    points: points[player],
+                    buchholz: games.iter().fold(0.0, |sum, c| sum + points[c.0]),
+                    sonneborn_berger: games.iter().fold(0.0, |sum, c| sum + points[c.0] * c.1),
+                }
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            b.points
+                .total_cmp(&a.points)
+                .then(b.buchholz.total_cmp(&a.buchholz))
+                .then(b.sonneborn_berger.total_cmp(&a.sonneborn_berger))
+                .then(a.player.cmp(&b.player))
+        });
+        standings
+    }
+
+    fn result_symbol(points: f64) -> &'static str {
+        if points >= 1.0 {
+            "1"
+        } else if points > 0.0 {
+            "="
+        } else {
+            "0"
+        }
+    }
+
+    //round robin as a table of all encounters, Swiss as the results of every round
+    pub fn crosstable(&self) -> String {
+        let standings = self.standings();
+        let width = self
+            .players
+            .iter()
+            .map(|c| c.name.len())
+            .max()
+            .unwrap_or(4)
+            .max(4);
+        let mut table = format!("{:>3}  {:<width$}", "#", "Name", width = width);
+        match self.format {
+            TournamentFormat::RoundRobin => {
+                for i in 1..=self.players.len() {
+                    table.push_str(&format!(" {:>3}", i));
+                }
+            }
+            TournamentFormat::Swiss(_) => {
+                for i in 1..=self.rounds.len() {
+                    table.push_str(&format!(" {:>5}", format!("R{}", i)));
+                }
+            }
+        }
+        table.push_str("   Pts  Buch    SB\n");
+
+        for standing in &standings {
+            let player = standing.player;
+            table.push_str(&format!(
+                "{:>3}  {:<width$}",
+                player + 1,
+                self.players[player].name,
+                width = width
+            ));
+            let results = self.results(player);
+            match self.format {
+                TournamentFormat::RoundRobin => {
+                    for opponent in 0..self.players.len() {
+                        let cell = if opponent == player {
+                            String::from("X")
+                        } else {
+                            results
+                                .iter()
+                                .filter(|c| c.0 == Some(opponent))
+                                .map(|c| Tournament::result_symbol(c.2))
+                                .collect::<Vec<&str>>()
+                                .join("")
+                        };
+                        table.push_str(&format!(" {:>3}", cell));
+                    }
+                }
+                TournamentFormat::Swiss(_) => {
+                    for round in &self.rounds {
+                        let cell = round
+                            .iter()
+                            .find(|c| c.white == player || c.black == Some(player))
+                            .map(|c| match (c.black, c.result) {
+                                (None, _) => String::from("bye"),
+                                (Some(_), None) => String::from("*"),
+                                (Some(black), Some(result)) => {
+                                    let (opponent, color, points) = if c.white == player {
+                                        (black, 'w', result)
+                                    } else {
+                                        (c.white, 'b', 1.0 - result)
+                                    };
+                                    let symbol = match Tournament::result_symbol(points) {
+                                        "1" => '+',
+                                        "0" => '-',
+                                        _ => '=',
+                                    };
+                                    format!("{}{}{}", symbol, opponent + 1, color)
+                                }
+                            })
+                            .unwrap_or_default();
+                        table.push_str(&format!(" {:>5}", cell));
+                    }
+                }
+            }
+            table.push_str(&format!(
+                " {:>5.1} {:>5.1} {:>5.2}\n",
+                standing.points, standing.buchholz, standing.sonneborn_berger
+            ));
+        }
+        table
+    }
+
+    pub fn export_pgn(&self, path: &str) -> Result<(), ChessError> {
+        let mut pgn = String::new();
+        for game in &self.games {
+            pgn.push_str(&game.to_pgn()?);
+        }
+        fs::write(path, pgn).map_err(|e| ChessError::FileError(e.to_string()))
+    }
+
+    fn print_round(&self) {
+        if let Some(round) = self.current_round() {
+            println!("Round {} of {}", self.rounds.len(), self.total_rounds());
+            for (i, pairing) in round.iter().enumerate() {
+                let white = &self.players[pairing.white].name;
+                match (pairing.black, pairing.result) {
+                    (None, _) => println!("Board {}: {} bye", i + 1, white),
+                    (Some(black), result) => println!(
+                        "Board {}: {} - {} {}",
+                        i + 1,
+                        white,
+                        self.players[black].name,
+                        match result.map(Tournament::result_symbol) {
+                            None => "*",
+                            Some("1") => "1-0",
+                            Some("0") => "0-1",
+                            Some(_) => "1/2-1/2",
+                        }
+                    ),
+                }
+            }
+        }
+    }
+
+    fn command(&mut self, input: &str) -> Result<(), ChessError> {
+        let usage = || {
+            ChessError::InvalidCommand(String::from(
+                "add <name> [human|builtin[:depth]|path], roundrobin, swiss <rounds>, pair, play <board|all>, result <board> <1-0|0-1|1/2-1/2>, round, standings, crosstable, book <file>, tb <dir>, export <file>, exit",
+            ))
+        };
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["add", name] => self.add_player(name, "human")?,
+            ["add", name, spec @ ..] => self.add_player(name, &spec.join(" "))?,
+            ["roundrobin"] => self.set_format(TournamentFormat::RoundRobin)?,
+            ["swiss", rounds] => self.set_format(TournamentFormat::Swiss(
+                rounds.parse().map_err(|_| usage())?,
+            ))?,
+            ["pair"] => {
+                self.pair_next_round()?;
+                self.print_round();
+            }
+            ["round"] => self.print_round(),
+            ["play", "all"] => {
+                let count = self.current_round().map(|c| c.len()).unwrap_or(0);
+                for board in 1..=count {
+                    let open = self
+                        .current_round()
+                        .map(|c| c[board - 1].result.is_none())
+                        .unwrap_or(false);
+                    if open {
+                        self.play(board)?;
+                    }
+                }
+                self.print_round();
+            }
+            ["play", board] => {
+                self.play(board.parse().map_err(|_| usage())?)?;
+                self.print_round();
+            }
+            ["result", board, result] => {
+                let white_points = match *result {
+                    "1-0" => 1.0,
+                    "0-1" => 0.0,
+                    "1/2-1/2" => 0.5,
+                    _ => return Err(usage()),
+                };
+                self.record_result(board.parse().map_err(|_| usage())?, white_points)?;
+                self.print_round();
+            }
+            ["standings"] => {
+                for (rank, standing) in self.standings().iter().enumerate() {
+                    println!(
+                        "{}. {} {:.1} (Buchholz {:.1}, Sonneborn-Berger {:.2})",
+                        rank + 1,
+                        self.players[standing.player].name,
+                        standing.points,
+                        standing.buchholz,
+                        standing.sonneborn_berger
+                    );
+                }
+            }
+            ["crosstable"] => print!("{}", self.crosstable()),
+            ["export", path] => self.export_pgn(path)?,
+            ["book", path] => self.book = Some(OpeningBook::open(path)?),
+            ["tb", directory] => {
+                let tablebase = Tablebase::open(directory)?;
+                println!(
+                    "Engines use {} tables for up to {} pieces",
+                    tablebase.table_count(),
+                    tablebase.max_pieces()
+                );
+                self.syzygy = Some(directory.to_string());
+            }
+            _ => return Err(usage()),
+        }
+        Ok(())
+    }
+
+    pub fn tournament_loop(&mut self) -> Result<(), std::io::Error> {
+        let mut user_input = String::from("");
+        println!("Tournament {}", self.name);
+        loop {
+            user_input.clear();
+            if std::io::stdin().read_line(&mut user_input)? == 0 || user_input.starts_with("exit") {
+                break;
+            }
+            if user_input.trim().is_empty() {
+                continue;
+            }
+            if let Err(e) = self.command(&user_input) {
+                println!("{}", e);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use std::mem;
+
+use crate::field::Move;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    //the score is at least this high (beta cutoff)
+    Lower,
+    //the score is at most this high (no move raised alpha)
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TtEntry {
+    pub key: u64,
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+    //search generation which stored the entry
+    age: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct TtStats {
+    pub probes: u64,
+    pub hits: u64,
+    pub stores: u64,
+    //stores which replaced an entry of another position
+    pub overwrites: u64,
+}
+
+impl TtStats {
+    pub fn hit_rate(&self) -> f64 {
+        if self.probes == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.probes as f64
+        }
+    }
+}
+
+impl fmt::Display for TtStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "probes {}, hits {} ({:.1}%), stores {}, overwrites {}",
+            self.probes,
+            self.hits,
+            self.hit_rate() * 100.0,
+            self.stores,
+            self.overwrites
+        )
+    }
+}
+
+//buckets of two entries: the first keeps the deepest search, the second is always replaced
+#[derive(Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+    age: u8,
+    stats: TtStats,
+}
+
+impl TranspositionTable {
+    pub fn new(megabytes: usize) -> TranspositionTable {
+        let count = (megabytes * 1024 * 1024 / mem::size_of::<Option<TtEntry>>()).max(2) & !1;
+        TranspositionTable {
+            entries: vec![None; count],
+            age: 0,
+            stats: TtStats::default(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|c| *c = None);
+        self.age = 0;
+        self.stats = TtStats::default();
+    }
+
+    //entries of earlier searches are replaced first
+    pub fn new_search(&mut self) {
+        self.age = self.age.wrapping_add(1);
+    }
+
+    pub fn stats(&self) -> TtStats {
+        self.stats
+    }
+
+    //used entries per thousand, like the UCI hashfull info
+    pub fn hashfull(&self) -> usize {
+        let sample = self.entries.len().min(1000);
+        self.entries[..sample]
+            .iter()
+            .filter(|c| c.is_some())
+            .count()
+            * 1000
+            / sample
+    }
+
+    fn bucket(&self, key: u64) -> usize {
+        (key % (self.entries.len() as u64 / 2)) as usize * 2
+    }
+
+    pub fn probe(&mut self, key: u64) -> Option<TtEntry> {
+        self.stats.probes += 1;
+        let bucket = self.bucket(key);
+        let entry = self.entries[bucket..bucket + 2]
+            .iter()
+            .flatten()
+            .find(|c| c.key == key)
+            .copied();
+        if entry.is_some() {
+            self.stats.hits += 1;
+        }
+        entry
+    }
+
+    pub fn store(
+        &mut self,
+        key: u64,
+        depth: u32,
+        score: i32,
+        bound: Bound,
+        best_move: Option<Move>,
+    ) {
+        let bucket = self.bucket(key);
+        let mut entry = TtEntry {
+            key,
+            depth,
+            score,
+            bound,
+            best_move,
+            age: self.age,
+        };
+        let slot = match (self.entries[bucket], self.entries[bucket + 1]) {
+            (Some(c), _) if c.key == key => bucket,
+            (_, Some(c)) if c.key == key => bucket + 1,
+            (None, _) => bucket,
+            (Some(c), _) if c.age != self.age || c.depth <= depth => bucket,
+            _ => bucket + 1,
+        };
+        match self.entries[slot] {
+            Some(old) if old.key == key => {
+                //a shallower search of the same position keeps the deeper result
+                if old.depth > depth && old.age == self.age && bound != Bound::Exact {
+                    return;
+                }
+                //keep the known best move when this search didn't find one
+                if entry.best_move.is_none() {
+                    entry.best_move = old.best_move;
+                }
+            }
+            Some(_) => self.stats.overwrites += 1,
+            None => {}
+        }
+        self.stats.stores += 1;
+        self.entries[slot] = Some(entry);
+    }
+}
@@ -0,0 +1,332 @@
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::field::{ChessError, Color, FieldType, Move};
+use crate::render::{glyph, PieceSet, DARK, LIGHT, RESET};
+use crate::square::{File, Rank, Square};
+
+const CURSOR: &str = "\x1b[48;5;75m";
+const SELECTED: &str = "\x1b[48;5;70m";
+const TARGET: &str = "\x1b[48;5;107m";
+const LAST_MOVE: &str = "\x1b[48;5;186m";
+const CHECK: &str = "\x1b[48;5;160m";
+//rows of the move list next to the board
+const MOVE_ROWS: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Enter,
+    Escape,
+    Backspace,
+    Char(char),
+}
+
+//keys of a chunk read from the terminal, arrows come as escape sequences
+pub fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(bytes);
+    let chars: Vec<char> = text.chars().collect();
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let key = match chars[i] {
+            '\x1b' if chars.get(i + 1) == Some(&'[') => {
+                let sequence: String = chars[i + 2..].iter().take(2).collect();
+                let (key, length) = match sequence.chars().next() {
+                    Some('A') => (Some(Key::Up), 3),
+                    Some('B') => (Some(Key::Down), 3),
+                    Some('C') => (Some(Key::Right), 3),
+                    Some('D') => (Some(Key::Left), 3),
+                    _ if sequence == "5~" => (Some(Key::PageUp), 4),
+                    _ if sequence == "6~" => (Some(Key::PageDown), 4),
+                    _ => (None, 3),
+                };
+                i += length;
+                if let Some(key) = key {
+                    keys.push(key);
+                }
+                continue;
+            }
+            '\x1b' => Key::Escape,
+            '\r' | '\n' => Key::Enter,
+            '\x7f' | '\x08' => Key::Backspace,
+            c => Key::Char(c),
+        };
+        keys.push(key);
+        i += 1;
+    }
+    keys
+}
+
+fn stty(arguments: &[&str]) -> Result<String, ChessError> {
+    let output = Command::new("stty")
+        .args(arguments)
+        .stdin(Stdio::inherit())
+        .output()
+        .map_err(|e| ChessError::TerminalError(e.to_string()))?;
+    if !output.status.success() {
+        return Err(ChessError::TerminalError(String::from(
+            "stdin is not a terminal",
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+//the terminal without line buffering and echo on the alternate screen,
+//the previous settings are restored when it is dropped
+pub struct Terminal {
+    saved: String,
+    keys: Receiver<Key>,
+}
+
+impl Terminal {
+    pub fn open() -> Result<Terminal, ChessError> {
+        let saved = stty(&["-g"])?;
+        let (sender, keys) = mpsc::channel();
+        //the reader stays blocked on stdin until the program ends
+        thread::spawn(move || {
+            let mut buffer = [0; 64];
+            let mut stdin = std::io::stdin();
+            while let Ok(length) = stdin.read(&mut buffer) {
+                if length == 0 {
+                    break;
+                }
+                for key in parse_keys(&buffer[..length]) {
+                    if sender.send(key).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        let terminal = Terminal { saved, keys };
+        terminal.resume()?;
+        Ok(terminal)
+    }
+
+    //None when no key was pressed in time, the clocks are redrawn then
+    pub fn key(&self, timeout: Duration) -> Option<Key> {
+        self.keys.recv_timeout(timeout).ok()
+    }
+
+    //lines are overwritten instead of clearing the screen first, which would flicker
+    pub fn draw(&self, frame: &str) {
+        print!("\x1b[H{}\x1b[J", frame.replace('\n', "\x1b[K\n"));
+        let _ = std::io::stdout().flush();
+    }
+
+    //back to the line mode, e.g. for commands printing their output
+    pub fn suspend(&self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = std::io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+
+    pub fn resume(&self) -> Result<(), ChessError> {
+        stty(&["-icanon", "-echo", "min", "1"])?;
+        print!("\x1b[?1049h\x1b[?25l");
+        let _ = std::io::stdout().flush();
+        Ok(())
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        self.suspend();
+    }
+}
+
+//everything shown on the screen
+pub struct View<'a> {
+    pub board: &'a Board,
+    pub flipped: bool,
+    pub cursor: Square,
+    pub selected: Option<Square>,
+    pub targets: Vec<Square>,
+    pub last_move: Option<Move>,
+    //moves in SAN, white and black alternating from the start position
+    pub moves: Vec<String>,
+    //full moves hidden at the end of the list when scrolled back
+    pub scroll: usize,
+    pub clocks: [Duration; 2],
+    //figures taken by white and by black with the lead
+    pub material: [String; 2],
+    pub status: String,
+    pub command: String,
+}
+
+fn clock(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+//the king in check and the figures giving it
+fn check_fields(board: &Board) -> Vec<Square> {
+    let mut fields = board.checkers();
+    if !fields.is_empty() {
+        fields.extend(board.get_king(board.active_player()));
+    }
+    fields
+}
+
+impl View<'_> {
+    fn background(&self, square: Square, check: &[Square]) -> &'static str {
+        let last = self
+            .last_move
+            .is_some_and(|m| m.from == square || m.to == square);
+        if self.cursor == square {
+            CURSOR
+        } else if self.selected == Some(square) {
+            SELECTED
+        } else if self.targets.contains(&square) {
+            TARGET
+        } else if check.contains(&square) {
+            CHECK
+        } else if last {
+            LAST_MOVE
+        } else if (square.rank().number() + square.file().number()) % 2 == 0 {
+            DARK
+        } else {
+            LIGHT
+        }
+    }
+
+    //the lines right of the board: clocks with the taken figures, then the move list
+    fn panel(&self) -> Vec<String> {
+        let active = self.board.active_player();
+        let mut lines = Vec::new();
+        for (color, time, taken) in [
+            (Color::White, self.clocks[0], &self.material[0]),
+            (Color::Black, self.clocks[1], &self.material[1]),
+        ] {
+            let marker = if color == active { ">" } else { " " };
+            lines.push(format!(
+                "{} {:<6}{}  {}",
+                marker,
+                color.to_string(),
+                clock(time),
+                taken
+            ));
+        }
+        lines.push(String::new());
+        let rows: Vec<String> = self
+            .moves
+            .chunks(2)
+            .enumerate()
+            .map(|(i, c)| format!("{:>3}. {}", i + 1, c.join(" ")))
+            .collect();
+        let end = rows.len().saturating_sub(self.scroll.min(rows.len()));
+        let start = end.saturating_sub(MOVE_ROWS);
+        lines.extend(rows[start..end].iter().cloned());
+        lines
+    }
+
+    pub fn render(&self) -> String {
+        let check = check_fields(self.board);
+        let panel = self.panel();
+        let ranks: Vec<Rank> = if self.flipped {
+            Rank::ALL.into_iter().collect()
+        } else {
+            Rank::ALL.into_iter().rev().collect()
+        };
+        let files: Vec<File> = if self.flipped {
+            File::ALL.into_iter().rev().collect()
+        } else {
+            File::ALL.into_iter().collect()
+        };
+        let mut frame = String::new();
+        if self.board.is_crazyhouse() {
+            frame.push_str(&format!("   {}\n", self.board.pocket_line(Color::Black)));
+        }
+        for (row, x) in ranks.iter().enumerate() {
+            frame.push_str(&format!(" {} ", x));
+            for y in &files {
+                let square = Square::new(*y, *x);
+                let field = self.board.get(square);
+                let mark = if field.get_type() == FieldType::None && self.targets.contains(&square)
+                {
+                    '·'
+                } else {
+                    glyph(field, PieceSet::Unicode)
+                };
+                frame.push_str(&format!(
+                    "{}\x1b[30m {} {}",
+                    self.background(square, &check),
+                    mark,
+                    RESET
+                ));
+            }
+            frame.push_str(&format!(
+                "   {}\n",
+                panel.get(row).map_or("", |c| c.as_str())
+            ));
+        }
+        frame.push_str("   ");
+        for y in &files {
+            frame.push_str(&format!(" {} ", y));
+        }
+        frame.push('\n');
+        if self.board.is_crazyhouse() {
+            frame.push_str(&format!("   {}\n", self.board.pocket_line(Color::White)));
+        }
+        frame.push_str(&format!("\n {}\n", self.status));
+        frame.push_str(
+            " arrows move, enter selects, pgup/pgdn scroll, type a move or command, flip, new, exit\n",
+        );
+        frame.push_str(&format!(" > {}", self.command));
+        frame
+    }
+}
+
+//thinking time used by each color, only the running color's clock goes on
+pub struct Clocks {
+    times: [Duration; 2],
+    running: Option<Color>,
+    since: Instant,
+}
+
+impl Clocks {
+    pub fn new() -> Clocks {
+        Clocks {
+            times: [Duration::ZERO; 2],
+            running: None,
+            since: Instant::now(),
+        }
+    }
+
+    fn index(color: Color) -> usize {
+        if color == Color::White {
+            0
+        } else {
+            1
+        }
+    }
+
+    //None stops all clocks
+    pub fn run(&mut self, color: Option<Color>) {
+        if color == self.running {
+            return;
+        }
+        if let Some(running) = self.running {
+            self.times[Clocks::index(running)] += self.since.elapsed();
+        }
+        self.running = color;
+        self.since = Instant::now();
+    }
+
+    pub fn times(&self) -> [Duration; 2] {
+        let mut times = self.times;
+        if let Some(running) = self.running {
+            times[Clocks::index(running)] += self.since.elapsed();
+        }
+        times
+    }
+}
@@ -0,0 +1,293 @@
+use std::fmt;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::board::START_FEN;
+use crate::field::{ChessError, Move};
+
+//how long the engine may take to answer anything but a search
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+//searches limited by depth or nodes have no time of their own
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(300);
+
+fn engine_error(e: Error) -> ChessError {
+    ChessError::EngineError(e.to_string())
+}
+
+//score as reported by the engine, always from the view of the side to move
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Score {
+    Centipawns(i32),
+    Mate(i32),
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Score::Centipawns(cp) => write!(f, "{:+.2}", *cp as f64 / 100.0),
+            Score::Mate(n) => write!(f, "#{}", n),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchLimit {
+    Depth(u32),
+    MoveTime(u64),
+    Nodes(u64),
+}
+
+impl SearchLimit {
+    fn to_go_command(self) -> String {
+        match self {
+            SearchLimit::Depth(n) => format!("go depth {}", n),
+            SearchLimit::MoveTime(n) => format!("go movetime {}", n),
+            SearchLimit::Nodes(n) => format!("go nodes {}", n),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct UciInfo {
+    pub depth: u32,
+    pub score: Option<Score>,
+    pub nodes: u64,
+    pub pv: Vec<Move>,
+}
+
+impl UciInfo {
+    //parses an "info ..." line, unknown tokens are skipped
+    pub fn parse(line: &str) -> UciInfo {
+        let mut info = UciInfo::default();
+        let mut tokens = line.split_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "depth" => info.depth = tokens.next().and_then(|c| c.parse().ok()).unwrap_or(0),
+                "nodes" => info.nodes = tokens.next().and_then(|c| c.parse().ok()).unwrap_or(0),
+                "score" => {
+                    let kind = tokens.next();
+                    let value = tokens.next().and_then(|c| c.parse().ok());
+                    info.score = match (kind, value) {
+                        (Some("cp"), Some(n)) => Some(Score::Centipawns(n)),
+                        (Some("mate"), Some(n)) => Some(Score::Mate(n)),
+                        _ => None,
+                    };
+                }
+                "pv" => {
+                    for c in tokens.by_ref() {
+                        match Move::from_uci(c) {
+                            Ok(m) => info.pv.push(m),
+                            Err(_) => break,
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        info
+    }
+}
+
+impl fmt::Display for UciInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "depth {}", self.depth)?;
+        if let Some(score) = self.score {
+            write!(f, " score {}", score)?;
+        }
+        write!(f, " nodes {} pv", self.nodes)?;
+        for m in &self.pv {
+            write!(f, " {}", m)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub info: Vec<UciInfo>,
+}
+
+pub struct UciEngine {
+    name: String,
+    process: Child,
+    stdin: ChildStdin,
+    //lines of the engine's output, read on their own thread so a hung engine can be given up
+    lines: Receiver<String>,
+    limit: SearchLimit,
+    chess960: bool,
+    timeout: Duration,
+}
+
+impl UciEngine {
+    pub fn new(path: &str) -> Result<UciEngine, ChessError> {
+        let mut process = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| ChessError::EngineError(format!("{}: {}", path, e)))?;
+        let stdin = process
+            .stdin
+            .take()
+            .ok_or_else(|| engine_error(ErrorKind::BrokenPipe.into()))?;
+        let stdout = process
+            .stdout
+            .take()
+            .ok_or_else(|| engine_error(ErrorKind::BrokenPipe.into()))?;
+        //the thread ends with the engine's output, the closed channel then reports it
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut engine = UciEngine {
+            name: path.to_string(),
+            process,
+            stdin,
+            lines,
+            limit: SearchLimit::MoveTime(1000),
+            chess960: false,
+            timeout: REPLY_TIMEOUT,
+        };
+
+        engine.send("uci")?;
+        let deadline = Instant::now() + engine.timeout;
+        loop {
+            let line = engine.read_line(deadline)?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.trim().to_string();
+            }
+            if line.trim() == "uciok" {
+                break;
+            }
+        }
+        engine.is_ready()?;
+        Ok(engine)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_limit(&mut self, limit: SearchLimit) {
+        self.limit = limit;
+    }
+
+    //time given to the engine for answers and on top of the move time for searches
+    #[allow(dead_code)]
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), ChessError> {
+        self.send(&format!("setoption name {} value {}", name, value))?;
+        self.is_ready()
+    }
+
+    //castling moves are written as the king taking its tower
+    pub fn set_chess960(&mut self, chess960: bool) -> Result<(), ChessError> {
+        if chess960 != self.chess960 {
+            self.chess960 = chess960;
+            self.set_option("UCI_Chess960", &chess960.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn is_ready(&mut self) -> Result<(), ChessError> {
+        self.send("isready")?;
+        let deadline = Instant::now() + self.timeout;
+        while self.read_line(deadline)?.trim() != "readyok" {}
+        Ok(())
+    }
+
+    pub fn new_game(&mut self) -> Result<(), ChessError> {
+        self.send("ucinewgame")?;
+        self.is_ready()
+    }
+
+    //moves played from the given position, the engine answers with its best move
+    pub fn go(&mut self, fen: &str, moves: &[Move]) -> Result<SearchResult, ChessError> {
+        let mut position = if fen == START_FEN {
+            String::from("position startpos")
+        } else {
+            format!("position fen {}", fen)
+        };
+        if !moves.is_empty() {
+            position.push_str(" moves");
+            for m in moves {
+                position.push_str(&format!(" {}", m));
+            }
+        }
+        self.send(&position)?;
+        self.send(&self.limit.to_go_command())?;
+        let deadline = Instant::now()
+            + match self.limit {
+                SearchLimit::MoveTime(n) => Duration::from_millis(n) + self.timeout,
+                _ => SEARCH_TIMEOUT,
+            };
+
+        let mut result = SearchResult {
+            best_move: None,
+            info: Vec::new(),
+        };
+        loop {
+            let line = self.read_line(deadline)?;
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                //"info string ..." and currmove updates carry no search result
+                Some("info") if line.contains(" pv ") || line.contains(" score ") => {
+                    result.info.push(UciInfo::parse(&line));
+                }
+                Some("bestmove") => {
+                    //"(none)" or "0000" means there is no legal move
+                    result.best_move = tokens.next().and_then(|c| Move::from_uci(c).ok());
+                    return Ok(result);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), ChessError> {
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(engine_error)
+    }
+
+    fn read_line(&mut self, deadline: Instant) -> Result<String, ChessError> {
+        let wait = deadline.saturating_duration_since(Instant::now());
+        match self.lines.recv_timeout(wait) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => Err(ChessError::EngineError(format!(
+                "{} didn't answer in time",
+                self.name
+            ))),
+            Err(RecvTimeoutError::Disconnected) => Err(ChessError::EngineError(format!(
+                "{} closed the connection",
+                self.name
+            ))),
+        }
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        //give the engine some time to exit on its own
+        for _ in 0..50 {
+            if let Ok(Some(_)) = self.process.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
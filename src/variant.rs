@@ -0,0 +1,323 @@
+use std::fmt;
+
+use crate::board::{Board, Undo, START_FEN};
+use crate::field::{ChessError, Color, Field, FieldType, Move, Outcome};
+use crate::square::{File, Rank, Square};
+
+//fields the king has to reach in King of the Hill
+const CENTER: [Square; 4] = [
+    Square::new(File::D, Rank::Fourth),
+    Square::new(File::E, Rank::Fourth),
+    Square::new(File::D, Rank::Fifth),
+    Square::new(File::E, Rank::Fifth),
+];
+const CHECKS_TO_WIN: usize = 3;
+
+//what Variant::make_move changed, Variant::unmake_move puts it back
+#[derive(Clone, Debug)]
+pub struct VariantUndo {
+    undo: Undo,
+    //the fields around an atomic capture before the explosion
+    exploded: Vec<(Square, Field)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Standard,
+    KingOfTheHill,
+    ThreeCheck,
+    Antichess,
+    Atomic,
+    Horde,
+    RacingKings,
+    Crazyhouse,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 8] = [
+        Variant::Standard,
+        Variant::KingOfTheHill,
+        Variant::ThreeCheck,
+        Variant::Antichess,
+        Variant::Atomic,
+        Variant::Horde,
+        Variant::RacingKings,
+        Variant::Crazyhouse,
+    ];
+
+    //the name as written in the PGN Variant tag or without spaces and dashes
+    pub fn from_name(name: &str) -> Option<Variant> {
+        let simple = |c: &str| c.to_lowercase().replace([' ', '-'], "");
+        Variant::ALL
+            .into_iter()
+            .find(|c| simple(&c.to_string()) == simple(name))
+    }
+
+    pub fn start_fen(self) -> &'static str {
+        match self {
+            Variant::Antichess => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+            Variant::Horde => {
+                "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+            }
+            Variant::RacingKings => "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1",
+            //empty pockets
+            Variant::Crazyhouse => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+            _ => START_FEN,
+        }
+    }
+
+    pub fn start_board(self) -> Result<Board, ChessError> {
+        let mut board = Board::new();
+        board.set_board_fen_unchecked(self.start_fen())?;
+        Ok(board)
+    }
+
+    pub fn legal_moves(self, board: &Board) -> Vec<Move> {
+        let color = board.active_player();
+        match self {
+            //the board knows the drops from its pockets
+            Variant::Standard
+            | Variant::KingOfTheHill
+            | Variant::ThreeCheck
+            | Variant::Crazyhouse => board.legal_moves(),
+            //kings are ordinary figures which can be taken, taking is compulsory
+            Variant::Antichess => {
+                let mut moves = board.pseudo_legal_moves();
+                let kings: Vec<Move> = moves
+                    .iter()
+                    .filter(|c| c.promotion == Some(FieldType::Queen))
+                    .map(|c| Move {
+                        promotion: Some(FieldType::King),
+                        ..*c
+                    })
+                    .collect();
+                moves.extend(kings);
+                if moves.iter().any(|m| m.flags.capture) {
+                    moves.retain(|m| m.flags.capture);
+                }
+                moves
+            }
+            //kings can't take, a move which explodes the enemy king wins even into check,
+            //kings next to each other can't give check
+            Variant::Atomic => board
+                .pseudo_legal_moves()
+                .into_iter()
+                .filter(|m| {
+                    let figure = board.get(m.from).get_type();
+                    if figure == FieldType::King && m.flags.capture {
+                        return false;
+                    }
+                    let mut next = *board;
+                    Variant::Atomic.make_move(&mut next, *m);
+                    match (
+                        Variant::king(&next, color),
+                        Variant::king(&next, color.enemy_color()),
+                    ) {
+                        (None, _) => false,
+                        (Some(_), None) => true,
+                        (Some(own), Some(enemy)) => {
+                            let ranks = own.rank().number() - enemy.rank().number();
+                            let files = own.file().number() - enemy.file().number();
+                            (ranks.abs() <= 1 && files.abs() <= 1) || !next.is_in_check(color)
+                        }
+                    }
+                })
+                .collect(),
+            //the horde has no king, its pawns on the first row can move two fields
+            Variant::Horde => {
+                let mut moves = board.legal_moves();
+                if color == Color::White {
+                    for file in File::ALL {
+                        let from = Square::new(file, Rank::First);
+                        let pawn = board.get(from);
+                        if pawn.get_type() == FieldType::Pawn
+                            && pawn.get_color() == Color::White
+                            && board.get(Square::new(file, Rank::Second)).get_type()
+                                == FieldType::None
+                            && board.get(Square::new(file, Rank::Third)).get_type()
+                                == FieldType::None
+                        {
+                            moves.push(Move::new(from, Square::new(file, Rank::Third)));
+                        }
+                    }
+                }
+                moves
+            }
+            //nobody may give check
+            Variant::RacingKings => board
+                .legal_moves()
+                .into_iter()
+                .filter(|m| {
+                    let mut next = *board;
+                    next.make_move(*m);
+                    !next.is_in_check(color.enemy_color())
+                })
+                .collect(),
+        }
+    }
+
+    fn king(board: &Board, color: Color) -> Option<Square> {
+        board.get_king(color)
+    }
+
+    //plays a move the variant allows, atomic captures explode the figures around them,
+    //returns the move in words and what is needed to take it back
+    pub fn make_move(self, board: &mut Board, next_move: Move) -> (String, VariantUndo) {
+        let capture = board.annotate(next_move).flags.capture;
+        let horde_pawn = board.get(next_move.from).get_type() == FieldType::Pawn
+            && next_move.from.rank() == Rank::First;
+        let mut exploded = Vec::new();
+        if self == Variant::Atomic && capture {
+            for step_x in -1..2 {
+                for step_y in -1..2 {
+                    if let Some(square) = next_move.to.offset(step_x, step_y) {
+                        exploded.push((square, board.get(square)));
+                    }
+                }
+            }
+        }
+        let undo = board.make_move(next_move);
+        let response = board.describe_move(&undo);
+        if !exploded.is_empty() {
+            board.remove_figure(next_move.to);
+            for (square, _) in &exploded {
+                if board.get(*square).get_type() != FieldType::Pawn {
+                    board.remove_figure(*square);
+                }
+            }
+        }
+        //the pawn passed no field which could be taken en passant
+        if self == Variant::Horde && horde_pawn {
+            board.clear_en_passant();
+        }
+        (response, VariantUndo { undo, exploded })
+    }
+
+    //takes back the last move made with make_move
+    pub fn unmake_move(self, board: &mut Board, undo: VariantUndo) {
+        for (square, field) in undo.exploded {
+            match field.get_type() {
+                FieldType::None => board.remove_figure(square),
+                figure_type => board.set_figure(square, figure_type, field.get_color()),
+            }
+        }
+        board.unmake_move(undo.undo);
+    }
+
+    //end of the game by the variant's rules, checked holds the color in check
+    //for every position before
+    pub fn outcome(
+        self,
+        board: &Board,
+        checked: impl IntoIterator<Item = Color>,
+    ) -> Option<Outcome> {
+        let color = board.active_player();
+        match self {
+            Variant::Standard => board.outcome(),
+            Variant::KingOfTheHill => {
+                let moved = color.enemy_color();
+                if Variant::king(board, moved).is_some_and(|c| CENTER.contains(&c)) {
+                    return Some(Outcome::KingOfTheHill(moved));
+                }
+                board.outcome()
+            }
+            Variant::ThreeCheck => {
+                let mut checked: Vec<Color> = checked.into_iter().collect();
+                if board.is_in_check(color) {
+                    checked.push(color);
+                }
+                for checking in [Color::White, Color::Black] {
+                    let checks = checked
+                        .iter()
+                        .filter(|c| **c == checking.enemy_color())
+                        .count();
+                    if checks >= CHECKS_TO_WIN {
+                        return Some(Outcome::ThreeChecks(checking));
+                    }
+                }
+                board.outcome()
+            }
+            Variant::Antichess => {
+                if self.legal_moves(board).is_empty() {
+                    return Some(Outcome::NoMovesLeft(color));
+                }
+                Variant::fifty_moves(board)
+            }
+            Variant::Atomic => {
+                for loser in [Color::White, Color::Black] {
+                    if Variant::king(board, loser).is_none() {
+                        return Some(Outcome::KingExploded(loser.enemy_color()));
+                    }
+                }
+                self.no_moves(board).or_else(|| Variant::fifty_moves(board))
+            }
+            Variant::Horde => {
+                let horde_left = board
+                    .fields()
+                    .any(|(_, field)| field.get_color() == Color::White);
+                if !horde_left {
+                    return Some(Outcome::AllFiguresTaken(Color::Black));
+                }
+                self.no_moves(board).or_else(|| Variant::fifty_moves(board))
+            }
+            Variant::RacingKings => {
+                let home =
+                    |c: Color| Variant::king(board, c).is_some_and(|k| k.rank() == Rank::Eighth);
+                match (home(Color::White), home(Color::Black)) {
+                    (true, true) => Some(Outcome::RaceDrawn),
+                    (false, true) => Some(Outcome::RaceWon(Color::Black)),
+                    //black gets one more move to reach the last row as well
+                    (true, false) => {
+                        let black_reaches = color == Color::Black
+                            && self.legal_moves(board).into_iter().any(|m| {
+                                board.get(m.from).get_type() == FieldType::King
+                                    && m.to.rank() == Rank::Eighth
+                            });
+                        if black_reaches {
+                            None
+                        } else {
+                            Some(Outcome::RaceWon(Color::White))
+                        }
+                    }
+                    (false, false) => self.no_moves(board).or_else(|| Variant::fifty_moves(board)),
+                }
+            }
+            //material never runs out
+            Variant::Crazyhouse => self.no_moves(board),
+        }
+    }
+
+    fn no_moves(self, board: &Board) -> Option<Outcome> {
+        if !self.legal_moves(board).is_empty() {
+            return None;
+        }
+        let color = board.active_player();
+        if board.is_in_check(color) {
+            Some(Outcome::Checkmate(color.enemy_color()))
+        } else {
+            Some(Outcome::Stalemate)
+        }
+    }
+
+    fn fifty_moves(board: &Board) -> Option<Outcome> {
+        if board.halfmove_clock() >= 100 {
+            return Some(Outcome::FiftyMoveRule);
+        }
+        None
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Variant::Standard => write!(f, "Standard"),
+            Variant::KingOfTheHill => write!(f, "King of the Hill"),
+            Variant::ThreeCheck => write!(f, "Three-check"),
+            Variant::Antichess => write!(f, "Antichess"),
+            Variant::Atomic => write!(f, "Atomic"),
+            Variant::Horde => write!(f, "Horde"),
+            Variant::RacingKings => write!(f, "Racing Kings"),
+            Variant::Crazyhouse => write!(f, "Crazyhouse"),
+        }
+    }
+}